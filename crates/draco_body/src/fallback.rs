@@ -1,4 +1,4 @@
-use draco_brain::stage_a::StageA;
+use draco_brain::shared::SharedEngine;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
 use windows::Win32::Foundation::*;
@@ -10,7 +10,7 @@ static mut HOOK_HANDLE: HHOOK = HHOOK(0);
 
 /// Estado global compartilhado com o hook de teclado.
 /// Inicializado pelo TIP quando o fallback é ativado.
-static FALLBACK_ENGINE: OnceLock<Arc<Mutex<StageA>>> = OnceLock::new();
+static FALLBACK_ENGINE: OnceLock<Arc<SharedEngine>> = OnceLock::new();
 
 /// Buffer de composição do fallback (palavras sendo digitadas fora do TSF).
 static FALLBACK_BUFFER: OnceLock<Arc<Mutex<String>>> = OnceLock::new();
@@ -24,7 +24,7 @@ impl FallbackManager {
 
     /// Inicializa o estado compartilhado do fallback com o engine de correção.
     /// Deve ser chamado pelo TIP antes de ativar o hook.
-    pub fn init(engine: Arc<Mutex<StageA>>) {
+    pub fn init(engine: Arc<SharedEngine>) {
         let _ = FALLBACK_ENGINE.set(engine);
         let _ = FALLBACK_BUFFER.set(Arc::new(Mutex::new(String::new())));
     }
@@ -183,11 +183,7 @@ impl FallbackManager {
                         if !word.is_empty() {
                             let agg = crate::ipc::AGGRESSIVENESS.load(Ordering::SeqCst);
 
-                            let corrected = if let Ok(engine) = engine_arc.lock() {
-                                engine.correct(&word, agg)
-                            } else {
-                                word.clone()
-                            };
+                            let corrected = engine_arc.correct(&word, agg);
 
                             // Se a palavra foi corrigida, substituir via SendInput:
                             // apaga os chars digitados + envia a correção
@@ -1,3 +1,6 @@
+use draco_brain::builder::Resource;
+use draco_brain::paths::PathResolver;
+use draco_brain::EngineBuilder;
 use std::path::PathBuf;
 use windows::core::GUID;
 
@@ -9,66 +12,23 @@ pub const GUID_PROFILE: GUID = GUID::from_u128(0xD0E1F203_1192_4392_83B3_2B30193
 
 pub const LANGID_PTBR: u16 = 0x0416; // Português (Brasil)
 
-/// Resolve o caminho do dicionário dinamicamente, baseado na localização da DLL.
-/// Procura `data/dictionary_pt_br.txt` relativo ao diretório da DLL.
-/// Se não encontrar, tenta caminhos fallback conhecidos.
-pub fn resolve_dict_path() -> PathBuf {
-    // Primeiro: tenta relativo à DLL
-    if let Some(dll_dir) = get_dll_directory() {
-        let relative = dll_dir
-            .join("..")
-            .join("..")
-            .join("data")
-            .join("dictionary_pt_br.txt");
-        if relative.exists() {
-            return relative;
-        }
-        // Tenta diretamente no diretório pai
-        let parent = dll_dir.join("data").join("dictionary_pt_br.txt");
-        if parent.exists() {
-            return parent;
-        }
+/// Resolvedor de dados da DLL: a busca "junto ao executável" usa o
+/// diretório da DLL, não o do aplicativo que a carregou.
+pub fn data_resolver() -> PathResolver {
+    let resolver = PathResolver::from_environment();
+    match get_dll_directory() {
+        Some(dir) => resolver.with_exe_dir(dir),
+        None => resolver,
     }
-
-    // Fallback: caminhos conhecidos
-    let fallbacks = [
-        r"c:\System-wide AI-assisted Input Method for Portuguese (PT-BR IME)\data\dictionary_pt_br.txt",
-        r"c:\Users\fabia\OneDrive\Documentos\System-wide AI-assisted Input Method for Portuguese (PT-BR IME)\data\dictionary_pt_br.txt",
-    ];
-
-    for path in &fallbacks {
-        let p = PathBuf::from(path);
-        if p.exists() {
-            return p;
-        }
-    }
-
-    // Último recurso
-    PathBuf::from(fallbacks[0])
 }
 
-/// Resolve o caminho do diretório de modelos ONNX dinamicamente.
-pub fn resolve_model_dir() -> PathBuf {
-    if let Some(dll_dir) = get_dll_directory() {
-        let relative = dll_dir.join("..").join("..").join("data").join("models");
-        if relative.exists() {
-            return relative;
-        }
-    }
-
-    let fallbacks = [
-        r"c:\System-wide AI-assisted Input Method for Portuguese (PT-BR IME)\data\models",
-        r"c:\Users\fabia\OneDrive\Documentos\System-wide AI-assisted Input Method for Portuguese (PT-BR IME)\data\models",
-    ];
-
-    for path in &fallbacks {
-        let p = PathBuf::from(path);
-        if p.exists() {
-            return p;
-        }
-    }
-
-    PathBuf::from(fallbacks[0])
+/// Resolve o diretório de modelos ONNX.
+pub fn resolve_model_dir() -> Option<PathBuf> {
+    let builder = EngineBuilder::with_resolver(data_resolver()).ok()?;
+    builder
+        .resolve(Resource::Models)
+        .path()
+        .map(|p| p.to_path_buf())
 }
 
 /// Obtém o diretório da DLL em execução.
@@ -12,11 +12,13 @@ pub enum IpcCommand {
     SetEnabled(bool),
     SetAggressiveness(u32),
     AddCustomWord(String),
+    /// Liga/desliga uma camada de dicionário (ex.: um pacote de domínio)
+    SetLayerEnabled(String, bool),
+    /// Inclui um termo no glossário: forma canônica (com a caixa exata) e variantes
+    AddGlossaryTerm(String, Vec<String>),
 }
 
-pub async fn start_ipc_server(
-    engine: std::sync::Arc<std::sync::Mutex<draco_brain::stage_a::StageA>>,
-) {
+pub async fn start_ipc_server(engine: std::sync::Arc<draco_brain::shared::SharedEngine>) {
     if STARTED.swap(true, Ordering::SeqCst) {
         return;
     }
@@ -40,8 +42,17 @@ pub async fn start_ipc_server(
                                 AGGRESSIVENESS.store(val, Ordering::SeqCst);
                             }
                             IpcCommand::AddCustomWord(word) => {
-                                let mut engine = engine.lock().unwrap();
-                                engine.load_dictionary(&[&word]);
+                                engine.update(|e| e.add_user_word(&word));
+                            }
+                            IpcCommand::SetLayerEnabled(name, enabled) => {
+                                engine.update(|e| e.set_layer_enabled(&name, enabled));
+                            }
+                            IpcCommand::AddGlossaryTerm(canonical, variants) => {
+                                let entry = draco_brain::glossary::GlossaryEntry {
+                                    canonical,
+                                    variants,
+                                };
+                                engine.update(|e| e.add_glossary_entries(&[entry]));
                             }
                         }
                     }
@@ -1,5 +1,5 @@
 use crate::composition::CompositionManager;
-use draco_brain::stage_a::StageA;
+use draco_brain::shared::SharedEngine;
 use std::sync::{Arc, Mutex};
 use windows::core::*;
 use windows::Win32::Foundation::*;
@@ -8,13 +8,13 @@ use windows::Win32::UI::TextServices::*;
 
 #[implement(ITfKeyEventSink)]
 pub struct PtBrKeyEventSink {
-    engine: Arc<Mutex<StageA>>,
+    engine: Arc<SharedEngine>,
     composition: Arc<Mutex<CompositionManager>>,
     _client_id: u32,
 }
 
 impl PtBrKeyEventSink {
-    pub fn new(engine: Arc<Mutex<StageA>>, client_id: u32) -> Self {
+    pub fn new(engine: Arc<SharedEngine>, client_id: u32) -> Self {
         Self {
             engine,
             composition: Arc::new(Mutex::new(CompositionManager::new())),
@@ -77,9 +77,7 @@ impl ITfKeyEventSink_Impl for PtBrKeyEventSink {
             let word = comp.get_buffer().to_string();
             if !word.is_empty() {
                 let agg = crate::ipc::AGGRESSIVENESS.load(std::sync::atomic::Ordering::SeqCst);
-                let engine = self.engine.lock().unwrap();
-                let corrected = engine.correct(&word, agg);
-                drop(engine);
+                let corrected = self.engine.correct(&word, agg);
                 comp.clear();
 
                 if corrected != word {
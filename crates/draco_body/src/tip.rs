@@ -2,22 +2,27 @@ use windows::core::*;
 use windows::Win32::Foundation::*;
 use windows::Win32::UI::TextServices::*;
 
-use draco_brain::stage_a::StageA;
+use draco_brain::shared::SharedEngine;
+use draco_brain::watcher::{DataWatcher, DEFAULT_POLL_INTERVAL};
+use draco_brain::EngineBuilder;
 use std::sync::{Arc, Mutex};
 
 #[implement(ITfTextInputProcessor, ITfTextInputProcessorEx)]
 pub struct PtBrTip {
-    engine: Arc<Mutex<StageA>>,
+    engine: Arc<SharedEngine>,
     tid: Mutex<Option<u32>>,
     thread_mgr: Mutex<Option<ITfThreadMgr>>,
+    /// Recarrega dicionário e regras quando os arquivos mudam
+    watcher: Mutex<Option<DataWatcher>>,
 }
 
 impl PtBrTip {
     pub fn new() -> Self {
         Self {
-            engine: Arc::new(Mutex::new(StageA::new())),
+            engine: Arc::new(SharedEngine::default()),
             tid: Mutex::new(None),
             thread_mgr: Mutex::new(None),
+            watcher: Mutex::new(None),
         }
     }
 }
@@ -30,6 +35,8 @@ impl ITfTextInputProcessor_Impl for PtBrTip {
     fn Deactivate(&self) -> Result<()> {
         let tid = self.tid.lock().unwrap().take();
         let thread_mgr = self.thread_mgr.lock().unwrap().take();
+        // Parar o vigia de dados (a thread termina no drop)
+        drop(self.watcher.lock().unwrap().take());
 
         if let (Some(tid), Some(tm)) = (tid, thread_mgr) {
             unsafe {
@@ -61,19 +68,35 @@ impl ITfTextInputProcessorEx_Impl for PtBrTip {
             });
         }
 
-        // Carregar dicionário usando caminho dinâmico
-        let mut engine = self.engine.lock().unwrap();
-        let dict_path = crate::globals::resolve_dict_path();
-
-        // Log para debug
-        if dict_path.exists() {
-            if let Ok(words) =
-                draco_brain::dict_loader::load_from_file(dict_path.to_str().unwrap_or(""))
-            {
-                engine.load_dictionary_strings(&words);
+        // Carregar frequências + dicionário (mesma carga do draco_head); o
+        // engine pronto é publicado de uma vez, sem bloquear quem já está corrigindo
+        let builder = match EngineBuilder::with_resolver(crate::globals::data_resolver()) {
+            Ok(builder) => Some(builder.keystroke_defaults()),
+            Err(e) => {
+                eprintln!("[TIP] Erro: {}", e);
+                None
+            }
+        };
+        if let Some(builder) = builder {
+            match builder.build() {
+                Ok((engine, report)) => {
+                    for line in report.to_string().lines() {
+                        eprintln!("[TIP] {}", line);
+                    }
+                    self.engine.replace(engine);
+                }
+                Err(e) => eprintln!("[TIP] Erro: {}", e),
             }
+            // Edições nos arquivos de dados entram sem reativar o IME; arquivos
+            // quebrados são rejeitados (e logados) e o engine atual continua em uso
+            let watcher = DataWatcher::spawn(
+                builder,
+                self.engine.clone(),
+                DEFAULT_POLL_INTERVAL,
+                |event| eprintln!("[TIP] {}", event),
+            );
+            *self.watcher.lock().unwrap() = Some(watcher);
         }
-        drop(engine);
 
         // Inicializar o Fallback com o engine compartilhado
         // (para apps que não suportam TSF — jogos, apps Java, etc.)
@@ -0,0 +1,273 @@
+//! Compara o trie em arena com o desenho anterior (um `HashMap` por nó)
+//! carregando o dicionário real: memória alocada na carga e latência de
+//! `contains`/`get_suggestions`.
+//!
+//! `cargo bench -p draco_brain --bench trie_arena`
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use draco_brain::trie::Trie;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// Alocador que contabiliza bytes vivos e número de alocações.
+struct Counting;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LIVE_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Cópia fiel do trie anterior (busca inclusive), mantida só como
+/// referência de comparação.
+#[derive(Default)]
+struct LegacyNode {
+    is_end_of_word: bool,
+    frequency: u32,
+    children: HashMap<char, LegacyNode>,
+}
+
+#[derive(Default)]
+struct LegacyTrie {
+    root: LegacyNode,
+}
+
+impl LegacyTrie {
+    fn insert_with_frequency(&mut self, word: &str, frequency: u32) {
+        let mut node = &mut self.root;
+        for c in word.chars() {
+            node = node.children.entry(c).or_default();
+        }
+        node.is_end_of_word = true;
+        if frequency > node.frequency {
+            node.frequency = frequency;
+        }
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        let mut node = &self.root;
+        for c in word.chars() {
+            match node.children.get(&c) {
+                Some(n) => node = n,
+                None => return false,
+            }
+        }
+        node.is_end_of_word
+    }
+
+    /// Retorna sugestões de palavras com distância de Levenshtein <= max_distance.
+    /// Ordenadas por: (distância crescente, frequência decrescente).
+    /// Isso garante que palavras COMUNS sejam preferidas quando há empate de distância.
+    pub fn get_suggestions(&self, word: &str, max_distance: usize) -> Vec<(String, usize, u32)> {
+        let mut suggestions = Vec::new();
+        let word_chars: Vec<char> = word.chars().collect();
+        let current_row: Vec<usize> = (0..=word_chars.len()).collect();
+
+        for (&c, child) in &self.root.children {
+            self.search_recursive(
+                child,
+                c,
+                &word_chars,
+                &current_row,
+                &mut String::new(),
+                max_distance,
+                &mut suggestions,
+            );
+        }
+
+        // Ordenar por: distância crescente, depois frequência decrescente
+        suggestions.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| b.2.cmp(&a.2)));
+        suggestions
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_recursive(
+        &self,
+        node: &LegacyNode,
+        letter: char,
+        word_chars: &[char],
+        previous_row: &[usize],
+        current_word: &mut String,
+        max_distance: usize,
+        suggestions: &mut Vec<(String, usize, u32)>,
+    ) {
+        current_word.push(letter);
+        let columns = word_chars.len() + 1;
+        let mut current_row = vec![0usize; columns];
+        current_row[0] = previous_row[0] + 1;
+
+        for i in 1..columns {
+            let insert_cost = current_row[i - 1] + 1;
+            let delete_cost = previous_row[i] + 1;
+            let replace_cost = if word_chars[i - 1] == letter {
+                previous_row[i - 1]
+            } else {
+                previous_row[i - 1] + 1
+            };
+
+            current_row[i] = insert_cost.min(delete_cost).min(replace_cost);
+        }
+
+        if current_row[columns - 1] <= max_distance && node.is_end_of_word {
+            suggestions.push((
+                current_word.clone(),
+                current_row[columns - 1],
+                node.frequency,
+            ));
+        }
+
+        // Poda: só continua se ainda há chance de encontrar uma palavra dentro do limite
+        if *current_row.iter().min().unwrap() <= max_distance {
+            for (&c, child) in &node.children {
+                self.search_recursive(
+                    child,
+                    c,
+                    word_chars,
+                    &current_row,
+                    current_word,
+                    max_distance,
+                    suggestions,
+                );
+            }
+        }
+
+        current_word.pop();
+    }
+}
+
+fn data_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../data")
+}
+
+/// Mede bytes vivos, alocações e tempo para construir uma estrutura.
+fn measure<T>(label: &str, build: impl FnOnce() -> T) -> T {
+    let bytes_before = LIVE_BYTES.load(Ordering::Relaxed);
+    let allocs_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let value = build();
+    let elapsed = start.elapsed();
+    let bytes = LIVE_BYTES.load(Ordering::Relaxed) - bytes_before;
+    let allocs = ALLOCATIONS.load(Ordering::Relaxed) - allocs_before;
+    println!(
+        "{:<8} {:>10.1} MB vivos  {:>10} alocações  carga em {:?}",
+        label,
+        bytes as f64 / (1024.0 * 1024.0),
+        allocs,
+        elapsed
+    );
+    value
+}
+
+const QUERIES: &[&str] = &[
+    "cassa",
+    "computaodr",
+    "trabalo",
+    "voce",
+    "exeção",
+    "brasilero",
+];
+
+fn bench_trie(c: &mut Criterion) {
+    let data = data_dir();
+    let words = match draco_brain::dict_loader::load_from_file(data.join("dictionary_pt_br.txt")) {
+        Ok(words) => words,
+        Err(e) => {
+            eprintln!("dicionário real indisponível em {:?}: {}", data, e);
+            return;
+        }
+    };
+    let frequency: HashMap<String, u32> =
+        draco_brain::dict_loader::load_frequency_file(data.join("frequency_pt_br.txt"))
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+    let freq = |w: &str| frequency.get(w).copied().unwrap_or(0);
+
+    println!("{} palavras", words.len());
+    let legacy = measure("hashmap", || {
+        let mut trie = LegacyTrie::default();
+        for w in &words {
+            trie.insert_with_frequency(w, freq(w));
+        }
+        trie
+    });
+    let arena = measure("arena", || {
+        let mut trie = Trie::new();
+        for w in &words {
+            trie.insert_with_frequency(w, freq(w));
+        }
+        trie.shrink_to_fit();
+        trie
+    });
+    println!("{} nós na arena", arena.node_count());
+
+    // Os dois desenhos devem concordar (a ordem de empates pode diferir)
+    for q in QUERIES {
+        let mut a = arena.get_suggestions(q, 2);
+        let mut b = legacy.get_suggestions(q, 2);
+        a.sort();
+        b.sort();
+        assert_eq!(a, b, "sugestões divergentes para {}", q);
+    }
+
+    let mut group = c.benchmark_group("Trie (dicionário real)");
+    group.bench_function("contains hashmap", |b| {
+        b.iter(|| {
+            QUERIES
+                .iter()
+                .filter(|q| legacy.contains(black_box(q)))
+                .count()
+        })
+    });
+    group.bench_function("contains arena", |b| {
+        b.iter(|| {
+            QUERIES
+                .iter()
+                .filter(|q| arena.contains(black_box(q)))
+                .count()
+        })
+    });
+    for distance in [1, 2] {
+        group.bench_function(format!("sugestões d{} hashmap", distance), |b| {
+            b.iter(|| {
+                for q in QUERIES {
+                    black_box(legacy.get_suggestions(black_box(q), distance));
+                }
+            })
+        });
+        group.bench_function(format!("sugestões d{} arena", distance), |b| {
+            b.iter(|| {
+                for q in QUERIES {
+                    black_box(arena.get_suggestions(black_box(q), distance));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_trie);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+//! Com a feature `embedded-data`, gera em `OUT_DIR` o dicionário e a tabela
+//! de frequências compactados (ver `src/front_coding.rs`), que o módulo
+//! `embedded` inclui no binário.
+
+// `decode` fica para o módulo `embedded`
+#[allow(dead_code)]
+#[path = "src/front_coding.rs"]
+mod front_coding;
+
+use std::path::{Path, PathBuf};
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=DRACO_EMBED_DATA_DIR");
+    if std::env::var_os("CARGO_FEATURE_EMBEDDED_DATA").is_none() {
+        return;
+    }
+
+    // Padrão: o `data/` da raiz do repositório
+    let data_dir = std::env::var_os("DRACO_EMBED_DATA_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("../../data"));
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").expect("OUT_DIR"));
+
+    // Dicionário: a ordem não importa, então ordena para comprimir melhor
+    let text = read(&data_dir.join("dictionary_pt_br.txt"));
+    let mut words: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|w| !w.is_empty())
+        .collect();
+    words.sort_unstable();
+    words.dedup();
+    write(&out_dir.join("dictionary.fc"), front_coding::encode(words));
+
+    // Frequências: a posição é o rank, então mantém a ordem e descarta as contagens
+    let text = read(&data_dir.join("frequency_pt_br.txt"));
+    let words = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .filter_map(|l| l.split(' ').next());
+    write(&out_dir.join("frequency.fc"), front_coding::encode(words));
+}
+
+fn read(path: &Path) -> String {
+    println!("cargo:rerun-if-changed={}", path.display());
+    std::fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!(
+            "embedded-data: falha ao ler {} ({}); defina DRACO_EMBED_DATA_DIR",
+            path.display(),
+            e
+        )
+    })
+}
+
+fn write(path: &Path, data: Vec<u8>) {
+    std::fs::write(path, data)
+        .unwrap_or_else(|e| panic!("embedded-data: falha ao gravar {}: {}", path.display(), e));
+}
@@ -0,0 +1,121 @@
+use crate::typo_model::RuleError;
+use std::collections::{HashMap, HashSet};
+
+/// Uma linha do arquivo de bloqueios.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockEntry {
+    /// Palavra que nunca é produzida como correção automática
+    Word(String),
+    /// Correção específica suprimida (`de` → `para`)
+    Pair { from: String, to: String },
+}
+
+/// Lê uma lista de bloqueios no formato TSV:
+///
+/// ```text
+/// # palavra que nunca deve ser sugerida
+/// palavra
+/// # correção proibida: erro<TAB>correção
+/// erro<TAB>correção
+/// ```
+///
+/// Tudo é comparado em minúsculas. Como nas regras do `TypoModel`, o
+/// arquivo inteiro é rejeitado no primeiro erro.
+pub fn parse_blocklist(text: &str) -> Result<Vec<BlockEntry>, RuleError> {
+    let mut entries = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: &str| RuleError {
+            line: i + 1,
+            message: message.to_string(),
+        };
+        let line = line.trim_start_matches('\u{feff}').trim_end_matches('\r');
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split('\t').map(|f| f.trim().to_lowercase());
+        let entry = match (fields.next(), fields.next(), fields.next()) {
+            (Some(word), None, _) => BlockEntry::Word(word),
+            (Some(from), Some(to), None) => {
+                if from.is_empty() || to.is_empty() {
+                    return Err(error("erro ou correção vazios"));
+                }
+                if from == to {
+                    return Err(error("erro e correção iguais"));
+                }
+                BlockEntry::Pair { from, to }
+            }
+            _ => return Err(error("esperado `palavra` ou `erro<TAB>correção`")),
+        };
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Correções que o [`StageA`](crate::stage_a::StageA) nunca pode produzir:
+/// palavras proibidas como resultado (qualquer que seja a entrada) e pares
+/// (entrada, correção) suprimidos.
+///
+/// Vale para todos os caminhos automáticos (`TypoModel`, upgrade de
+/// frequência, transposição, fonética e fuzzy): um candidato bloqueado é
+/// descartado e a etapa segue para o próximo. A palavra digitada nunca é
+/// bloqueada (se o usuário a escreveu, ela fica), e o glossário da equipe
+/// não passa pela lista, já que suas grafias são escolhidas a dedo.
+#[derive(Debug, Clone, Default)]
+pub struct Blocklist {
+    words: HashSet<String>,
+    /// Entrada → correções proibidas para ela
+    pairs: HashMap<String, HashSet<String>>,
+}
+
+impl Blocklist {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_entries(entries: &[BlockEntry]) -> Self {
+        let mut blocklist = Self::new();
+        blocklist.extend(entries);
+        blocklist
+    }
+
+    pub fn extend(&mut self, entries: &[BlockEntry]) {
+        for entry in entries {
+            match entry {
+                BlockEntry::Word(word) => self.block_word(word),
+                BlockEntry::Pair { from, to } => self.block_pair(from, to),
+            }
+        }
+    }
+
+    pub fn block_word(&mut self, word: &str) {
+        self.words.insert(word.to_lowercase());
+    }
+
+    pub fn block_pair(&mut self, from: &str, to: &str) {
+        self.pairs
+            .entry(from.to_lowercase())
+            .or_default()
+            .insert(to.to_lowercase());
+    }
+
+    /// Palavras e pares bloqueados, somados.
+    pub fn len(&self) -> usize {
+        self.words.len() + self.pairs.values().map(HashSet::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.pairs.is_empty()
+    }
+
+    /// Se corrigir `from` para `to` está proibido. Os dois já devem estar
+    /// em minúsculas (como no pipeline do `StageA`).
+    pub fn blocks(&self, from: &str, to: &str) -> bool {
+        from != to
+            && (self.words.contains(to)
+                || self
+                    .pairs
+                    .get(from)
+                    .is_some_and(|targets| targets.contains(to)))
+    }
+}
@@ -0,0 +1,751 @@
+use crate::blocklist::{self, Blocklist};
+use crate::dict_loader;
+use crate::embedded;
+use crate::glossary::{self, Glossary};
+use crate::grammar::{self, GrammarEngine};
+use crate::layers::{self, DictionaryLayer, LayerKind};
+use crate::paths::{PathResolver, Resolution};
+use crate::stage_a::{StageA, KEYSTROKE_SEARCH_TIMEOUT};
+use crate::typo_model::{self, TypoModel};
+use crate::variant::{self, Variant};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const DICTIONARY_FILE: &str = "dictionary_pt_br.txt";
+pub const FREQUENCY_FILE: &str = "frequency_pt_br.txt";
+pub const MODELS_DIR: &str = "models";
+pub const CONFIG_FILE: &str = "config.json";
+pub const TYPO_RULES_FILE: &str = "typo_rules.tsv";
+pub const TEAM_DICTIONARY_FILE: &str = "team_dictionary.txt";
+pub const PACKS_DIR: &str = "packs";
+pub const GLOSSARY_DIR: &str = "glossary";
+pub const BLOCKLIST_FILE: &str = "blocklist.tsv";
+pub const VARIANTS_DIR: &str = "variants";
+pub const GRAMMAR_DIR: &str = "grammar";
+
+/// Configuração de carga do engine. Pode vir de um JSON
+/// ([`EngineConfig::load`]); campos ausentes usam o padrão.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineConfig {
+    /// Diretório com os arquivos de dados
+    pub data_dir: Option<PathBuf>,
+    /// Caminho explícito do dicionário (tem precedência sobre `data_dir`)
+    pub dictionary: Option<PathBuf>,
+    /// Caminho explícito da tabela de frequências
+    pub frequency: Option<PathBuf>,
+    /// Diretório explícito dos modelos ONNX
+    pub models: Option<PathBuf>,
+    /// Arquivo explícito de regras extras do `TypoModel`
+    pub typo_rules: Option<PathBuf>,
+    /// Arquivo explícito do vocabulário da equipe
+    pub team_dictionary: Option<PathBuf>,
+    /// Diretório explícito dos pacotes de domínio
+    pub packs: Option<PathBuf>,
+    /// Pacotes de domínio ligados ao carregar (nome do arquivo sem `.txt`);
+    /// os demais são carregados desligados
+    pub enabled_packs: Vec<String>,
+    /// Diretório explícito dos glossários (pode ser uma pasta compartilhada)
+    pub glossary: Option<PathBuf>,
+    /// Arquivo explícito da lista de correções proibidas
+    pub blocklist: Option<PathBuf>,
+    /// Norma ortográfica (`pt_br` ou `pt_pt`)
+    pub variant: Variant,
+    /// Diretório explícito das listas de palavras por norma
+    pub variants: Option<PathBuf>,
+    /// Diretório explícito das regras gramaticais extras do Stage B
+    pub grammar: Option<PathBuf>,
+    /// Prazo das buscas no trie por correção, em milissegundos
+    pub search_timeout_ms: Option<u64>,
+    /// Nós visitados no máximo por busca no trie
+    pub search_max_nodes: Option<usize>,
+}
+
+impl EngineConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| LoadError::new(Resource::Config, path, e.to_string()))?;
+        serde_json::from_str(&text)
+            .map_err(|e| LoadError::new(Resource::Config, path, e.to_string()))
+    }
+
+    /// Caminho explícito configurado para um recurso.
+    pub fn path_of(&self, resource: Resource) -> Option<&PathBuf> {
+        match resource {
+            Resource::Config => None,
+            Resource::Frequency => self.frequency.as_ref(),
+            Resource::Dictionary => self.dictionary.as_ref(),
+            Resource::Models => self.models.as_ref(),
+            Resource::TypoRules => self.typo_rules.as_ref(),
+            Resource::TeamDictionary => self.team_dictionary.as_ref(),
+            Resource::Packs => self.packs.as_ref(),
+            Resource::Glossary => self.glossary.as_ref(),
+            Resource::Blocklist => self.blocklist.as_ref(),
+            Resource::Variants => self.variants.as_ref(),
+            Resource::Grammar => self.grammar.as_ref(),
+        }
+    }
+}
+
+/// Recurso carregado pelo [`EngineBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    Config,
+    Frequency,
+    Dictionary,
+    /// Diretório dos modelos ONNX (Stage B/C)
+    Models,
+    /// Regras extras do `TypoModel` (TSV, ver [`typo_model::parse_rules`])
+    TypoRules,
+    /// Vocabulário da equipe (uma camada acima dos pacotes)
+    TeamDictionary,
+    /// Diretório dos pacotes de domínio (um `.txt` por pacote)
+    Packs,
+    /// Diretório dos glossários da equipe (um `.tsv` por glossário)
+    Glossary,
+    /// Correções proibidas (TSV, ver [`blocklist::parse_blocklist`])
+    Blocklist,
+    /// Diretório das listas de palavras por norma (`pt_pt.txt`...)
+    Variants,
+    /// Diretório das regras gramaticais extras do Stage B (um `.tsv` por arquivo)
+    Grammar,
+}
+
+impl Resource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Resource::Config => "configuração",
+            Resource::Frequency => "frequências",
+            Resource::Dictionary => "dicionário",
+            Resource::Models => "modelos",
+            Resource::TypoRules => "regras do TypoModel",
+            Resource::TeamDictionary => "vocabulário da equipe",
+            Resource::Packs => "pacotes de domínio",
+            Resource::Glossary => "glossário",
+            Resource::Blocklist => "correções proibidas",
+            Resource::Variants => "listas da norma",
+            Resource::Grammar => "regras gramaticais",
+        }
+    }
+
+    /// Nome padrão do arquivo (ou diretório) dentro de um diretório de dados.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Resource::Config => CONFIG_FILE,
+            Resource::Frequency => FREQUENCY_FILE,
+            Resource::Dictionary => DICTIONARY_FILE,
+            Resource::Models => MODELS_DIR,
+            Resource::TypoRules => TYPO_RULES_FILE,
+            Resource::TeamDictionary => TEAM_DICTIONARY_FILE,
+            Resource::Packs => PACKS_DIR,
+            Resource::Glossary => GLOSSARY_DIR,
+            Resource::Blocklist => BLOCKLIST_FILE,
+            Resource::Variants => VARIANTS_DIR,
+            Resource::Grammar => GRAMMAR_DIR,
+        }
+    }
+
+    pub fn is_directory(self) -> bool {
+        matches!(
+            self,
+            Resource::Models
+                | Resource::Packs
+                | Resource::Glossary
+                | Resource::Variants
+                | Resource::Grammar
+        )
+    }
+
+    /// Extensão dos arquivos lidos de um recurso que é um diretório.
+    pub fn entry_extension(self) -> Option<&'static str> {
+        match self {
+            Resource::Packs => Some(layers::PACK_EXTENSION),
+            Resource::Glossary => Some(glossary::GLOSSARY_EXTENSION),
+            Resource::Variants => Some(variant::VARIANT_EXTENSION),
+            Resource::Grammar => Some(grammar::GRAMMAR_EXTENSION),
+            _ => None,
+        }
+    }
+
+    /// Recursos opcionais: a ausência não é reportada como falta.
+    pub fn optional(self) -> bool {
+        matches!(
+            self,
+            Resource::Config
+                | Resource::TypoRules
+                | Resource::TeamDictionary
+                | Resource::Packs
+                | Resource::Glossary
+                | Resource::Blocklist
+                | Resource::Variants
+                | Resource::Grammar
+        )
+    }
+
+    /// Se existe uma versão embutida no binário como último recurso.
+    pub fn embeddable(self) -> bool {
+        matches!(self, Resource::Frequency | Resource::Dictionary)
+    }
+}
+
+/// Falha ao ler um arquivo que existe (arquivos ausentes não são erro:
+/// aparecem em [`LoadReport::missing`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadError {
+    pub resource: Resource,
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl LoadError {
+    fn new(resource: Resource, path: &Path, message: String) -> Self {
+        Self {
+            resource,
+            path: path.to_path_buf(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "falha ao carregar {} de {:?}: {}",
+            self.resource.as_str(),
+            self.path,
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Resultado da carga de um recurso.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceReport {
+    pub resource: Resource,
+    /// Candidatos avaliados e o escolhido
+    pub resolution: Resolution,
+    pub loaded: bool,
+    /// Entradas carregadas (palavras, pares...)
+    pub entries: usize,
+    pub elapsed: Duration,
+    /// Problemas que não impedem a carga (ex.: conflitos entre glossários)
+    pub warnings: Vec<String>,
+}
+
+/// O que o [`EngineBuilder::build`] carregou, de onde e quanto tempo levou.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LoadReport {
+    pub resources: Vec<ResourceReport>,
+    pub total: Duration,
+}
+
+impl ResourceReport {
+    pub fn path(&self) -> Option<&Path> {
+        self.resolution.path()
+    }
+}
+
+impl LoadReport {
+    pub fn get(&self, resource: Resource) -> Option<&ResourceReport> {
+        self.resources.iter().find(|r| r.resource == resource)
+    }
+
+    /// Recursos de dados que não foram encontrados (os opcionais, como o
+    /// `config.json`, não entram aqui).
+    pub fn missing(&self) -> impl Iterator<Item = &ResourceReport> {
+        self.resources
+            .iter()
+            .filter(|r| !r.loaded && !r.resource.optional())
+    }
+
+    pub fn entries(&self, resource: Resource) -> usize {
+        self.get(resource).map_or(0, |r| r.entries)
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for r in &self.resources {
+            let origin = r.resolution.origin().map_or("", |o| o.as_str());
+            match r.path() {
+                Some(path) if r.loaded => writeln!(
+                    f,
+                    "{}: {} entradas de {:?} ({}) em {:?}",
+                    r.resource.as_str(),
+                    r.entries,
+                    path,
+                    origin,
+                    r.elapsed
+                )?,
+                Some(path) => writeln!(f, "{}: {:?} ({})", r.resource.as_str(), path, origin)?,
+                None if r.loaded => writeln!(
+                    f,
+                    "{}: {} entradas embutidas em {:?}",
+                    r.resource.as_str(),
+                    r.entries,
+                    r.elapsed
+                )?,
+                None if r.resource.optional() => writeln!(
+                    f,
+                    "{}: nenhum {}, usando o padrão",
+                    r.resource.as_str(),
+                    r.resource.file_name()
+                )?,
+                None => {
+                    writeln!(f, "{}: não encontrado", r.resource.as_str())?;
+                    write!(f, "{}", r.resolution)?;
+                }
+            }
+            for warning in &r.warnings {
+                writeln!(f, "  ! {}", warning)?;
+            }
+        }
+        write!(f, "carga total em {:?}", self.total)
+    }
+}
+
+/// Monta um [`StageA`] pronto, carregando cada recurso na ordem certa
+/// (frequências antes do dicionário, para que as palavras já entrem no trie
+/// com o rank). Usado por todos os frontends para que corrijam igual.
+///
+/// Cada recurso é localizado por um [`PathResolver`]; com [`new`](Self::new)
+/// só os caminhos configurados contam, com [`from_environment`](Self::from_environment)
+/// entram também `DRACO_DATA_DIR`, a pasta do usuário e o diretório do executável.
+///
+/// Só o dicionário é obrigatório: um arquivo quebrado de qualquer outro
+/// recurso fica de fora, com o erro como aviso no [`LoadReport`]. Já
+/// [`build_strict`](Self::build_strict) não aceita nenhum arquivo quebrado.
+#[derive(Debug, Clone, Default)]
+pub struct EngineBuilder {
+    config: EngineConfig,
+    resolver: PathResolver,
+    /// Onde o `config.json` foi procurado, quando veio do ambiente
+    config_resolution: Option<Resolution>,
+}
+
+impl EngineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_config(config: EngineConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Busca completa a partir do ambiente do processo, incluindo um
+    /// `config.json` opcional nas mesmas origens dos dados.
+    pub fn from_environment() -> Result<Self, LoadError> {
+        Self::with_resolver(PathResolver::from_environment())
+    }
+
+    /// Como [`from_environment`](Self::from_environment), com um resolvedor próprio
+    /// (ex.: a DLL informando o diretório dela com [`PathResolver::with_exe_dir`]).
+    pub fn with_resolver(resolver: PathResolver) -> Result<Self, LoadError> {
+        let resolution = resolver.resolve(Resource::Config);
+        let config = match resolution.path() {
+            Some(path) => EngineConfig::load(path)?,
+            None => EngineConfig::default(),
+        };
+        Ok(Self {
+            config,
+            resolver,
+            config_resolution: Some(resolution),
+        })
+    }
+
+    pub fn data_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.config.data_dir = Some(dir.into());
+        self
+    }
+
+    pub fn dictionary<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config.dictionary = Some(path.into());
+        self
+    }
+
+    pub fn frequency<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config.frequency = Some(path.into());
+        self
+    }
+
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.config.variant = variant;
+        self
+    }
+
+    pub fn search_limits(mut self, timeout: Option<Duration>, max_nodes: Option<usize>) -> Self {
+        self.config.search_timeout_ms = timeout.map(|t| t.as_millis() as u64);
+        self.config.search_max_nodes = max_nodes;
+        self
+    }
+
+    /// Usa o prazo de busca de digitação, a menos que a configuração já defina um.
+    pub fn keystroke_defaults(mut self) -> Self {
+        if self.config.search_timeout_ms.is_none() {
+            self.config.search_timeout_ms = Some(KEYSTROKE_SEARCH_TIMEOUT.as_millis() as u64);
+        }
+        self
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    /// Localiza um recurso sem carregá-lo (ex.: o diretório de modelos).
+    pub fn resolve(&self, resource: Resource) -> Resolution {
+        self.resolver
+            .clone()
+            .with_config(&self.config)
+            .resolve(resource)
+    }
+
+    /// Monta só o `TypoModel`: o embutido mais as regras do arquivo, se houver.
+    pub fn build_typo_model(&self) -> Result<(TypoModel, ResourceReport), LoadError> {
+        let mut model = TypoModel::new();
+        let resolution = self.resolve(Resource::TypoRules);
+        let report = load(resolution, |source| {
+            let Some(path) = source else { return Ok(0) };
+            let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let rules = typo_model::parse_rules(&text).map_err(|e| e.to_string())?;
+            model.add_rules(&rules);
+            Ok(rules.len())
+        })?;
+        Ok((model, report))
+    }
+
+    /// Monta o motor de regras do Stage B: as regras embutidas mais as dos
+    /// arquivos do diretório de gramática, em ordem alfabética.
+    pub fn build_grammar(&self) -> Result<(GrammarEngine, ResourceReport), LoadError> {
+        let mut engine = GrammarEngine::builtin();
+        let resolution = self.resolve(Resource::Grammar);
+        let report = load(resolution, |source| {
+            let Some(dir) = source else { return Ok(0) };
+            let mut total = 0;
+            for path in grammar::discover_grammars(dir).map_err(|e| e.to_string())? {
+                let text = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+                total += engine
+                    .add_rules(&text)
+                    .map_err(|e| format!("{:?}: {}", path, e))?;
+            }
+            Ok(total)
+        })?;
+        Ok((engine, report))
+    }
+
+    pub fn build(&self) -> Result<(StageA, LoadReport), LoadError> {
+        self.build_with(false)
+    }
+
+    /// Como [`build`](Self::build), mas qualquer recurso ou arquivo quebrado
+    /// é erro. É o que a recarga usa: o engine anterior continua no lugar.
+    pub fn build_strict(&self) -> Result<(StageA, LoadReport), LoadError> {
+        self.build_with(true)
+    }
+
+    fn build_with(&self, strict: bool) -> Result<(StageA, LoadReport), LoadError> {
+        let start = Instant::now();
+        let mut engine = StageA::new();
+        engine.set_search_limits(
+            self.config.search_timeout_ms.map(Duration::from_millis),
+            self.config.search_max_nodes,
+        );
+        let mut report = LoadReport::default();
+        if let Some(resolution) = &self.config_resolution {
+            report.resources.push(ResourceReport {
+                resource: Resource::Config,
+                loaded: resolution.path().is_some(),
+                resolution: resolution.clone(),
+                entries: 0,
+                elapsed: Duration::ZERO,
+                warnings: Vec::new(),
+            });
+        }
+
+        // 1. Frequências primeiro: `load_dictionary*` consulta o rank de cada palavra
+        let resolution = self.resolve(Resource::Frequency);
+        let mut frequencies = Vec::new();
+        let frequency_report = load(resolution, |source| {
+            frequencies = match source {
+                Some(path) => non_empty(dict_loader::load_frequency_file(path))?,
+                None => embedded::frequency().unwrap_or_default(),
+            };
+            Ok(frequencies.len())
+        });
+        let (frequencies, frequency_report) =
+            self.or_skip(frequency_report.map(|r| (frequencies, r)), strict)?;
+        engine.load_frequency_data(&frequencies);
+        report.resources.push(frequency_report);
+
+        // 2. Dicionário: o único recurso sem o qual não há engine
+        let resolution = self.resolve(Resource::Dictionary);
+        report.resources.push(load(resolution, |source| {
+            let words = match source {
+                Some(path) => non_empty(dict_loader::load_from_file(path))?,
+                None => embedded::dictionary().unwrap_or_default(),
+            };
+            engine.load_dictionary_strings(&words);
+            Ok(words.len())
+        })?);
+
+        // Daqui em diante, fora do modo estrito, um recurso quebrado fica de
+        // fora com um aviso
+
+        // 3. Camadas extras: norma ortográfica, equipe e pacotes de domínio
+        engine.set_variant(self.config.variant);
+        let (words, variants_report) = self.or_skip(self.build_variant_words(), strict)?;
+        engine.add_variant_words(&words);
+        report.resources.push(variants_report);
+
+        let (team, team_report) = self.or_skip(self.build_team_layer(), strict)?;
+        if let Some(layer) = team {
+            engine.add_layer(layer);
+        }
+        report.resources.push(team_report);
+
+        let (packs, mut packs_report) = self.or_skip(self.load_packs(strict), strict)?;
+        for layer in packs {
+            let name = layer.name().to_string();
+            if !engine.add_layer(layer) {
+                packs_report.warnings.push(format!(
+                    "pacote {:?} ignorado: nome de camada reservado",
+                    name
+                ));
+            }
+        }
+        report.resources.push(packs_report);
+
+        // 4. Glossário da equipe
+        let (glossary, glossary_report) = self.or_skip(self.load_glossary(strict), strict)?;
+        engine.set_glossary(glossary);
+        report.resources.push(glossary_report);
+
+        // 5. Correções proibidas
+        let (blocklist, blocklist_report) = self.or_skip(self.build_blocklist(), strict)?;
+        engine.set_blocklist(blocklist);
+        report.resources.push(blocklist_report);
+
+        // 6. Regras extras do TypoModel
+        let (model, typo_report) = self.or_skip(self.build_typo_model(), strict)?;
+        engine.set_typo_model(model);
+        report.resources.push(typo_report);
+
+        report.total = start.elapsed();
+        Ok((engine, report))
+    }
+
+    /// Lê a lista de palavras da norma configurada (`variants/pt_pt.txt`...),
+    /// que completa a camada da norma.
+    pub fn build_variant_words(&self) -> Result<(Vec<(String, u32)>, ResourceReport), LoadError> {
+        let mut words = Vec::new();
+        let resolution = self.resolve(Resource::Variants);
+        let report = load(resolution, |source| {
+            let Some(dir) = source else { return Ok(0) };
+            let file = format!(
+                "{}.{}",
+                self.config.variant.as_str(),
+                variant::VARIANT_EXTENSION
+            );
+            let path = dir.join(file);
+            if !path.exists() {
+                return Ok(0);
+            }
+            words = non_empty(dict_loader::load_weighted_file(&path))
+                .map_err(|e| format!("{:?}: {}", path, e))?;
+            Ok(words.len())
+        })?;
+        Ok((words, report))
+    }
+
+    /// Monta a camada do vocabulário da equipe, se houver o arquivo.
+    pub fn build_team_layer(&self) -> Result<(Option<DictionaryLayer>, ResourceReport), LoadError> {
+        let mut layer = None;
+        let resolution = self.resolve(Resource::TeamDictionary);
+        let report = load(resolution, |source| {
+            let Some(path) = source else { return Ok(0) };
+            let entries = non_empty(dict_loader::load_weighted_file(path))?;
+            layer = Some(DictionaryLayer::from_entries(
+                LayerKind::Team.as_str(),
+                LayerKind::Team,
+                &entries,
+            ));
+            Ok(entries.len())
+        })?;
+        Ok((layer, report))
+    }
+
+    /// Monta uma camada por pacote de domínio, ligadas conforme
+    /// `enabled_packs`. Um pacote quebrado fica de fora, com um aviso.
+    pub fn build_packs(&self) -> Result<(Vec<DictionaryLayer>, ResourceReport), LoadError> {
+        self.load_packs(false)
+    }
+
+    /// [`build_packs`](Self::build_packs); com `strict`, um pacote quebrado
+    /// é erro em vez de aviso.
+    pub(crate) fn load_packs(
+        &self,
+        strict: bool,
+    ) -> Result<(Vec<DictionaryLayer>, ResourceReport), LoadError> {
+        let mut packs = Vec::new();
+        let mut warnings = Vec::new();
+        let resolution = self.resolve(Resource::Packs);
+        let mut report = load(resolution, |source| {
+            let Some(dir) = source else { return Ok(0) };
+            let mut total = 0;
+            for path in layers::discover_packs(dir).map_err(|e| e.to_string())? {
+                let Some(name) = layers::pack_name(&path) else {
+                    let warning = format!("{:?}: nome de pacote inválido", path);
+                    if strict {
+                        return Err(warning);
+                    }
+                    warnings.push(warning);
+                    continue;
+                };
+                let entries = match non_empty(dict_loader::load_weighted_file(&path)) {
+                    Ok(entries) => entries,
+                    Err(e) if strict => return Err(format!("{:?}: {}", path, e)),
+                    Err(e) => {
+                        warnings.push(format!("{:?}: {}", path, e));
+                        continue;
+                    }
+                };
+                let enabled = self.config.enabled_packs.iter().any(|p| p == name);
+                packs.push(
+                    DictionaryLayer::from_entries(name, LayerKind::Pack, &entries)
+                        .with_enabled(enabled),
+                );
+                total += entries.len();
+            }
+            Ok(total)
+        })?;
+        report.warnings = warnings;
+        Ok((packs, report))
+    }
+
+    /// Monta o glossário da equipe a partir dos arquivos do diretório, em
+    /// ordem alfabética. Conflitos entre arquivos e arquivos quebrados não
+    /// impedem a carga: viram avisos.
+    pub fn build_glossary(&self) -> Result<(Glossary, ResourceReport), LoadError> {
+        self.load_glossary(false)
+    }
+
+    /// [`build_glossary`](Self::build_glossary); com `strict`, um arquivo
+    /// quebrado é erro. Conflitos continuam sendo avisos.
+    pub(crate) fn load_glossary(
+        &self,
+        strict: bool,
+    ) -> Result<(Glossary, ResourceReport), LoadError> {
+        let mut glossary = Glossary::new();
+        let mut warnings = Vec::new();
+        let resolution = self.resolve(Resource::Glossary);
+        let mut report = load(resolution, |source| {
+            let Some(dir) = source else { return Ok(0) };
+            let mut total = 0;
+            for path in glossary::discover_glossaries(dir).map_err(|e| e.to_string())? {
+                let parsed = std::fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| glossary::parse_glossary(&text).map_err(|e| e.to_string()));
+                let entries = match parsed {
+                    Ok(entries) => entries,
+                    Err(e) if strict => return Err(format!("{:?}: {}", path, e)),
+                    Err(e) => {
+                        warnings.push(format!("{:?}: {}", path, e));
+                        continue;
+                    }
+                };
+                let name = path.file_name().map_or_else(
+                    || path.display().to_string(),
+                    |n| n.to_string_lossy().into_owned(),
+                );
+                let conflicts = glossary.add(&entries, &name);
+                warnings.extend(conflicts.iter().map(ToString::to_string));
+                total += entries.len();
+            }
+            Ok(total)
+        })?;
+        report.warnings = warnings;
+        Ok((glossary, report))
+    }
+
+    /// Lê a lista de correções proibidas, se houver o arquivo.
+    pub fn build_blocklist(&self) -> Result<(Blocklist, ResourceReport), LoadError> {
+        let mut blocklist = Blocklist::default();
+        let resolution = self.resolve(Resource::Blocklist);
+        let report = load(resolution, |source| {
+            let Some(path) = source else { return Ok(0) };
+            let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let entries = blocklist::parse_blocklist(&text).map_err(|e| e.to_string())?;
+            blocklist = Blocklist::from_entries(&entries);
+            Ok(entries.len())
+        })?;
+        Ok((blocklist, report))
+    }
+
+    /// Um recurso que falhou ao carregar fica de fora (valor padrão), com o
+    /// erro como aviso no relatório. Com `strict`, o erro sobe.
+    fn or_skip<T: Default>(
+        &self,
+        result: Result<(T, ResourceReport), LoadError>,
+        strict: bool,
+    ) -> Result<(T, ResourceReport), LoadError> {
+        if strict {
+            return result;
+        }
+        Ok(result.unwrap_or_else(|error| {
+            let report = ResourceReport {
+                resource: error.resource,
+                resolution: self.resolve(error.resource),
+                loaded: false,
+                entries: 0,
+                elapsed: Duration::ZERO,
+                warnings: vec![error.to_string()],
+            };
+            (T::default(), report)
+        }))
+    }
+}
+
+/// Carrega um recurso se algum candidato foi escolhido, cronometrando a
+/// leitura. `read` recebe o caminho do arquivo, ou `None` para os dados embutidos.
+fn load(
+    resolution: Resolution,
+    read: impl FnOnce(Option<&Path>) -> Result<usize, String>,
+) -> Result<ResourceReport, LoadError> {
+    let start = Instant::now();
+    let resource = resolution.resource;
+    let mut entries = 0;
+    let mut loaded = false;
+    if resolution.chosen().is_some() {
+        let path = resolution.path();
+        entries = read(path)
+            .map_err(|message| LoadError::new(resource, path.unwrap_or(Path::new("")), message))?;
+        loaded = true;
+    }
+    Ok(ResourceReport {
+        resource,
+        resolution,
+        loaded,
+        entries,
+        elapsed: start.elapsed(),
+        warnings: Vec::new(),
+    })
+}
+
+/// Um arquivo de dados que existe mas não rende nenhuma entrada é tratado
+/// como quebrado (ex.: salvo pela metade), não como um dicionário vazio.
+fn non_empty<T>(result: std::io::Result<Vec<T>>) -> Result<Vec<T>, String> {
+    match result {
+        Ok(entries) if entries.is_empty() => Err("arquivo sem nenhuma entrada".to_string()),
+        Ok(entries) => Ok(entries),
+        Err(e) => Err(e.to_string()),
+    }
+}
@@ -0,0 +1,177 @@
+use crate::chunk;
+use crate::edit;
+use crate::stage_c::Rewrite;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Textos guardados por padrão.
+pub const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// Contadores do cache, para medir quanto ele poupa do modelo.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// Textos respondidos pelo cache
+    pub hits: u64,
+    /// Textos que tiveram de ir ao modelo
+    pub misses: u64,
+    /// Entradas descartadas para abrir espaço
+    pub evictions: u64,
+    /// Entradas guardadas agora
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+/// (versão do modelo, texto normalizado)
+type Key = (String, String);
+
+#[derive(Debug, Default)]
+struct Lru {
+    entries: HashMap<Key, (Option<Rewrite>, u64)>,
+    /// Último uso de cada chave; a primeira é a usada há mais tempo
+    order: BTreeMap<u64, Key>,
+    tick: u64,
+}
+
+/// Cache LRU das reescritas do Stage C, pela versão do modelo e pelo texto
+/// normalizado (ver [`normalize`]). Guarda também os textos que o modelo
+/// deixou como estavam, o caso mais comum: o Stage B manda a mesma frase a
+/// cada pausa na digitação.
+#[derive(Debug)]
+pub struct RewriteCache {
+    capacity: usize,
+    lru: Mutex<Lru>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl Default for RewriteCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl RewriteCache {
+    /// Cache de até `capacity` textos; com 0, nada é guardado.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            lru: Mutex::new(Lru::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// A reescrita guardada para o texto (`Some(None)` se o modelo não o
+    /// mudou), ou `None` se ele não está no cache.
+    pub fn get(&self, version: &str, normalized: &str) -> Option<Option<Rewrite>> {
+        let found = self.lru.lock().ok().and_then(|mut lru| {
+            lru.tick += 1;
+            let tick = lru.tick;
+            let Lru { entries, order, .. } = &mut *lru;
+            let key = (version.to_string(), normalized.to_string());
+            let (rewrite, used) = entries.get_mut(&key)?;
+            order.remove(used);
+            *used = tick;
+            order.insert(tick, key);
+            Some(rewrite.clone())
+        });
+        let counter = if found.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    /// Guarda o resultado do modelo para o texto, descartando o usado há
+    /// mais tempo se o cache estiver cheio.
+    pub fn insert(&self, version: &str, normalized: &str, rewrite: Option<Rewrite>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let Ok(mut lru) = self.lru.lock() else {
+            return;
+        };
+        lru.tick += 1;
+        let tick = lru.tick;
+        let Lru { entries, order, .. } = &mut *lru;
+        let key = (version.to_string(), normalized.to_string());
+        if let Some((_, used)) = entries.remove(&key) {
+            order.remove(&used);
+        } else if entries.len() >= self.capacity {
+            if let Some((_, oldest)) = order.pop_first() {
+                entries.remove(&oldest);
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        entries.insert(key.clone(), (rewrite, tick));
+        order.insert(tick, key);
+    }
+
+    /// Esvazia o cache (os contadores continuam).
+    pub fn clear(&self) {
+        if let Ok(mut lru) = self.lru.lock() {
+            lru.entries.clear();
+            lru.order.clear();
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: self.lru.lock().map_or(0, |lru| lru.entries.len()),
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// O texto como chave do cache e entrada do modelo: sem espaços nas pontas
+/// e com um só espaço entre as palavras.
+pub fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Leva a reescrita de `normalized` (o [`normalize`] de `original`) para o
+/// texto original: as trocas voltam às posições dele, que mantém os seus
+/// espaços.
+pub fn project(original: &str, normalized: &str, rewrite: &Rewrite) -> Rewrite {
+    if original == normalized {
+        return rewrite.clone();
+    }
+    // Posição no original de cada início e de cada fim de trecho do
+    // normalizado: as palavras são as mesmas, e o espaço entre duas delas
+    // vale todo o espaço entre elas no original
+    let words = chunk::words(original);
+    let mut starts = Vec::with_capacity(normalized.len() + 1);
+    let mut ends = Vec::with_capacity(normalized.len() + 1);
+    ends.push(0);
+    for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+            starts.push(words[i - 1].end);
+            ends.push(word.start);
+        }
+        starts.extend(word.clone());
+        ends.extend(word.start + 1..=word.end);
+    }
+    starts.push(words.last().map_or(original.len(), |word| word.end));
+
+    let changes = edit::diff_hunks(normalized, &rewrite.text)
+        .into_iter()
+        .map(|hunk| {
+            let start = starts[hunk.from.start];
+            let end = if hunk.from.is_empty() {
+                start
+            } else {
+                ends[hunk.from.end]
+            };
+            (start..end, hunk.replacement, rewrite.probability(hunk.to))
+        });
+    Rewrite::splice(original, changes)
+}
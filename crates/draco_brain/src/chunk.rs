@@ -0,0 +1,198 @@
+use crate::edit;
+use crate::grammar;
+use crate::stage_c::Rewrite;
+use std::ops::Range;
+
+/// Tokens por trecho quando o modelo não declara `max_sequence_length`.
+pub const DEFAULT_MAX_TOKENS: usize = 512;
+
+/// Palavras de contexto repetidas de cada lado de um trecho.
+pub const DEFAULT_OVERLAP_WORDS: usize = 8;
+
+/// Como dividir textos longos para o modelo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkOptions {
+    /// Tokens de cada trecho, contexto incluído
+    pub max_tokens: usize,
+    /// Palavras antes e depois do núcleo de cada trecho que entram só como
+    /// contexto (reduzidas se não couberem)
+    pub overlap_words: usize,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            max_tokens: DEFAULT_MAX_TOKENS,
+            overlap_words: DEFAULT_OVERLAP_WORDS,
+        }
+    }
+}
+
+/// Trecho do texto enviado ao modelo: `range` inteiro entra, mas só as
+/// correções dentro de `core` valem. Os núcleos cobrem o texto sem se
+/// sobrepor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub range: Range<usize>,
+    pub core: Range<usize>,
+}
+
+/// Tokens do texto inteiro, tokenizado uma vez só: os de qualquer trecho
+/// são contados sem tokenizá-lo de novo.
+#[derive(Debug, Clone, Default)]
+pub struct TokenCounts {
+    /// Início (em bytes) de cada token, em ordem
+    starts: Vec<usize>,
+    /// Tokens especiais que o modelo acrescenta a cada entrada
+    special: usize,
+}
+
+impl TokenCounts {
+    /// A partir dos trechos em bytes de cada token do texto, sem os
+    /// especiais (como os de `Encoding::get_offsets`).
+    pub fn new(offsets: &[(usize, usize)], special: usize) -> Self {
+        let mut starts: Vec<usize> = offsets.iter().map(|&(start, _)| start).collect();
+        starts.sort_unstable();
+        Self { starts, special }
+    }
+
+    /// Tokens de um trecho ao ir sozinho ao modelo, com os especiais. Um
+    /// token conta para o trecho em que começa, então a conta pode errar
+    /// por um nas bordas.
+    pub fn count(&self, range: &Range<usize>) -> usize {
+        let from = self.starts.partition_point(|&start| start < range.start);
+        let to = self.starts.partition_point(|&start| start < range.end);
+        to - from + self.special
+    }
+}
+
+/// Divide o texto em trechos de até `max_tokens` tokens (contados em
+/// `tokens`, com os especiais do modelo): frases inteiras juntas enquanto
+/// cabem e, se uma frase sozinha não cabe, janelas de palavras. Cada núcleo
+/// leva até `overlap_words` palavras de contexto de cada lado.
+pub fn chunk(text: &str, options: &ChunkOptions, tokens: &TokenCounts) -> Vec<Chunk> {
+    let fits = |range: &Range<usize>| tokens.count(range) <= options.max_tokens;
+    if fits(&(0..text.len())) {
+        return vec![Chunk {
+            range: 0..text.len(),
+            core: 0..text.len(),
+        }];
+    }
+    let words = words(text);
+
+    // Unidades contíguas: frases, ou janelas de palavras das frases longas
+    let starts: Vec<usize> = grammar::sentences(text)
+        .iter()
+        .enumerate()
+        .map(|(i, sentence)| if i == 0 { 0 } else { sentence.start })
+        .collect();
+    let mut units: Vec<Range<usize>> = Vec::new();
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(text.len());
+        if fits(&(start..end)) {
+            units.push(start..end);
+            continue;
+        }
+        // Uma frase dentro de uma só palavra ("yy.zzzz.ww") não tem palavras
+        // inteiras: vai sozinha, mesmo que não caiba
+        let first = words.partition_point(|w| w.start < start);
+        let last = words.partition_point(|w| w.end <= end).max(first);
+        let inside = &words[first..last];
+        if inside.is_empty() {
+            units.push(start..end);
+            continue;
+        }
+        let mut from = start;
+        let mut k = 0;
+        while k < inside.len() {
+            // Ao menos uma palavra por janela, mesmo que não caiba
+            let mut last = k;
+            while last + 1 < inside.len() && fits(&(from..inside[last + 1].end)) {
+                last += 1;
+            }
+            let to = inside.get(last + 1).map_or(end, |w| w.start);
+            units.push(from..to);
+            from = to;
+            k = last + 1;
+        }
+    }
+    if units.is_empty() {
+        units.push(0..text.len());
+    }
+
+    // Núcleos: unidades seguidas enquanto cabem com o contexto
+    let with_context = |core: &Range<usize>, overlap: usize| {
+        let before = words.partition_point(|w| w.end <= core.start);
+        let after = words.partition_point(|w| w.start < core.end);
+        let start = if overlap == 0 || before == 0 {
+            core.start
+        } else {
+            words[before.saturating_sub(overlap)].start
+        };
+        let end = if overlap == 0 || after == words.len() {
+            core.end
+        } else {
+            words[(after + overlap).min(words.len()) - 1].end
+        };
+        start..end
+    };
+    let mut chunks = Vec::new();
+    let mut k = 0;
+    while k < units.len() {
+        let mut core = units[k].clone();
+        k += 1;
+        while k < units.len() {
+            let grown = core.start..units[k].end;
+            if !fits(&with_context(&grown, options.overlap_words)) {
+                break;
+            }
+            core = grown;
+            k += 1;
+        }
+        // Contexto menor (ou nenhum) se o núcleo sozinho já enche o trecho
+        let mut overlap = options.overlap_words;
+        let mut range = with_context(&core, overlap);
+        while overlap > 0 && !fits(&range) {
+            overlap /= 2;
+            range = with_context(&core, overlap);
+        }
+        chunks.push(Chunk { range, core });
+    }
+    chunks
+}
+
+/// Junta as reescritas dos trechos (`None` onde o modelo não mudou nada)
+/// numa reescrita do texto inteiro. De cada trecho só valem as trocas
+/// dentro do seu núcleo, levadas para as posições do texto original.
+pub fn merge(text: &str, chunks: &[Chunk], rewrites: &[Option<Rewrite>]) -> Rewrite {
+    let mut changes = Vec::new();
+    for (chunk, rewrite) in chunks.iter().zip(rewrites) {
+        let Some(rewrite) = rewrite else {
+            continue;
+        };
+        // O modelo vê o trecho sem os espaços das pontas
+        let part = &text[chunk.range.clone()];
+        let offset = chunk.range.start + part.len() - part.trim_start().len();
+        for hunk in edit::diff_hunks(part.trim(), &rewrite.text) {
+            let from = hunk.from.start + offset..hunk.from.end + offset;
+            // Inserções na fronteira ficam com o núcleo seguinte
+            let owned = chunk.core.start <= from.start
+                && from.end <= chunk.core.end
+                && (from.start < chunk.core.end || chunk.core.end == text.len());
+            if owned {
+                changes.push((from, hunk.replacement, rewrite.probability(hunk.to)));
+            }
+        }
+    }
+    Rewrite::splice(text, changes)
+}
+
+/// Palavras separadas por espaço em branco, como trechos em bytes.
+pub(crate) fn words(text: &str) -> Vec<Range<usize>> {
+    text.split_whitespace()
+        .map(|word| {
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            start..start + word.len()
+        })
+        .collect()
+}
@@ -1,52 +1,74 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 /// Carrega palavras de um arquivo (uma por linha).
 pub fn load_from_file<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<String>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let mut words = Vec::new();
-
-    for line in reader.lines() {
-        let line = line?;
-        let word = line.trim();
-        if !word.is_empty() {
-            words.push(word.to_string());
-        }
-    }
+    Ok(parse_words(&std::fs::read_to_string(path)?))
+}
+
+/// Palavras de um texto com uma por linha (linhas vazias são ignoradas).
+pub fn parse_words(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Carrega uma lista de palavras com frequência opcional (formato:
+/// "palavra [frequência]" por linha, `#` inicia comentário), usada pelas
+/// camadas extras de dicionário. Sem frequência, a palavra fica com 0 e
+/// herda a da tabela de frequências.
+pub fn load_weighted_file<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<(String, u32)>> {
+    Ok(parse_weighted(&std::fs::read_to_string(path)?))
+}
 
-    Ok(words)
+/// Igual a [`load_weighted_file`], a partir do texto já lido. Frequências
+/// inválidas contam como ausentes.
+pub fn parse_weighted(text: &str) -> Vec<(String, u32)> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let word = fields.next()?.to_lowercase();
+            let frequency = fields.next().and_then(|f| f.parse().ok()).unwrap_or(0);
+            Some((word, frequency))
+        })
+        .collect()
 }
 
 /// Carrega arquivo de frequência (formato: "palavra contagem" por linha).
 /// Retorna vetor de (palavra, frequência_normalizada).
 /// A frequência é normalizada para um rank: posição 1 = mais comum.
 pub fn load_frequency_file<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<(String, u32)>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    Ok(parse_frequency(&std::fs::read_to_string(path)?))
+}
+
+/// Igual a [`load_frequency_file`], a partir do texto já lido.
+pub fn parse_frequency(text: &str) -> Vec<(String, u32)> {
+    // Formato: "palavra contagem" (separados por espaço)
+    rank_words(
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| line.split(' ').next()),
+    )
+}
+
+/// Converte palavras em ordem decrescente de uso em (palavra, frequência).
+pub fn rank_words<'a>(words: impl IntoIterator<Item = &'a str>) -> Vec<(String, u32)> {
     let mut entries = Vec::new();
     let mut rank: u32 = 0;
 
-    for line in reader.lines() {
-        let line = line?;
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-
-        // Formato: "palavra contagem" (separados por espaço)
-        let parts: Vec<&str> = trimmed.splitn(2, ' ').collect();
-        if let Some(&word) = parts.first() {
-            if word.len() >= 2 {
-                rank += 1;
-                // Inverter rank para que palavras mais comuns tenham valor MAIOR
-                // Max rank = 50000, então freq = 50001 - rank
-                let freq = 50001u32.saturating_sub(rank);
-                entries.push((word.to_lowercase(), freq));
-            }
+    for word in words {
+        if word.len() >= 2 {
+            rank += 1;
+            // Inverter rank para que palavras mais comuns tenham valor MAIOR
+            // Max rank = 50000, então freq = 50001 - rank
+            let freq = 50001u32.saturating_sub(rank);
+            entries.push((word.to_lowercase(), freq));
         }
     }
 
-    Ok(entries)
+    entries
 }
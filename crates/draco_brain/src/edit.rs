@@ -0,0 +1,256 @@
+use crate::grammar::GrammarMatch;
+use crate::stage_c::Rewrite;
+use serde::Serialize;
+use std::ops::Range;
+
+/// Trecho de um texto nas três unidades que os clientes usam: bytes UTF-8
+/// (Rust), caracteres (code points) e unidades UTF-16 (Windows/TSF, JS).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EditRange {
+    pub bytes: Range<usize>,
+    pub chars: Range<usize>,
+    pub utf16: Range<usize>,
+}
+
+impl EditRange {
+    /// Converte um trecho em bytes de `text` (em limites de caractere).
+    pub fn from_bytes(text: &str, bytes: Range<usize>) -> Self {
+        let before = &text[..bytes.start];
+        let inside = &text[bytes.clone()];
+        let (chars, utf16) = (before.chars().count(), before.encode_utf16().count());
+        Self {
+            chars: chars..chars + inside.chars().count(),
+            utf16: utf16..utf16 + inside.encode_utf16().count(),
+            bytes,
+        }
+    }
+}
+
+/// De onde veio uma edição.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditSource {
+    /// Regra gramatical do Stage B
+    Rule,
+    /// Reescrita de um modelo (Stage C)
+    Model,
+}
+
+/// Uma correção pontual sobre o texto enviado ao Stage B, que o cliente
+/// pode mostrar e aplicar isoladamente.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Edit {
+    pub range: EditRange,
+    pub original: String,
+    pub replacement: String,
+    pub source: EditSource,
+    /// Id da regra ou do modelo
+    pub id: String,
+    pub message: String,
+    /// De 0 a 1
+    pub confidence: f32,
+}
+
+impl Edit {
+    pub fn from_grammar(text: &str, m: &GrammarMatch) -> Self {
+        Self {
+            range: EditRange::from_bytes(text, m.start..m.end),
+            original: m.original.clone(),
+            replacement: m.suggestion.clone(),
+            source: EditSource::Rule,
+            id: m.rule.clone(),
+            message: m.message.clone(),
+            confidence: m.confidence,
+        }
+    }
+
+    /// Edições que levam `original` a `rewritten`, a reescrita de um
+    /// modelo. Diferenças só de espaço em branco são ignoradas.
+    pub fn from_rewrite(
+        original: &str,
+        rewritten: &str,
+        model: &str,
+        confidence: f32,
+    ) -> Vec<Self> {
+        Self::from_model(original, &Rewrite::new(rewritten), model, confidence)
+    }
+
+    /// Como [`from_rewrite`](Self::from_rewrite); a confiança de cada
+    /// edição é a menor probabilidade dos tokens que a produziram, ou
+    /// `confidence` se o modelo não as informa.
+    pub fn from_model(
+        original: &str,
+        rewrite: &Rewrite,
+        model: &str,
+        confidence: f32,
+    ) -> Vec<Self> {
+        diff_hunks(original, &rewrite.text)
+            .into_iter()
+            .map(|hunk| Self {
+                original: original[hunk.from.clone()].to_string(),
+                range: EditRange::from_bytes(original, hunk.from),
+                replacement: hunk.replacement,
+                source: EditSource::Model,
+                id: model.to_string(),
+                message: "Reescrita sugerida pelo modelo.".to_string(),
+                confidence: rewrite.probability(hunk.to).unwrap_or(confidence),
+            })
+            .collect()
+    }
+
+    /// O texto com só esta edição aplicada.
+    pub fn apply(&self, text: &str) -> String {
+        apply_edits(text, std::slice::from_ref(self))
+    }
+}
+
+/// Aplica edições sem sobreposição, em ordem de posição, ao texto em que
+/// foram calculadas.
+pub fn apply_edits(text: &str, edits: &[Edit]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for edit in edits {
+        out.push_str(&text[copied..edit.range.bytes.start]);
+        out.push_str(&edit.replacement);
+        copied = edit.range.bytes.end;
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+/// Divide o texto em palavras, sequências de espaço e sinais de pontuação
+/// (um por token), como trechos em bytes.
+fn diff_tokens(text: &str) -> Vec<Range<usize>> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Punct,
+    }
+    let class = |c: char| match c {
+        c if c.is_alphanumeric() || c == '-' || c == '\'' => Class::Word,
+        c if c.is_whitespace() => Class::Space,
+        _ => Class::Punct,
+    };
+    let mut tokens: Vec<Range<usize>> = Vec::new();
+    let mut last = None;
+    for (i, c) in text.char_indices() {
+        let current = class(c);
+        match tokens.last_mut() {
+            Some(token) if last.as_ref() == Some(&current) && current != Class::Punct => {
+                token.end = i + c.len_utf8();
+            }
+            _ => tokens.push(i..i + c.len_utf8()),
+        }
+        last = Some(current);
+    }
+    tokens
+}
+
+/// Diferença por tokens (LCS) entre dois textos: trechos de `original` e o
+/// que os substitui. Trechos vizinhos alterados formam uma só edição.
+pub fn diff(original: &str, rewritten: &str) -> Vec<(Range<usize>, String)> {
+    diff_hunks(original, rewritten)
+        .into_iter()
+        .map(|hunk| (hunk.from, hunk.replacement))
+        .collect()
+}
+
+/// Um trecho alterado: de onde sai em `original`, onde fica em `rewritten`
+/// e o texto que entra (o de `rewritten`, com o espaço de `original` entre
+/// palavras de trechos unidos).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub from: Range<usize>,
+    pub to: Range<usize>,
+    pub replacement: String,
+}
+
+/// Como [`diff`], com a posição de cada troca no texto reescrito.
+pub fn diff_hunks(original: &str, rewritten: &str) -> Vec<Hunk> {
+    let a = diff_tokens(original);
+    let b = diff_tokens(rewritten);
+    let same = |i: usize, j: usize| original[a[i].clone()] == rewritten[b[j].clone()];
+
+    // Prefixo e sufixo comuns ficam fora da tabela (em geral, quase tudo)
+    let mut head = 0;
+    while head < a.len() && head < b.len() && same(head, head) {
+        head += 1;
+    }
+    let mut tail = 0;
+    while tail < a.len() - head
+        && tail < b.len() - head
+        && same(a.len() - 1 - tail, b.len() - 1 - tail)
+    {
+        tail += 1;
+    }
+    let (n, m) = (a.len() - head - tail, b.len() - head - tail);
+
+    // lcs[i][j]: maior subsequência comum de a[head + i..] e b[head + j..]
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if same(head + i, head + j) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let position = |i: usize| a.get(head + i).map_or(original.len(), |t| t.start);
+    let target = |j: usize| b.get(head + j).map_or(rewritten.len(), |t| t.start);
+    let mut edits = Vec::new();
+    let mut hunk: Option<Hunk> = None;
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && same(head + i, head + j) {
+            edits.extend(hunk.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let current = hunk.get_or_insert_with(|| Hunk {
+            from: position(i)..position(i),
+            to: target(j)..target(j),
+            replacement: String::new(),
+        });
+        if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            current
+                .replacement
+                .push_str(&rewritten[b[head + j].clone()]);
+            current.to.end = b[head + j].end;
+            j += 1;
+        } else {
+            current.from.end = a[head + i].end;
+            i += 1;
+        }
+    }
+    edits.extend(hunk);
+    // Trocar um espaço por outro (ou aparar as pontas) não é edição;
+    // juntar ou separar palavras é
+    edits.retain(|hunk| {
+        let from = &original[hunk.from.clone()];
+        let spacing = from.trim().is_empty() && hunk.replacement.trim().is_empty();
+        let joins = from.is_empty() != hunk.replacement.is_empty()
+            && hunk.from.start > 0
+            && hunk.from.end < original.len();
+        !spacing || joins
+    });
+
+    // Palavras vizinhas trocadas (só espaço entre elas) formam uma edição
+    let mut merged: Vec<Hunk> = Vec::with_capacity(edits.len());
+    for hunk in edits {
+        match merged.last_mut() {
+            Some(last) if original[last.from.end..hunk.from.start].trim().is_empty() => {
+                last.replacement
+                    .push_str(&original[last.from.end..hunk.from.start]);
+                last.replacement.push_str(&hunk.replacement);
+                last.from.end = hunk.from.end;
+                last.to.end = hunk.to.end;
+            }
+            _ => merged.push(hunk),
+        }
+    }
+    merged
+}
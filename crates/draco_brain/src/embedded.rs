@@ -0,0 +1,35 @@
+//! Dicionário e frequências padrão embutidos no binário (feature
+//! `embedded-data`). Servem de último recurso quando nenhum arquivo externo
+//! é encontrado; sem a feature, as funções retornam `None`.
+
+use crate::{dict_loader, front_coding};
+
+/// Se o binário foi compilado com os dados embutidos.
+pub const AVAILABLE: bool = cfg!(feature = "embedded-data");
+
+#[cfg(feature = "embedded-data")]
+static DICTIONARY: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/dictionary.fc"));
+#[cfg(feature = "embedded-data")]
+static FREQUENCY: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/frequency.fc"));
+
+#[cfg(not(feature = "embedded-data"))]
+static DICTIONARY: &[u8] = &[];
+#[cfg(not(feature = "embedded-data"))]
+static FREQUENCY: &[u8] = &[];
+
+/// Palavras do dicionário embutido.
+pub fn dictionary() -> Option<Vec<String>> {
+    front_coding::decode(DICTIONARY)
+}
+
+/// Tabela de frequências embutida, já convertida em rank como em
+/// [`dict_loader::load_frequency_file`].
+pub fn frequency() -> Option<Vec<(String, u32)>> {
+    let words = front_coding::decode(FREQUENCY)?;
+    Some(dict_loader::rank_words(words.iter().map(String::as_str)))
+}
+
+/// Tamanho dos dados embutidos (dicionário, frequências), em bytes.
+pub fn compressed_size() -> (usize, usize) {
+    (DICTIONARY.len(), FREQUENCY.len())
+}
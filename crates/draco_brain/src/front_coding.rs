@@ -0,0 +1,56 @@
+//! Codificação por prefixo compartilhado ("front coding") de listas de
+//! palavras. Usada pelo `build.rs` para gerar os dados embutidos e pelo
+//! módulo `embedded` para lê-los, então as duas pontas compartilham este arquivo.
+//!
+//! Formato: `DRFC` + quantidade (u32 LE) e, por palavra, um byte com o
+//! tamanho do prefixo em comum com a anterior (até 255), o resto da
+//! palavra em UTF-8 e `\n`. Listas ordenadas comprimem bem; a ordem
+//! original é preservada de qualquer forma.
+
+const MAGIC: &[u8; 4] = b"DRFC";
+
+pub fn encode<'a>(words: impl IntoIterator<Item = &'a str>) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.extend_from_slice(&[0; 4]);
+    let mut previous: &[u8] = &[];
+    let mut count: u32 = 0;
+    for word in words {
+        let bytes = word.as_bytes();
+        let common = previous
+            .iter()
+            .zip(bytes)
+            .take_while(|(a, b)| a == b)
+            .count()
+            .min(u8::MAX as usize);
+        out.push(common as u8);
+        out.extend_from_slice(&bytes[common..]);
+        out.push(b'\n');
+        previous = bytes;
+        count += 1;
+    }
+    out[4..8].copy_from_slice(&count.to_le_bytes());
+    out
+}
+
+/// Decodifica o que [`encode`] produziu. `None` se os dados estiverem corrompidos.
+pub fn decode(data: &[u8]) -> Option<Vec<String>> {
+    if data.len() < 8 || &data[..4] != MAGIC {
+        return None;
+    }
+    let count = u32::from_le_bytes(data[4..8].try_into().ok()?) as usize;
+    let mut words = Vec::with_capacity(count);
+    let mut current: Vec<u8> = Vec::new();
+    let mut i = 8;
+    while i < data.len() {
+        let common = data[i] as usize;
+        let end = i + 1 + data[i + 1..].iter().position(|&b| b == b'\n')?;
+        if common > current.len() {
+            return None;
+        }
+        current.truncate(common);
+        current.extend_from_slice(&data[i + 1..end]);
+        words.push(String::from_utf8(current.clone()).ok()?);
+        i = end + 1;
+    }
+    (words.len() == count).then_some(words)
+}
@@ -0,0 +1,272 @@
+use crate::typo_model::RuleError;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Extensão dos arquivos de glossário.
+pub const GLOSSARY_EXTENSION: &str = "tsv";
+
+/// Origem das entradas incluídas em tempo de execução (ex.: via IPC).
+pub const RUNTIME_SOURCE: &str = "tempo de execução";
+
+/// Um termo do glossário: a grafia exigida e as formas que devem virar ela.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GlossaryEntry {
+    /// Forma canônica, com a caixa exata (`WhatsApp`, `e-mail`)
+    pub canonical: String,
+    /// Variantes e erros conhecidos; a própria forma canônica em qualquer
+    /// caixa também é reconhecida
+    pub variants: Vec<String>,
+}
+
+/// Duas entradas que reivindicam a mesma grafia com formas canônicas
+/// diferentes. Fica valendo a primeira carregada.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GlossaryConflict {
+    /// Grafia disputada (em minúsculas)
+    pub key: String,
+    pub kept: String,
+    pub kept_source: String,
+    pub rejected: String,
+    pub rejected_source: String,
+}
+
+impl fmt::Display for GlossaryConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "conflito em \"{}\": \"{}\" ({}) × \"{}\" ({}); mantido \"{}\"",
+            self.key, self.kept, self.kept_source, self.rejected, self.rejected_source, self.kept
+        )
+    }
+}
+
+/// Lê um glossário no formato TSV, um termo por linha:
+///
+/// ```text
+/// # forma canônica<TAB>variante<TAB>variante...
+/// WhatsApp<TAB>whatsap<TAB>zap zap
+/// e-mail<TAB>email<TAB>imeil
+/// Nota Fiscal Eletrônica<TAB>nota fiscal eletronica
+/// ```
+///
+/// Uma linha só com a forma canônica apenas fixa a grafia e a caixa. Assim
+/// como nas regras do `TypoModel`, o arquivo inteiro é rejeitado no
+/// primeiro erro.
+pub fn parse_glossary(text: &str) -> Result<Vec<GlossaryEntry>, RuleError> {
+    let mut entries = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: String| RuleError {
+            line: i + 1,
+            message,
+        };
+        let line = line.trim_start_matches('\u{feff}').trim_end_matches('\r');
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split('\t').map(normalize_spaces);
+        let canonical = fields.next().unwrap_or_default();
+        if canonical.is_empty() {
+            return Err(error("forma canônica vazia".into()));
+        }
+        let mut variants = Vec::new();
+        for variant in fields {
+            if variant.is_empty() {
+                return Err(error("variante vazia".into()));
+            }
+            if variant == canonical {
+                return Err(error(format!(
+                    "variante igual à forma canônica: \"{}\"",
+                    variant
+                )));
+            }
+            variants.push(variant);
+        }
+        entries.push(GlossaryEntry {
+            canonical,
+            variants,
+        });
+    }
+    Ok(entries)
+}
+
+/// Arquivos de glossário (`*.tsv`) de um diretório, em ordem alfabética.
+pub fn discover_glossaries(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    crate::paths::files_with_extension(dir, GLOSSARY_EXTENSION)
+}
+
+fn normalize_spaces(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Chave de busca: minúsculas, palavras separadas por um espaço.
+fn key_of(text: &str) -> String {
+    normalize_spaces(&text.to_lowercase())
+}
+
+/// Glossário da equipe: grafias obrigatórias de termos de produto e empresa.
+///
+/// Cada variante (e a forma canônica em qualquer caixa) é reescrita para a
+/// forma canônica. Termos de uma palavra entram no pipeline do
+/// [`StageA`](crate::stage_a::StageA); termos de várias palavras só podem ser
+/// reconhecidos em texto corrido, com [`apply`](Self::apply).
+#[derive(Debug, Clone, Default)]
+pub struct Glossary {
+    /// Entradas com a origem de cada uma (nome do arquivo)
+    entries: Vec<(GlossaryEntry, String)>,
+    /// Grafia em minúsculas → índice em `entries`
+    keys: HashMap<String, usize>,
+    /// Maior número de palavras de uma chave
+    max_words: usize,
+}
+
+impl Glossary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &GlossaryEntry> {
+        self.entries.iter().map(|(entry, _)| entry)
+    }
+
+    /// Entradas que vieram de uma origem específica.
+    pub fn entries_from<'a>(&'a self, source: &'a str) -> impl Iterator<Item = &'a GlossaryEntry> {
+        self.entries
+            .iter()
+            .filter(move |(_, s)| s == source)
+            .map(|(entry, _)| entry)
+    }
+
+    /// Acrescenta entradas vindas de `source`. Grafias já reivindicadas por
+    /// outra forma canônica não são trocadas e voltam como conflito.
+    pub fn add(&mut self, entries: &[GlossaryEntry], source: &str) -> Vec<GlossaryConflict> {
+        let mut conflicts = Vec::new();
+        for entry in entries {
+            let index = self.entries.len();
+            self.entries.push((entry.clone(), source.to_string()));
+            let spellings = std::iter::once(&entry.canonical).chain(&entry.variants);
+            for key in spellings.map(|s| key_of(s)) {
+                match self.keys.get(&key) {
+                    Some(&other) if self.entries[other].0.canonical != entry.canonical => {
+                        let (kept, kept_source) = &self.entries[other];
+                        conflicts.push(GlossaryConflict {
+                            key,
+                            kept: kept.canonical.clone(),
+                            kept_source: kept_source.clone(),
+                            rejected: entry.canonical.clone(),
+                            rejected_source: source.to_string(),
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.max_words = self.max_words.max(key.split(' ').count());
+                        self.keys.insert(key, index);
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Forma canônica de uma grafia (palavra ou expressão, qualquer caixa).
+    pub fn canonical(&self, spelling: &str) -> Option<&str> {
+        self.keys
+            .get(&key_of(spelling))
+            .map(|&i| self.entries[i].0.canonical.as_str())
+    }
+
+    /// Formas canônicas de uma palavra só, em minúsculas (para a busca
+    /// fuzzy achar termos do glossário digitados com erro).
+    pub fn single_words(&self) -> impl Iterator<Item = String> + '_ {
+        self.entries
+            .iter()
+            .map(|(entry, _)| &entry.canonical)
+            .filter(|c| !c.contains(' '))
+            .map(|c| c.to_lowercase())
+    }
+
+    /// Reescreve os termos do glossário num texto corrido, preferindo a
+    /// expressão mais longa. Palavras de um termo precisam estar separadas
+    /// só por espaços; pontuação em volta é mantida.
+    pub fn apply(&self, text: &str) -> String {
+        let words = word_spans(text);
+        let mut out = String::with_capacity(text.len());
+        let mut copied = 0;
+        let mut i = 0;
+        while i < words.len() {
+            let longest = (1..=self.max_words.min(words.len() - i))
+                .rev()
+                .find_map(|n| {
+                    let span = &words[i..i + n];
+                    let joined = span
+                        .windows(2)
+                        .all(|w| text[w[0].1..w[1].0].chars().all(char::is_whitespace));
+                    let (start, end) = (span[0].0, span[n - 1].1);
+                    let canonical = self.canonical(&text[start..end]).filter(|_| joined)?;
+                    Some((n, start, end, canonical))
+                });
+            match longest {
+                Some((n, start, end, canonical)) => {
+                    out.push_str(&text[copied..start]);
+                    out.push_str(&match_case(canonical, &text[start..end]));
+                    copied = end;
+                    i += n;
+                }
+                None => i += 1,
+            }
+        }
+        out.push_str(&text[copied..]);
+        out
+    }
+}
+
+/// Trechos (início, fim) em bytes das palavras de um texto. Hífen e
+/// apóstrofo entre letras fazem parte da palavra (`e-mail`, `d'água`).
+pub(crate) fn word_spans(text: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut spans = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, &(pos, c)) in chars.iter().enumerate() {
+        let inner = (c == '-' || c == '\'')
+            && start.is_some()
+            && chars.get(i + 1).is_some_and(|(_, n)| n.is_alphanumeric());
+        if c.is_alphanumeric() || inner {
+            start.get_or_insert(pos);
+        } else if let Some(s) = start.take() {
+            spans.push((s, pos));
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, text.len()));
+    }
+    spans
+}
+
+/// Aplica a forma canônica respeitando a caixa de quem digitou quando a
+/// forma canônica não impõe nenhuma: `WhatsApp` sai sempre igual, mas
+/// `e-mail` vira `E-mail` no início da frase e `E-MAIL` em caixa alta.
+pub fn match_case(canonical: &str, typed: &str) -> String {
+    if canonical.chars().any(char::is_uppercase) {
+        return canonical.to_string();
+    }
+    let letters: Vec<char> = typed.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        return canonical.to_uppercase();
+    }
+    match (typed.chars().next(), canonical.chars().next()) {
+        (Some(first), Some(c)) if first.is_uppercase() => {
+            c.to_uppercase().collect::<String>() + &canonical[c.len_utf8()..]
+        }
+        _ => canonical.to_string(),
+    }
+}
@@ -0,0 +1,546 @@
+use crate::glossary;
+use crate::typo_model::RuleError;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Extensão dos arquivos de regras gramaticais.
+pub const GRAMMAR_EXTENSION: &str = "tsv";
+
+/// Regras embutidas: crase, há/a, mas/mais, mal/mau, onde/aonde e porquês.
+const BUILTIN_RULES: &str = include_str!("../rules/grammar_pt_br.tsv");
+
+/// Etiquetas calculadas a partir do próprio token (as demais vêm das linhas
+/// `tag` dos arquivos de regras).
+const BUILTIN_TAGS: [&str; 3] = ["NUM", "WORD", "PUNCT"];
+
+/// Confiança de uma regra sem a coluna opcional.
+pub const DEFAULT_CONFIDENCE: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Number,
+    Punct,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    lower: String,
+    kind: TokenKind,
+    /// Trecho em bytes no texto original
+    start: usize,
+    end: usize,
+}
+
+/// Divide o texto em palavras (com hífen e apóstrofo internos), números
+/// (`10`, `10h`, `10:30`) e sinais de pontuação, um por token.
+fn tokenize(text: &str) -> Vec<Token> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let byte_at = |i: usize| chars.get(i).map_or(text.len(), |&(b, _)| b);
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i].1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        if c.is_alphanumeric() {
+            i += 1;
+            while i < chars.len() {
+                let c = chars[i].1;
+                let next_alnum = chars.get(i + 1).is_some_and(|(_, n)| n.is_alphanumeric());
+                let joiner = match c {
+                    '-' | '\'' => next_alnum,
+                    ':' | ',' | '.' => {
+                        next_alnum
+                            && chars[i - 1].1.is_ascii_digit()
+                            && chars[i + 1].1.is_ascii_digit()
+                    }
+                    _ => false,
+                };
+                if !c.is_alphanumeric() && !joiner {
+                    break;
+                }
+                i += 1;
+            }
+        } else {
+            i += 1;
+        }
+        let (start, end) = (byte_at(start), byte_at(i));
+        let kind = match c {
+            c if c.is_ascii_digit() => TokenKind::Number,
+            c if c.is_alphanumeric() => TokenKind::Word,
+            _ => TokenKind::Punct,
+        };
+        tokens.push(Token {
+            lower: text[start..end].to_lowercase(),
+            kind,
+            start,
+            end,
+        });
+    }
+    tokens
+}
+
+fn ends_sentence(token: &Token) -> bool {
+    token.kind == TokenKind::Punct && matches!(token.lower.as_str(), "." | "!" | "?" | "…")
+}
+
+/// Frases do texto, do primeiro ao último token de cada uma (a pontuação
+/// final fica na frase), como trechos em bytes.
+pub fn sentences(text: &str) -> Vec<Range<usize>> {
+    tokenize(text)
+        .split_inclusive(ends_sentence)
+        .filter_map(|sentence| Some(sentence.first()?.start..sentence.last()?.end))
+        .collect()
+}
+
+/// Uma alternativa de um elemento do padrão.
+#[derive(Debug, Clone)]
+enum Atom {
+    /// Forma exata (em minúsculas), inclusive pontuação: `mas`, `?`
+    Word(String),
+    /// Etiqueta: `<NUM>`, `<ART>`
+    Tag(String),
+    /// Lema: `@ir` casa `vou`, `vai`, `iremos`...
+    Lemma(String),
+    /// Expressão regular sobre a forma em minúsculas, inteira: `/.+ado/`
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone)]
+enum Element {
+    /// `^`: começo da frase
+    Start,
+    /// `$`: fim da frase (antes da pontuação final, se houver)
+    End,
+    /// `*`: qualquer sequência de tokens da mesma frase
+    Any,
+    /// `{` e `}`: trecho substituído pela sugestão
+    Open,
+    Close,
+    Token {
+        alternatives: Vec<Atom>,
+        /// `!x`: um token que não é `x` (ou a borda da frase,
+        /// sem consumir nada)
+        negated: bool,
+        /// `x?`: o token pode faltar
+        optional: bool,
+    },
+}
+
+/// Uma regra gramatical: um padrão sobre tokens, a grafia sugerida para o
+/// trecho marcado e a explicação mostrada ao usuário.
+#[derive(Debug, Clone)]
+pub struct GrammarRule {
+    pub id: String,
+    pub suggestion: String,
+    pub message: String,
+    /// De 0 a 1; regras mais sujeitas a falso positivo valem menos
+    pub confidence: f32,
+    pattern: Vec<Element>,
+}
+
+/// Um problema encontrado por [`GrammarEngine::check`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GrammarMatch {
+    pub rule: String,
+    pub message: String,
+    /// Trecho em bytes no texto verificado
+    pub start: usize,
+    pub end: usize,
+    pub original: String,
+    /// Substituição, já com a caixa do trecho original
+    pub suggestion: String,
+    pub confidence: f32,
+}
+
+/// Motor de regras gramaticais do Stage B.
+///
+/// As regras vêm de arquivos TSV com três tipos de linha:
+///
+/// ```text
+/// # etiquetas e lemas usados pelos padrões
+/// tag<TAB>ART<TAB>o a os as um uma uns umas
+/// lemma<TAB>ir<TAB>vou vai vamos vão ir irá
+/// # rule<TAB>id<TAB>padrão<TAB>sugestão<TAB>mensagem[<TAB>confiança]
+/// rule<TAB>crase_horas<TAB>!desde|após {as} <NUM> horas|h<TAB>às<TAB>Crase antes de horas.
+/// ```
+///
+/// O padrão é uma sequência de elementos separados por espaço. Cada
+/// elemento casa um token e pode ter alternativas separadas por `|`: uma
+/// forma (`mas`, `,`), uma etiqueta (`<NUM>`, `<WORD>`, `<PUNCT>` ou as
+/// definidas com `tag`), um lema (`@ir`) ou uma expressão regular sobre a
+/// palavra inteira (`/.+ado/`, sozinha no elemento). `!` nega o elemento e
+/// `?` o torna opcional; `^`, `$` e `*` são o começo da frase, o fim da frase
+/// e qualquer sequência de tokens. Chaves marcam o trecho que a sugestão
+/// substitui (sem chaves, o padrão inteiro). A confiança, de 0 a 1, é
+/// opcional (padrão [`DEFAULT_CONFIDENCE`]).
+#[derive(Debug, Clone, Default)]
+pub struct GrammarEngine {
+    rules: Vec<GrammarRule>,
+    /// Etiqueta → formas
+    tags: HashMap<String, HashSet<String>>,
+    /// Forma → lemas
+    lemmas: HashMap<String, Vec<String>>,
+}
+
+impl GrammarEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Motor com as regras embutidas.
+    pub fn builtin() -> Self {
+        let mut engine = Self::new();
+        engine
+            .add_rules(BUILTIN_RULES)
+            .expect("regras gramaticais embutidas inválidas");
+        engine
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn rules(&self) -> &[GrammarRule] {
+        &self.rules
+    }
+
+    /// Acrescenta as etiquetas, lemas e regras de um arquivo. Como nas regras
+    /// do `TypoModel`, nada é incluído se alguma linha tiver erro. Retorna
+    /// quantas regras entraram.
+    pub fn add_rules(&mut self, text: &str) -> Result<usize, RuleError> {
+        let mut tags = self.tags.clone();
+        let mut lemmas = self.lemmas.clone();
+        let mut pending = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let error = |message: String| RuleError {
+                line: line_no,
+                message,
+            };
+            let line = line.trim_start_matches('\u{feff}').trim_end_matches('\r');
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+            match fields.as_slice() {
+                ["tag", name, words @ ..] if !name.is_empty() && !words.is_empty() => {
+                    tags.entry(name.to_string()).or_default().extend(
+                        words
+                            .iter()
+                            .flat_map(|w| w.split_whitespace())
+                            .map(str::to_lowercase),
+                    );
+                }
+                ["lemma", lemma, forms @ ..] if !lemma.is_empty() && !forms.is_empty() => {
+                    for form in forms.iter().flat_map(|f| f.split_whitespace()) {
+                        lemmas
+                            .entry(form.to_lowercase())
+                            .or_default()
+                            .push(lemma.to_lowercase());
+                    }
+                }
+                ["rule", id, pattern, suggestion, message, confidence @ ..]
+                    if confidence.len() <= 1 =>
+                {
+                    let confidence = match confidence.first() {
+                        Some(value) => value
+                            .parse::<f32>()
+                            .ok()
+                            .filter(|c| (0.0..=1.0).contains(c))
+                            .ok_or_else(|| error(format!("confiança inválida: {}", value)))?,
+                        None => DEFAULT_CONFIDENCE,
+                    };
+                    pending.push((line_no, *id, *pattern, *suggestion, *message, confidence));
+                }
+                ["rule", ..] => return Err(error(
+                    "esperado `rule<TAB>id<TAB>padrão<TAB>sugestão<TAB>mensagem[<TAB>confiança]`"
+                        .into(),
+                )),
+                ["tag" | "lemma", ..] => return Err(error("etiqueta ou lema sem palavras".into())),
+                _ => {
+                    return Err(error(
+                        "linha deve começar com `tag`, `lemma` ou `rule`".into(),
+                    ))
+                }
+            }
+        }
+
+        // Regras por último: podem usar etiquetas definidas mais abaixo
+        let mut rules = Vec::new();
+        for (line, id, pattern, suggestion, message, confidence) in pending {
+            let error = |message: String| RuleError { line, message };
+            if id.is_empty() || message.is_empty() {
+                return Err(error("regra sem id ou sem mensagem".into()));
+            }
+            let pattern = parse_pattern(pattern).map_err(&error)?;
+            for element in &pattern {
+                if let Element::Token { alternatives, .. } = element {
+                    for atom in alternatives {
+                        if let Atom::Tag(tag) = atom {
+                            if !BUILTIN_TAGS.contains(&tag.as_str()) && !tags.contains_key(tag) {
+                                return Err(error(format!("etiqueta desconhecida: <{}>", tag)));
+                            }
+                        }
+                    }
+                }
+            }
+            rules.push(GrammarRule {
+                id: id.to_string(),
+                suggestion: suggestion.to_string(),
+                message: message.to_string(),
+                confidence,
+                pattern,
+            });
+        }
+
+        let added = rules.len();
+        self.tags = tags;
+        self.lemmas = lemmas;
+        self.rules.extend(rules);
+        Ok(added)
+    }
+
+    /// Problemas encontrados no texto, em ordem e sem sobreposição (numa
+    /// mesma posição vale a primeira regra carregada).
+    pub fn check(&self, text: &str) -> Vec<GrammarMatch> {
+        let tokens = tokenize(text);
+        let mut matches = Vec::new();
+        for sentence in tokens.split_inclusive(ends_sentence) {
+            let mut start = 0;
+            while start < sentence.len() {
+                let found = self.rules.iter().find_map(|rule| {
+                    let mut marks = (None, None);
+                    let end = self.match_here(&rule.pattern, sentence, start, &mut marks)?;
+                    let (open, close) = (marks.0.unwrap_or(start), marks.1.unwrap_or(end));
+                    (close > open).then_some((rule, open, close))
+                });
+                let Some((rule, open, close)) = found else {
+                    start += 1;
+                    continue;
+                };
+                let (from, to) = (sentence[open].start, sentence[close - 1].end);
+                let original = &text[from..to];
+                let suggestion = glossary::match_case(&rule.suggestion, original);
+                if suggestion != original {
+                    matches.push(GrammarMatch {
+                        rule: rule.id.clone(),
+                        message: rule.message.clone(),
+                        start: from,
+                        end: to,
+                        original: original.to_string(),
+                        suggestion,
+                        confidence: rule.confidence,
+                    });
+                }
+                start = close.max(start + 1);
+            }
+        }
+        matches
+    }
+
+    /// Aplica todas as sugestões de [`check`](Self::check).
+    pub fn apply(&self, text: &str) -> String {
+        apply_matches(text, &self.check(text))
+    }
+
+    /// Tenta casar `pattern` a partir do token `at`; devolve onde terminou.
+    /// `marks` recebe as posições de `{` e `}`.
+    fn match_here(
+        &self,
+        pattern: &[Element],
+        tokens: &[Token],
+        at: usize,
+        marks: &mut (Option<usize>, Option<usize>),
+    ) -> Option<usize> {
+        let Some((element, rest)) = pattern.split_first() else {
+            return Some(at);
+        };
+        let at_end = at == tokens.len() || (at + 1 == tokens.len() && ends_sentence(&tokens[at]));
+        match element {
+            Element::Start => (at == 0).then(|| self.match_here(rest, tokens, at, marks))?,
+            Element::End => at_end.then(|| self.match_here(rest, tokens, at, marks))?,
+            Element::Any => {
+                (at..=tokens.len()).find_map(|next| self.match_here(rest, tokens, next, marks))
+            }
+            Element::Open | Element::Close => {
+                let saved = *marks;
+                if matches!(element, Element::Open) {
+                    marks.0 = Some(at);
+                } else {
+                    marks.1 = Some(at);
+                }
+                let result = self.match_here(rest, tokens, at, marks);
+                if result.is_none() {
+                    *marks = saved;
+                }
+                result
+            }
+            Element::Token {
+                alternatives,
+                negated,
+                optional,
+            } => {
+                let hit = tokens
+                    .get(at)
+                    .map(|t| alternatives.iter().any(|a| self.atom_matches(a, t)));
+                let consumed = match (hit, negated) {
+                    (Some(hit), _) if hit != *negated => {
+                        self.match_here(rest, tokens, at + 1, marks)
+                    }
+                    _ => None,
+                };
+                consumed
+                    .or_else(|| {
+                        (*negated && (at == 0 || at == tokens.len()))
+                            .then(|| self.match_here(rest, tokens, at, marks))
+                            .flatten()
+                    })
+                    .or_else(|| {
+                        optional
+                            .then(|| self.match_here(rest, tokens, at, marks))
+                            .flatten()
+                    })
+            }
+        }
+    }
+
+    fn atom_matches(&self, atom: &Atom, token: &Token) -> bool {
+        match atom {
+            Atom::Word(word) => token.lower == *word,
+            Atom::Tag(tag) => match tag.as_str() {
+                "NUM" => token.kind == TokenKind::Number,
+                "WORD" => token.kind == TokenKind::Word,
+                "PUNCT" => token.kind == TokenKind::Punct,
+                _ => self
+                    .tags
+                    .get(tag)
+                    .is_some_and(|words| words.contains(&token.lower)),
+            },
+            Atom::Lemma(lemma) => {
+                token.lower == *lemma
+                    || self
+                        .lemmas
+                        .get(&token.lower)
+                        .is_some_and(|lemmas| lemmas.contains(lemma))
+            }
+            Atom::Regex(regex) => token.kind != TokenKind::Punct && regex.is_match(&token.lower),
+        }
+    }
+}
+
+fn parse_pattern(pattern: &str) -> Result<Vec<Element>, String> {
+    let mut elements = Vec::new();
+    let mut open = false;
+    for piece in pattern.split_whitespace() {
+        let mut piece = piece;
+        if let Some(rest) = piece
+            .strip_prefix('{')
+            .filter(|r| !r.is_empty() || piece == "{")
+        {
+            if open || elements.iter().any(|e| matches!(e, Element::Open)) {
+                return Err("mais de um trecho marcado com `{`".into());
+            }
+            elements.push(Element::Open);
+            open = true;
+            piece = rest;
+        }
+        let close = piece.len() > 1 && piece.ends_with('}');
+        if close {
+            piece = &piece[..piece.len() - 1];
+        }
+        match piece {
+            "" => {}
+            "^" => elements.push(Element::Start),
+            "$" => elements.push(Element::End),
+            "*" => elements.push(Element::Any),
+            "}" => {
+                if !open {
+                    return Err("`}` sem `{`".into());
+                }
+                elements.push(Element::Close);
+                open = false;
+            }
+            _ => elements.push(parse_element(piece)?),
+        }
+        if close {
+            if !open {
+                return Err("`}` sem `{`".into());
+            }
+            elements.push(Element::Close);
+            open = false;
+        }
+    }
+    if open {
+        return Err("`{` sem `}`".into());
+    }
+    if !elements.iter().any(|e| matches!(e, Element::Token { .. })) {
+        return Err("padrão sem nenhum token".into());
+    }
+    Ok(elements)
+}
+
+fn parse_element(piece: &str) -> Result<Element, String> {
+    let (negated, piece) = match piece.strip_prefix('!') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, piece),
+    };
+    let (optional, piece) = match piece.strip_suffix('?') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, piece),
+    };
+    let alternatives = if piece.len() > 2 && piece.starts_with('/') && piece.ends_with('/') {
+        let source = &piece[1..piece.len() - 1];
+        let regex = Regex::new(&format!("^(?:{})$", source))
+            .map_err(|e| format!("expressão regular inválida {}: {}", piece, e))?;
+        vec![Atom::Regex(regex)]
+    } else {
+        piece
+            .split('|')
+            .map(|alt| match alt {
+                "" => Err(format!("alternativa vazia em {}", piece)),
+                _ if alt.len() > 2 && alt.starts_with('<') && alt.ends_with('>') => {
+                    Ok(Atom::Tag(alt[1..alt.len() - 1].to_string()))
+                }
+                _ if alt.len() > 1 && alt.starts_with('@') => {
+                    Ok(Atom::Lemma(alt[1..].to_lowercase()))
+                }
+                _ => Ok(Atom::Word(alt.to_lowercase())),
+            })
+            .collect::<Result<_, _>>()?
+    };
+    Ok(Element::Token {
+        alternatives,
+        negated,
+        optional,
+    })
+}
+
+/// Aplica ao texto as sugestões de [`GrammarEngine::check`] feito nele.
+pub fn apply_matches(text: &str, matches: &[GrammarMatch]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for m in matches {
+        out.push_str(&text[copied..m.start]);
+        out.push_str(&m.suggestion);
+        copied = m.end;
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+/// Arquivos de regras (`*.tsv`) de um diretório, em ordem alfabética.
+pub fn discover_grammars(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    crate::paths::files_with_extension(dir, GRAMMAR_EXTENSION)
+}
@@ -0,0 +1,223 @@
+use crate::edit::Edit;
+use crate::shared::SharedEngine;
+use crate::stage_c::Rewrite;
+use serde::Serialize;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Limites para aceitar as edições de uma reescrita do modelo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuardOptions {
+    /// Edições por frase; acima disso a reescrita inteira é descartada
+    pub max_edits: usize,
+    /// Palavras de cada lado de uma edição
+    pub max_edit_words: usize,
+    /// Fração das palavras da frase que as edições podem tocar (frases de
+    /// até `max_edit_words` palavras não passam por esse limite)
+    pub max_changed_ratio: f32,
+    /// Probabilidade mínima dos tokens de uma edição, quando o modelo a
+    /// informa
+    pub min_probability: f32,
+}
+
+impl Default for GuardOptions {
+    fn default() -> Self {
+        Self {
+            max_edits: 3,
+            max_edit_words: 3,
+            max_changed_ratio: 0.4,
+            min_probability: 0.5,
+        }
+    }
+}
+
+/// Por que uma edição do modelo foi descartada.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardReason {
+    /// A reescrita tem edições demais
+    TooManyEdits,
+    /// A reescrita mexe em boa parte da frase
+    TooMuchChanged,
+    /// Mexe em número, URL, e-mail ou nome próprio
+    Protected,
+    /// Troca palavras demais de uma vez
+    EditTooLong,
+    /// Introduz uma palavra fora do dicionário
+    UnknownWord(String),
+    /// O modelo não tinha certeza
+    LowProbability(f32),
+}
+
+/// Resultado da revisão de uma reescrita: as edições aceitas e as
+/// descartadas, com o motivo.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GuardReview {
+    pub accepted: Vec<Edit>,
+    pub rejected: Vec<(Edit, GuardReason)>,
+}
+
+/// Filtro contra alucinações do Stage C: um modelo pode reescrever a frase
+/// inteira, trocar nomes e números ou inventar palavras. Só passam edições
+/// pequenas, em poucas palavras, sobre texto comum, que levam a palavras do
+/// dicionário e em que o modelo confia.
+pub struct RewriteGuard {
+    pub options: GuardOptions,
+    dictionary: Arc<SharedEngine>,
+}
+
+impl RewriteGuard {
+    /// Filtro que confere as palavras novas no dicionário do engine (em
+    /// geral o mesmo do Stage A). Com um engine vazio, nenhuma palavra nova
+    /// passa.
+    pub fn new(options: GuardOptions, dictionary: Arc<SharedEngine>) -> Self {
+        Self {
+            options,
+            dictionary,
+        }
+    }
+
+    /// Revisa as edições (de [`Edit::from_model`]) que levam `original` à
+    /// reescrita.
+    pub fn review(&self, original: &str, rewrite: &Rewrite, edits: Vec<Edit>) -> GuardReview {
+        let options = &self.options;
+        let words = words(original);
+
+        // A reescrita como um todo
+        let touched = words
+            .iter()
+            .filter(|word| edits.iter().any(|e| overlaps(&e.range.bytes, word)))
+            .count()
+            + edits.iter().filter(|e| e.range.bytes.is_empty()).count();
+        let global = if edits.len() > options.max_edits {
+            Some(GuardReason::TooManyEdits)
+        } else if words.len() > options.max_edit_words
+            && touched as f32 > options.max_changed_ratio * words.len() as f32
+        {
+            Some(GuardReason::TooMuchChanged)
+        } else {
+            None
+        };
+        if let Some(reason) = global {
+            return GuardReview {
+                accepted: Vec::new(),
+                rejected: edits.into_iter().map(|e| (e, reason.clone())).collect(),
+            };
+        }
+
+        let snapshot = self.dictionary.snapshot();
+        let known = |word: &str| snapshot.contains_word(word);
+        let mut review = GuardReview::default();
+        for edit in edits {
+            match self.check(original, rewrite, &edit, &known) {
+                Some(reason) => review.rejected.push((edit, reason)),
+                None => review.accepted.push(edit),
+            }
+        }
+        review
+    }
+
+    fn check(
+        &self,
+        original: &str,
+        rewrite: &Rewrite,
+        edit: &Edit,
+        known: &dyn Fn(&str) -> bool,
+    ) -> Option<GuardReason> {
+        let options = &self.options;
+        let range = &edit.range.bytes;
+
+        // Trechos entre espaços que a edição toca (uma URL vira vários
+        // tokens no diff, mas é um trecho só)
+        let protected = chunks(original)
+            .into_iter()
+            .filter(|chunk| {
+                overlaps(range, chunk) || chunk.start < range.start && range.start < chunk.end
+            })
+            .any(|chunk| is_protected(original, chunk, known));
+        if protected
+            || edit.replacement.chars().any(|c| c.is_ascii_digit())
+            || is_address(&edit.replacement)
+        {
+            return Some(GuardReason::Protected);
+        }
+
+        let new_words = words(&edit.replacement);
+        if words(&edit.original).len().max(new_words.len()) > options.max_edit_words {
+            return Some(GuardReason::EditTooLong);
+        }
+
+        // Palavras que já estavam na frase (uma troca de ordem) não contam
+        let present: Vec<&str> = words(original).into_iter().map(|w| &original[w]).collect();
+        let unknown = new_words
+            .into_iter()
+            .map(|word| &edit.replacement[word])
+            .find(|word| !known(word) && !present.contains(word));
+        if let Some(word) = unknown {
+            return Some(GuardReason::UnknownWord(word.to_string()));
+        }
+
+        if !rewrite.tokens.is_empty() && edit.confidence < options.min_probability {
+            return Some(GuardReason::LowProbability(edit.confidence));
+        }
+        None
+    }
+}
+
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+/// Palavras (letras, dígitos, `-` e `'`) do texto, como trechos em bytes.
+fn words(text: &str) -> Vec<Range<usize>> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '-' || c == '\'';
+    let mut words: Vec<Range<usize>> = Vec::new();
+    let mut inside = false;
+    for (i, c) in text.char_indices() {
+        match (is_word(c), inside, words.last_mut()) {
+            (true, true, Some(word)) => word.end = i + c.len_utf8(),
+            (true, _, _) => words.push(i..i + c.len_utf8()),
+            _ => {}
+        }
+        inside = is_word(c);
+    }
+    words
+}
+
+/// Trechos separados por espaço em branco.
+fn chunks(text: &str) -> Vec<Range<usize>> {
+    text.split_whitespace()
+        .map(|chunk| {
+            let start = chunk.as_ptr() as usize - text.as_ptr() as usize;
+            start..start + chunk.len()
+        })
+        .collect()
+}
+
+fn is_address(chunk: &str) -> bool {
+    let lower = chunk.to_lowercase();
+    lower.contains("://")
+        || lower.starts_with("www.")
+        || lower
+            .split_once('@')
+            .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'))
+}
+
+/// Números, endereços e nomes próprios ficam como estão. Uma palavra com
+/// maiúscula no meio da frase é nome; no começo da frase, só se o
+/// dicionário não a conhece.
+fn is_protected(text: &str, chunk: Range<usize>, known: &dyn Fn(&str) -> bool) -> bool {
+    let part = &text[chunk.clone()];
+    if part.chars().any(|c| c.is_ascii_digit()) || is_address(part) {
+        return true;
+    }
+    let sentence_start = text[..chunk.start]
+        .trim_end()
+        .chars()
+        .next_back()
+        .is_none_or(|c| matches!(c, '.' | '!' | '?' | '…'));
+    words(part).into_iter().any(|word| {
+        let word = &part[word];
+        word.chars().next().is_some_and(char::is_uppercase) && (!sentence_start || !known(word))
+    })
+}
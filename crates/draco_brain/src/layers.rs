@@ -0,0 +1,187 @@
+use crate::phonetic::PhoneticIndex;
+use crate::trie::Trie;
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Extensão dos arquivos de pacote de domínio.
+pub const PACK_EXTENSION: &str = "txt";
+
+/// Papel de uma camada na pilha de dicionários. Define a prioridade padrão:
+/// base < norma ortográfica < pacotes de domínio < equipe < glossário < usuário.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayerKind {
+    /// Dicionário geral carregado dos arquivos de dados
+    Base,
+    /// Palavras da norma escolhida que faltam na base (ex.: português europeu)
+    Variant,
+    /// Vocabulário de um domínio (jurídico, médico, software...)
+    Pack,
+    /// Vocabulário compartilhado pela equipe
+    Team,
+    /// Termos de uma palavra do glossário (mantida pelo próprio `StageA`)
+    Glossary,
+    /// Palavras adicionadas pelo usuário em tempo de execução
+    User,
+}
+
+impl LayerKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LayerKind::Base => "base",
+            LayerKind::Variant => "variante",
+            LayerKind::Pack => "pacote",
+            LayerKind::Team => "equipe",
+            LayerKind::Glossary => "glossário",
+            LayerKind::User => "usuário",
+        }
+    }
+
+    pub fn default_priority(self) -> i32 {
+        match self {
+            LayerKind::Base => 0,
+            LayerKind::Variant => 5,
+            LayerKind::Pack => 10,
+            LayerKind::Team => 20,
+            LayerKind::Glossary => 25,
+            LayerKind::User => 30,
+        }
+    }
+}
+
+/// Vocabulário de uma camada: trie para busca fuzzy + índice fonético.
+#[derive(Clone, Default)]
+pub(crate) struct Lexicon {
+    pub(crate) trie: Trie,
+    pub(crate) phonetic_index: PhoneticIndex,
+}
+
+impl Lexicon {
+    pub(crate) fn insert(&mut self, word: &str, frequency: u32) {
+        self.trie.insert_with_frequency(word, frequency);
+        self.phonetic_index.insert(word);
+    }
+}
+
+/// Uma camada nomeada da pilha de dicionários do [`StageA`](crate::stage_a::StageA).
+///
+/// Quando mais de uma camada ligada conhece a mesma palavra, vale a de
+/// maior prioridade: é ela que aparece como origem do candidato e cuja
+/// frequência é usada. Clonar é barato (o vocabulário fica em `Arc`), então
+/// ligar, desligar ou repriorizar uma camada não copia nenhum dicionário.
+#[derive(Clone)]
+pub struct DictionaryLayer {
+    name: Arc<str>,
+    kind: LayerKind,
+    enabled: bool,
+    priority: i32,
+    lexicon: Arc<Lexicon>,
+}
+
+impl DictionaryLayer {
+    /// Camada vazia, ligada, com a prioridade padrão do tipo.
+    pub fn new(name: &str, kind: LayerKind) -> Self {
+        Self {
+            name: Arc::from(name),
+            kind,
+            enabled: true,
+            priority: kind.default_priority(),
+            lexicon: Arc::new(Lexicon::default()),
+        }
+    }
+
+    /// Camada com as palavras de `entries` (frequência 0 = usar a da tabela
+    /// de frequências do engine).
+    pub fn from_entries(name: &str, kind: LayerKind, entries: &[(String, u32)]) -> Self {
+        let mut layer = Self::new(name, kind);
+        for (word, frequency) in entries {
+            layer.insert(word, *frequency);
+        }
+        layer.lexicon_mut().trie.shrink_to_fit();
+        layer
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Acrescenta uma palavra (em minúsculas). Se outras cópias da camada
+    /// estiverem vivas, o vocabulário é copiado antes (copy-on-write).
+    pub fn insert(&mut self, word: &str, frequency: u32) {
+        let lower = word.to_lowercase();
+        if !lower.is_empty() {
+            Arc::make_mut(&mut self.lexicon).insert(&lower, frequency);
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> LayerKind {
+        self.kind
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    pub fn len(&self) -> usize {
+        self.lexicon.trie.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lexicon.trie.is_empty()
+    }
+
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub(crate) fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
+    pub(crate) fn lexicon(&self) -> &Lexicon {
+        &self.lexicon
+    }
+
+    pub(crate) fn lexicon_mut(&mut self) -> &mut Lexicon {
+        Arc::make_mut(&mut self.lexicon)
+    }
+}
+
+impl fmt::Debug for DictionaryLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DictionaryLayer")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .field("enabled", &self.enabled)
+            .field("priority", &self.priority)
+            .field("words", &self.len())
+            .finish()
+    }
+}
+
+/// Arquivos de pacote (`*.txt`) de um diretório, em ordem alfabética. O
+/// nome do pacote é o nome do arquivo sem a extensão (ver [`pack_name`]).
+pub fn discover_packs(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    crate::paths::files_with_extension(dir, PACK_EXTENSION)
+}
+
+/// Nome do pacote de um arquivo: `packs/juridico.txt` → `juridico`.
+pub fn pack_name(path: &Path) -> Option<&str> {
+    path.file_stem().and_then(|s| s.to_str())
+}
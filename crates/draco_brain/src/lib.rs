@@ -1,10 +1,34 @@
+pub mod blocklist;
+pub mod builder;
+pub mod cache;
+pub mod chunk;
 pub mod dict_loader;
+pub mod edit;
+pub mod embedded;
+// `encode` só é usado pelo build.rs (e pelos testes)
+#[allow(dead_code)]
+mod front_coding;
+pub mod glossary;
+pub mod grammar;
+pub mod guard;
+pub mod layers;
+pub mod manifest;
+pub mod orthography;
+pub mod paths;
 pub mod phonetic;
+pub mod rerank;
+pub mod seq2seq;
+pub mod shared;
 pub mod stage_a;
 pub mod stage_b;
 pub mod stage_c;
+pub mod trace;
 pub mod trie;
 pub mod typo_model;
+pub mod variant;
+pub mod watcher;
+
+pub use builder::{EngineBuilder, EngineConfig, LoadReport};
 
 #[cfg(test)]
 mod validation;
@@ -0,0 +1,308 @@
+use crate::seq2seq::{self, DecodeOptions, DecodeStrategy};
+use crate::stage_c::{StageCError, TAGGER_FILE};
+use ort::session::Session;
+use ort::tensor::TensorElementType;
+use ort::value::ValueType;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Manifesto do modelo do Stage C, na raiz do diretório do modelo.
+pub const MANIFEST_FILE: &str = "model.json";
+
+/// Versão do formato do manifesto que este código entende.
+pub const MANIFEST_FORMAT: u32 = 1;
+
+pub const TOKENIZER_FILE: &str = "tokenizer.json";
+
+/// O que o modelo faz com a frase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelTask {
+    /// Um grafo que devolve um token corrigido por token de entrada
+    Tagger,
+    /// Encoder e decoder: reescrita autorregressiva
+    #[serde(rename = "seq2seq")]
+    Seq2Seq,
+    /// Modelo de linguagem mascarado (estilo BERT), que só dá notas a
+    /// candidatos no contexto (ver [`StageC::score_candidates`](crate::stage_c::StageC::score_candidates))
+    MaskedLm,
+}
+
+/// Arquivos do modelo, relativos ao diretório do manifesto.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelFiles {
+    /// Grafo do `tagger` e do `masked_lm`
+    pub model: String,
+    /// Grafos do `seq2seq`
+    pub encoder: String,
+    pub decoder: String,
+    pub tokenizer: String,
+}
+
+impl Default for ModelFiles {
+    fn default() -> Self {
+        Self {
+            model: TAGGER_FILE.to_string(),
+            encoder: seq2seq::ENCODER_FILE.to_string(),
+            decoder: seq2seq::DECODER_FILES[0].to_string(),
+            tokenizer: TOKENIZER_FILE.to_string(),
+        }
+    }
+}
+
+/// Nomes das entradas do grafo (do encoder, no `seq2seq`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelInputs {
+    pub input_ids: String,
+    /// `null` se o grafo não recebe máscara
+    pub attention_mask: Option<String>,
+}
+
+impl Default for ModelInputs {
+    fn default() -> Self {
+        Self {
+            input_ids: "input_ids".to_string(),
+            attention_mask: Some("attention_mask".to_string()),
+        }
+    }
+}
+
+/// Nomes das saídas do grafo (do decoder, no `seq2seq`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelOutputs {
+    /// Logits `[lote, posição, vocabulário]`; sem nome, a primeira saída
+    pub logits: Option<String>,
+}
+
+/// Ids de tokens especiais que o modelo não informa em outro lugar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpecialTokens {
+    pub decoder_start: Option<u32>,
+    pub eos: Option<u32>,
+    /// Token de máscara do `masked_lm`; sem ele, `[MASK]` ou `<mask>` do tokenizer
+    pub mask: Option<u32>,
+    /// Preenchimento das frases mais curtas de um lote; sem ele, o do
+    /// tokenizer, `[PAD]`, `<pad>` ou 0
+    pub pad: Option<u32>,
+}
+
+/// Descrição de um modelo do Stage C:
+///
+/// ```json
+/// {
+///   "format": 1,
+///   "task": "seq2seq",
+///   "version": "corretor-t5-small-2025.03",
+///   "files": { "encoder": "encoder_model.onnx", "decoder": "decoder_model_merged.onnx" },
+///   "inputs": { "input_ids": "input_ids", "attention_mask": "attention_mask" },
+///   "outputs": { "logits": "logits" },
+///   "max_sequence_length": 256,
+///   "special_tokens": { "decoder_start": 0, "eos": 1 },
+///   "strategy": { "beam": { "width": 4 } },
+///   "threads": 1
+/// }
+/// ```
+///
+/// Só `task` é obrigatório; o resto tem os padrões dos exports do Optimum.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelManifest {
+    #[serde(default = "default_format")]
+    pub format: u32,
+    pub task: ModelTask,
+    /// Versão do modelo, para logs e relatórios
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub files: ModelFiles,
+    #[serde(default)]
+    pub inputs: ModelInputs,
+    #[serde(default)]
+    pub outputs: ModelOutputs,
+    /// Tokens de entrada aceitos (e gerados, no `seq2seq`) no máximo;
+    /// textos maiores são divididos em trechos (ver [`chunk`](crate::chunk::chunk))
+    #[serde(default)]
+    pub max_sequence_length: Option<usize>,
+    #[serde(default)]
+    pub special_tokens: SpecialTokens,
+    /// Decodificação do `seq2seq`; sem ela, vale a da configuração do modelo
+    #[serde(default)]
+    pub strategy: Option<DecodeStrategy>,
+    #[serde(default = "default_threads")]
+    pub threads: usize,
+}
+
+fn default_format() -> u32 {
+    MANIFEST_FORMAT
+}
+
+fn default_threads() -> usize {
+    1
+}
+
+impl ModelManifest {
+    /// Manifesto com os padrões para a tarefa.
+    pub fn new(task: ModelTask) -> Self {
+        Self {
+            format: MANIFEST_FORMAT,
+            task,
+            version: String::new(),
+            files: ModelFiles::default(),
+            inputs: ModelInputs::default(),
+            outputs: ModelOutputs::default(),
+            max_sequence_length: None,
+            special_tokens: SpecialTokens::default(),
+            strategy: None,
+            threads: default_threads(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let manifest: Self = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if manifest.format != MANIFEST_FORMAT {
+            return Err(format!(
+                "formato {} não suportado (esperado {})",
+                manifest.format, MANIFEST_FORMAT
+            ));
+        }
+        if manifest.threads == 0 {
+            return Err("`threads` deve ser ao menos 1".to_string());
+        }
+        if manifest.max_sequence_length == Some(0) {
+            return Err("`max_sequence_length` deve ser ao menos 1".to_string());
+        }
+        Ok(manifest)
+    }
+
+    /// Lê o `model.json` do diretório ou, sem ele, deduz o manifesto dos
+    /// arquivos presentes (ver [`detect_model_kind`](crate::stage_c::detect_model_kind)).
+    pub fn from_dir(dir: &Path) -> Result<Self, StageCError> {
+        let path = dir.join(MANIFEST_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                Self::parse(&text).map_err(|message| StageCError::Manifest { path, message })
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::infer(dir),
+            Err(e) => Err(StageCError::Manifest {
+                path,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    fn infer(dir: &Path) -> Result<Self, StageCError> {
+        use crate::stage_c::{detect_model_kind, ModelKind};
+        let file_name = |path: &Path| {
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        };
+        match detect_model_kind(dir) {
+            Some(ModelKind::Tagger(model)) => {
+                let mut manifest = Self::new(ModelTask::Tagger);
+                manifest.files.model = file_name(&model);
+                Ok(manifest)
+            }
+            Some(ModelKind::Seq2Seq { encoder, decoder }) => {
+                let mut manifest = Self::new(ModelTask::Seq2Seq);
+                manifest.files.encoder = file_name(&encoder);
+                manifest.files.decoder = file_name(&decoder);
+                Ok(manifest)
+            }
+            None => Err(StageCError::NoModel(dir.to_path_buf())),
+        }
+    }
+
+    /// Caminhos dos grafos da tarefa e do tokenizer, que precisam existir.
+    pub fn paths(&self, dir: &Path) -> Vec<PathBuf> {
+        let graphs: &[&String] = match self.task {
+            ModelTask::Tagger | ModelTask::MaskedLm => &[&self.files.model],
+            ModelTask::Seq2Seq => &[&self.files.encoder, &self.files.decoder],
+        };
+        graphs
+            .iter()
+            .chain([&&self.files.tokenizer])
+            .map(|file| dir.join(file))
+            .collect()
+    }
+
+    /// A decodificação da configuração do modelo com o que o manifesto fixa.
+    pub fn decode_options(&self, dir: &Path) -> DecodeOptions {
+        let mut options = DecodeOptions::from_model_dir(dir);
+        if let Some(id) = self.special_tokens.decoder_start {
+            options.decoder_start_token_id = id;
+        }
+        if let Some(id) = self.special_tokens.eos {
+            options.eos_token_id = id;
+        }
+        if let Some(length) = self.max_sequence_length {
+            options.max_length = length;
+        }
+        if let Some(strategy) = self.strategy {
+            options.strategy = strategy;
+        }
+        options
+    }
+}
+
+/// Confere as entradas e a saída de logits de um grafo carregado.
+///
+/// Toda entrada do grafo tem de estar em `provided` (o que o Stage C sabe
+/// alimentar) e todo nome em `required` tem de ser entrada do grafo. A saída
+/// de logits (`logits`, ou a primeira) tem de ser um tensor `f32` de três
+/// dimensões.
+pub(crate) fn check_session(
+    session: &Session,
+    file: &Path,
+    provided: &dyn Fn(&str) -> bool,
+    required: &[&str],
+    logits: Option<&str>,
+) -> Result<(), StageCError> {
+    let file = file.to_path_buf();
+    for input in session.inputs() {
+        if !provided(input.name()) {
+            return Err(StageCError::UnexpectedInput {
+                file,
+                name: input.name().to_string(),
+            });
+        }
+    }
+    for &name in required {
+        if !session.inputs().iter().any(|input| input.name() == name) {
+            return Err(StageCError::MissingInput {
+                file,
+                name: name.to_string(),
+            });
+        }
+    }
+
+    let output = match logits {
+        Some(name) => session
+            .outputs()
+            .iter()
+            .find(|output| output.name() == name),
+        None => session.outputs().first(),
+    };
+    let Some(output) = output else {
+        return Err(StageCError::MissingOutput {
+            file,
+            name: logits.unwrap_or("logits").to_string(),
+        });
+    };
+    match output.dtype() {
+        ValueType::Tensor { ty, shape, .. }
+            if *ty == TensorElementType::Float32 && shape.len() == 3 =>
+        {
+            Ok(())
+        }
+        other => Err(StageCError::OutputType {
+            file,
+            name: output.name().to_string(),
+            found: other.to_string(),
+        }),
+    }
+}
@@ -0,0 +1,258 @@
+use serde::Serialize;
+
+/// Regra do Acordo Ortográfico de 1990 aplicada por [`modernize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrthographyRule {
+    /// Trema abolido: `lingüiça` → `linguiça`
+    Diaeresis,
+    /// Ditongo aberto em paroxítona: `idéia` → `ideia`, `heróico` → `heroico`
+    OpenDiphthong,
+    /// Hiato `ôo`/`êem`: `vôo` → `voo`, `crêem` → `creem`
+    Hiatus,
+    /// `i`/`u` tônicos após ditongo e `ú` em `gue`/`gui`: `feiúra` → `feiura`
+    StressedVowel,
+    /// Acento diferencial: `pára` → `para`, `pêlo` → `pelo`
+    DifferentialAccent,
+    /// Hífen com prefixos: `auto-escola` → `autoescola`, `microondas` → `micro-ondas`
+    PrefixHyphen,
+}
+
+impl OrthographyRule {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrthographyRule::Diaeresis => "diaeresis",
+            OrthographyRule::OpenDiphthong => "open_diphthong",
+            OrthographyRule::Hiatus => "hiatus",
+            OrthographyRule::StressedVowel => "stressed_vowel",
+            OrthographyRule::DifferentialAccent => "differential_accent",
+            OrthographyRule::PrefixHyphen => "prefix_hyphen",
+        }
+    }
+}
+
+/// Grafia atual de uma palavra e as regras que a mudaram.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Modernization {
+    pub word: String,
+    pub rules: Vec<OrthographyRule>,
+}
+
+/// Acentos diferenciais abolidos (`pôr` e `pôde` continuam).
+const DIFFERENTIAL: &[(&str, &str)] = &[
+    ("pára", "para"),
+    ("péla", "pela"),
+    ("pélas", "pelas"),
+    ("pélo", "pelo"),
+    ("pêlo", "pelo"),
+    ("pêlos", "pelos"),
+    ("pólo", "polo"),
+    ("pólos", "polos"),
+    ("pêra", "pera"),
+    ("pêras", "peras"),
+];
+
+/// Prefixos terminados em vogal sujeitos às regras de hífen do Acordo.
+/// `co-` e `re-` ficam de fora: sempre se juntam (`coautor`, `reeleição`).
+const PREFIXES: &[&str] = &[
+    "aero", "agro", "ante", "anti", "arqui", "auto", "bio", "contra", "eco", "eletro", "extra",
+    "foto", "geo", "hidro", "infra", "intra", "macro", "maxi", "micro", "mini", "multi", "neo",
+    "pluri", "poli", "proto", "pseudo", "retro", "semi", "supra", "tele", "ultra",
+];
+
+/// Letras que podem disparar alguma regra; palavras sem nenhuma delas (a
+/// grande maioria) só passam pela checagem de prefixo colado.
+const TRIGGERS: &[char] = &['ü', 'é', 'ó', 'ô', 'ê', 'ú', 'í', 'á', '-'];
+
+/// Uma regra aplicada a um trecho sem hífen; diz se mudou algo.
+type Pass = fn(&mut [char]) -> bool;
+
+/// Converte uma palavra (em minúsculas) escrita pelas regras anteriores ao
+/// Acordo de 1990 para a grafia atual. `None` se ela já está atualizada.
+pub fn modernize(word: &str) -> Option<Modernization> {
+    if let Some(&(_, modern)) = DIFFERENTIAL.iter().find(|(old, _)| *old == word) {
+        return Some(Modernization {
+            word: modern.to_string(),
+            rules: vec![OrthographyRule::DifferentialAccent],
+        });
+    }
+
+    let mut rules = Vec::new();
+    let mut word = match join_prefix(word) {
+        Some(joined) => {
+            rules.push(OrthographyRule::PrefixHyphen);
+            joined
+        }
+        None if word.contains(TRIGGERS) => word.to_string(),
+        None => return None,
+    };
+
+    let mut segments: Vec<Vec<char>> = word.split('-').map(|s| s.chars().collect()).collect();
+    let passes: [(OrthographyRule, Pass); 4] = [
+        (OrthographyRule::Diaeresis, drop_diaeresis),
+        (OrthographyRule::OpenDiphthong, drop_open_diphthong),
+        (OrthographyRule::Hiatus, drop_hiatus),
+        (OrthographyRule::StressedVowel, drop_stressed_vowel),
+    ];
+    for (rule, pass) in passes {
+        let mut changed = false;
+        for segment in &mut segments {
+            changed |= pass(segment);
+        }
+        if changed {
+            rules.push(rule);
+        }
+    }
+    if rules.is_empty() {
+        return None;
+    }
+    if rules != [OrthographyRule::PrefixHyphen] {
+        word = segments
+            .iter()
+            .map(|s| s.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("-");
+    }
+    Some(Modernization { word, rules })
+}
+
+/// Parte depois do prefixo quando a palavra é um composto com prefixo já
+/// na grafia atual e com hífen obrigatório (`anti-inflamatório`,
+/// `micro-ondas`, `anti-higiênico`). Usado para aceitar esses compostos
+/// quando o dicionário só conhece a segunda parte.
+pub fn hyphenated_base(word: &str) -> Option<&str> {
+    let (prefix, base) = word.split_once('-')?;
+    let last = prefix.chars().last()?;
+    let first = base.chars().next()?;
+    (PREFIXES.contains(&prefix) && (first == 'h' || base_letter(first) == last)).then_some(base)
+}
+
+/// Aplica as regras de hífen dos prefixos terminados em vogal:
+/// mesma vogal ou `h` → hífen; `r`/`s` → junta dobrando; o resto → junta.
+fn join_prefix(word: &str) -> Option<String> {
+    if let Some((prefix, base)) = word.split_once('-') {
+        if !PREFIXES.contains(&prefix) || base.contains('-') {
+            return None;
+        }
+        let first = base.chars().next()?;
+        return match first {
+            'h' => None,
+            _ if base_letter(first) == prefix.chars().last()? => None,
+            'r' | 's' => Some(format!("{}{}{}", prefix, first, base)),
+            _ => Some(format!("{}{}", prefix, base)),
+        };
+    }
+    // Colado pela regra antiga: `antiinflamatório`, `microondas`
+    PREFIXES.iter().find_map(|prefix| {
+        let base = word.strip_prefix(prefix)?;
+        let first = base.chars().next()?;
+        (base.chars().count() >= 3 && base_letter(first) == prefix.chars().last()?)
+            .then(|| format!("{}-{}", prefix, base))
+    })
+}
+
+fn base_letter(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' => 'a',
+        'é' | 'ê' => 'e',
+        'í' => 'i',
+        'ó' | 'ô' | 'õ' => 'o',
+        'ú' | 'ü' => 'u',
+        c => c,
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(base_letter(c), 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+/// O que vem depois da sílaba tônica numa paroxítona: uma sílaba só,
+/// terminada em vogal, opcionalmente seguida de `s` ou `m` (`-a`, `-cos`, `-am`).
+/// Vogais em hiato contam como sílabas separadas (`-deo` tem duas).
+fn is_paroxytone_tail(rest: &[char]) -> bool {
+    let rest = match rest.last() {
+        Some('s' | 'm') => &rest[..rest.len() - 1],
+        _ => rest,
+    };
+    let mut syllables = 0;
+    for (i, &c) in rest.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|p| rest[p]);
+        let glide = prev.is_some_and(is_vowel) && matches!(c, 'i' | 'u');
+        let after_qu = i >= 2 && rest[i - 1] == 'u' && matches!(rest[i - 2], 'q' | 'g');
+        if is_vowel(c) && !glide && !after_qu {
+            syllables += 1;
+        }
+    }
+    syllables == 1 && rest.last().is_some_and(|&c| is_vowel(c))
+}
+
+/// `gü`/`qü` antes de `e`/`i` → `gu`/`qu`.
+fn drop_diaeresis(chars: &mut [char]) -> bool {
+    let mut changed = false;
+    for i in 1..chars.len().saturating_sub(1) {
+        if chars[i] == 'ü'
+            && matches!(chars[i - 1], 'g' | 'q')
+            && matches!(base_letter(chars[i + 1]), 'e' | 'i')
+        {
+            chars[i] = 'u';
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// `éi`/`ói` na penúltima sílaba perdem o acento (`papéis` e `herói`,
+/// oxítonas, mantêm).
+fn drop_open_diphthong(chars: &mut [char]) -> bool {
+    let mut changed = false;
+    for i in 0..chars.len().saturating_sub(1) {
+        if matches!(chars[i], 'é' | 'ó')
+            && chars[i + 1] == 'i'
+            && is_paroxytone_tail(&chars[i + 2..])
+        {
+            chars[i] = base_letter(chars[i]);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// `ôo` → `oo` em qualquer posição; `êem` final → `eem`.
+fn drop_hiatus(chars: &mut [char]) -> bool {
+    let mut changed = false;
+    for i in 0..chars.len().saturating_sub(1) {
+        if chars[i] == 'ô' && chars[i + 1] == 'o' {
+            chars[i] = 'o';
+            changed = true;
+        }
+    }
+    if chars.ends_with(&['ê', 'e', 'm']) {
+        let i = chars.len() - 3;
+        chars[i] = 'e';
+        changed = true;
+    }
+    changed
+}
+
+/// `í`/`ú` tônicos logo após ditongo em paroxítona (`feiúra`, `baiúca`;
+/// `Piauí` mantém) e `ú` tônico em `gue`/`gui`/`que`/`qui` (`averigúe`, `argúi`).
+fn drop_stressed_vowel(chars: &mut [char]) -> bool {
+    let mut changed = false;
+    for i in 1..chars.len() {
+        if !matches!(chars[i], 'í' | 'ú') {
+            continue;
+        }
+        let after_diphthong = i >= 2
+            && matches!(chars[i - 1], 'i' | 'u')
+            && is_vowel(chars[i - 2])
+            && is_paroxytone_tail(&chars[i + 1..]);
+        let in_gue_gui = chars[i] == 'ú'
+            && matches!(chars[i - 1], 'g' | 'q')
+            && chars.get(i + 1).is_some_and(|&c| matches!(c, 'e' | 'i'));
+        if after_diphthong || in_gue_gui {
+            chars[i] = base_letter(chars[i]);
+            changed = true;
+        }
+    }
+    changed
+}
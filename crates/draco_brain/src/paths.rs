@@ -0,0 +1,266 @@
+use crate::builder::{EngineConfig, Resource};
+use crate::embedded;
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Variável de ambiente com um diretório de dados alternativo.
+pub const DATA_DIR_ENV: &str = "DRACO_DATA_DIR";
+
+/// Nome da pasta de dados por usuário (mesmo nome da pasta do instalador).
+#[cfg(windows)]
+const USER_DIR_NAME: &str = "AutocorretorPTBR";
+#[cfg(not(windows))]
+const USER_DIR_NAME: &str = "autocorretor-ptbr";
+
+/// De onde veio um caminho candidato, na ordem de busca.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Origin {
+    /// Caminho explícito ou `data_dir` do [`EngineConfig`]
+    Config,
+    /// `DRACO_DATA_DIR`
+    Env,
+    /// `%APPDATA%\AutocorretorPTBR` / `$XDG_DATA_HOME/autocorretor-ptbr`
+    UserDir,
+    /// `data/` ao lado do executável (ou da DLL)
+    Executable,
+    /// Dados embutidos no binário
+    Embedded,
+}
+
+impl Origin {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Origin::Config => "configuração",
+            Origin::Env => DATA_DIR_ENV,
+            Origin::UserDir => "pasta do usuário",
+            Origin::Executable => "junto ao executável",
+            Origin::Embedded => "embutido",
+        }
+    }
+}
+
+/// Um caminho avaliado pelo [`PathResolver`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candidate {
+    pub origin: Origin,
+    /// `None` para dados embutidos ou quando a origem não está configurada
+    pub path: Option<PathBuf>,
+    /// Motivo da rejeição; `None` no candidato escolhido
+    pub rejected: Option<String>,
+}
+
+/// Resultado da busca de um recurso: todos os candidatos, na ordem avaliada.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Resolution {
+    pub resource: Resource,
+    pub candidates: Vec<Candidate>,
+}
+
+impl Resolution {
+    pub fn chosen(&self) -> Option<&Candidate> {
+        self.candidates.iter().find(|c| c.rejected.is_none())
+    }
+
+    pub fn origin(&self) -> Option<Origin> {
+        self.chosen().map(|c| c.origin)
+    }
+
+    /// Caminho escolhido (`None` se nada foi achado ou se o escolhido é embutido).
+    pub fn path(&self) -> Option<&Path> {
+        self.chosen().and_then(|c| c.path.as_deref())
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in &self.candidates {
+            let path = c
+                .path
+                .as_ref()
+                .map(|p| format!(" {:?}", p))
+                .unwrap_or_default();
+            match &c.rejected {
+                None => writeln!(f, "  * {}{}: escolhido", c.origin.as_str(), path)?,
+                Some(reason) => writeln!(f, "  - {}{}: {}", c.origin.as_str(), path, reason)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Localiza os arquivos de dados. Cada recurso é resolvido sozinho, na
+/// ordem: configuração explícita, `DRACO_DATA_DIR`, pasta do usuário,
+/// `data/` ao lado do executável e, por fim, os dados embutidos.
+#[derive(Debug, Clone, Default)]
+pub struct PathResolver {
+    config: EngineConfig,
+    /// Se as origens do ambiente (variável, pasta do usuário, executável) entram na busca
+    search_environment: bool,
+    env_dir: Option<PathBuf>,
+    user_dir: Option<PathBuf>,
+    exe_dir: Option<PathBuf>,
+}
+
+impl PathResolver {
+    /// Resolvedor que só olha a configuração explícita.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolvedor com todas as origens, lidas do ambiente do processo.
+    pub fn from_environment() -> Self {
+        Self {
+            config: EngineConfig::default(),
+            search_environment: true,
+            env_dir: std::env::var_os(DATA_DIR_ENV)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from),
+            user_dir: user_data_dir(),
+            exe_dir: std::env::current_exe()
+                .ok()
+                .and_then(|p| p.parent().map(Path::to_path_buf)),
+        }
+    }
+
+    pub fn with_config(mut self, config: &EngineConfig) -> Self {
+        self.config = config.clone();
+        self
+    }
+
+    /// Substitui o valor lido de `DRACO_DATA_DIR`.
+    pub fn with_env_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.env_dir = Some(dir.into());
+        self
+    }
+
+    /// Substitui a pasta de dados do usuário.
+    pub fn with_user_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.user_dir = Some(dir.into());
+        self
+    }
+
+    /// Diretório de referência para a busca "junto ao executável". A DLL
+    /// do TSF passa o próprio diretório, já que o executável é o do aplicativo hospedeiro.
+    pub fn with_exe_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.exe_dir = Some(dir.into());
+        self
+    }
+
+    pub fn resolve(&self, resource: Resource) -> Resolution {
+        let name = resource.file_name();
+        let mut candidates: Vec<(Origin, Option<PathBuf>, Option<&str>)> = Vec::new();
+
+        // 1. Configuração explícita: caminho do recurso, depois `data_dir`
+        if let Some(path) = self.config.path_of(resource) {
+            candidates.push((Origin::Config, Some(path.clone()), None));
+        }
+        if let Some(dir) = &self.config.data_dir {
+            candidates.push((Origin::Config, Some(dir.join(name)), None));
+        }
+
+        if self.search_environment {
+            // 2. Variável de ambiente
+            let env = self.env_dir.as_ref().map(|d| d.join(name));
+            candidates.push((Origin::Env, env, Some("DRACO_DATA_DIR não definida")));
+
+            // 3. Pasta de dados do usuário
+            let user = self.user_dir.as_ref().map(|d| d.join(name));
+            candidates.push((Origin::UserDir, user, Some("pasta do usuário desconhecida")));
+
+            // 4. Ao lado do executável; `../../data` cobre `target/<perfil>/` no desenvolvimento
+            match &self.exe_dir {
+                Some(dir) => {
+                    candidates.push((Origin::Executable, Some(dir.join("data").join(name)), None));
+                    let dev = dir.join("..").join("..").join("data").join(name);
+                    candidates.push((Origin::Executable, Some(dev), None));
+                }
+                None => candidates.push((
+                    Origin::Executable,
+                    None,
+                    Some("diretório do executável desconhecido"),
+                )),
+            }
+        }
+
+        // 5. Dados embutidos
+        let embedded = resource.embeddable() && embedded::AVAILABLE;
+        if resource.embeddable() {
+            candidates.push((
+                Origin::Embedded,
+                None,
+                Some("compilado sem a feature embedded-data"),
+            ));
+        }
+
+        let mut chosen: Option<Origin> = None;
+        let candidates = candidates
+            .into_iter()
+            .map(|(origin, path, unavailable)| {
+                let rejected = if let Some(first) = chosen {
+                    Some(format!("ignorado: já resolvido por {}", first.as_str()))
+                } else {
+                    match &path {
+                        None if origin == Origin::Embedded && embedded => None,
+                        None => Some(unavailable.unwrap_or("não configurado").to_string()),
+                        Some(p) => Self::reject_reason(resource, p),
+                    }
+                };
+                if rejected.is_none() {
+                    chosen = Some(origin);
+                }
+                Candidate {
+                    origin,
+                    path,
+                    rejected,
+                }
+            })
+            .collect();
+
+        Resolution {
+            resource,
+            candidates,
+        }
+    }
+
+    fn reject_reason(resource: Resource, path: &Path) -> Option<String> {
+        if !path.exists() {
+            Some("não existe".to_string())
+        } else if resource.is_directory() && !path.is_dir() {
+            Some("não é um diretório".to_string())
+        } else if !resource.is_directory() && !path.is_file() {
+            Some("não é um arquivo".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// `%APPDATA%\AutocorretorPTBR` no Windows; `$XDG_DATA_HOME/autocorretor-ptbr`
+/// (ou `~/.local/share/autocorretor-ptbr`) nos demais.
+pub fn user_data_dir() -> Option<PathBuf> {
+    let non_empty = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty());
+    let base = if cfg!(windows) {
+        non_empty("APPDATA").map(PathBuf::from)
+    } else {
+        non_empty("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| non_empty("HOME").map(|h| PathBuf::from(h).join(".local").join("share")))
+    };
+    base.map(|b| b.join(USER_DIR_NAME))
+}
+
+/// Arquivos com a extensão dada num diretório (sem descer em
+/// subdiretórios), em ordem alfabética.
+pub fn files_with_extension(dir: &Path, extension: &str) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == extension) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
@@ -43,7 +43,7 @@ impl PhoneticNormalizer {
     }
 
     /// Regras de substituição para sibilantes (S/SS/C/Z/SC/Ç)
-    fn get_sibilant_rules() -> Vec<(String, Vec<String>)> {
+    pub fn get_sibilant_rules() -> Vec<(String, Vec<String>)> {
         vec![
             // ss ↔ ç ↔ c (antes de e/i)
             ("ss".into(), vec!["ç".into(), "c".into(), "sc".into()]),
@@ -92,11 +92,18 @@ impl PhoneticNormalizer {
 
 /// Mapa fonético: armazena palavras do dicionário indexadas pela forma normalizada.
 /// Permite busca rápida de palavras que soam igual.
+#[derive(Clone)]
 pub struct PhoneticIndex {
     /// forma_normalizada → lista de palavras originais do dicionário
     index: HashMap<String, Vec<String>>,
 }
 
+impl Default for PhoneticIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PhoneticIndex {
     pub fn new() -> Self {
         Self {
@@ -109,7 +116,7 @@ impl PhoneticIndex {
         let normalized = PhoneticNormalizer::normalize(word);
         self.index
             .entry(normalized)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(word.to_string());
     }
 
@@ -0,0 +1,51 @@
+use crate::stage_a::{Candidate, CorrectionStep};
+
+/// Candidatos (os primeiros de [`StageA::candidates`](crate::stage_a::StageA::candidates))
+/// que o contexto pode reordenar.
+pub const RERANK_LIMIT: usize = 5;
+
+/// O segundo candidato está perto do primeiro se tem no máximo a mesma
+/// distância e ao menos 1/N da frequência dele.
+pub const CLOSE_FREQUENCY_RATIO: u32 = 10;
+
+/// Se vale pedir ao modelo para desempatar: o primeiro candidato veio de
+/// uma busca (não de uma regra, como o `TypoModel` ou o glossário) e o
+/// segundo está perto dele.
+pub fn is_close(candidates: &[Candidate]) -> bool {
+    let [first, second, ..] = candidates else {
+        return false;
+    };
+    let searched = matches!(
+        first.step,
+        CorrectionStep::Transposition
+            | CorrectionStep::Phonetic
+            | CorrectionStep::FuzzyDistance1
+            | CorrectionStep::FuzzyDistance2
+    );
+    searched
+        && second.distance <= first.distance
+        && second.frequency.saturating_mul(CLOSE_FREQUENCY_RATIO) >= first.frequency
+}
+
+/// Reordena os primeiros [`RERANK_LIMIT`] candidatos pela nota que `score`
+/// dá a cada palavra no contexto (maior é melhor), guardando-a em
+/// [`Candidate::context_score`]. Empates e os demais candidatos mantêm a
+/// ordem; sem notas, a lista volta como veio.
+pub fn rerank(
+    mut candidates: Vec<Candidate>,
+    score: impl FnOnce(&[String]) -> Option<Vec<f32>>,
+) -> Vec<Candidate> {
+    let n = candidates.len().min(RERANK_LIMIT);
+    let words: Vec<String> = candidates[..n].iter().map(|c| c.word.clone()).collect();
+    let Some(scores) = score(&words).filter(|scores| scores.len() == n) else {
+        return candidates;
+    };
+    for (candidate, score) in candidates.iter_mut().zip(scores) {
+        candidate.context_score = Some(score);
+    }
+    candidates[..n].sort_by(|a, b| {
+        let score = |c: &Candidate| c.context_score.unwrap_or(f32::NEG_INFINITY);
+        score(b).total_cmp(&score(a))
+    });
+    candidates
+}
@@ -0,0 +1,598 @@
+use crate::manifest::{self, ModelInputs, ModelManifest};
+use crate::stage_c::{open_session, StageCError};
+use ort::session::{Session, SessionInputValue};
+use ort::value::{Tensor, ValueType};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Encoder de um modelo encoder-decoder exportado no formato do Optimum.
+pub const ENCODER_FILE: &str = "encoder_model.onnx";
+
+/// Decoders aceitos, em ordem de preferência: o "merged" (com e sem cache
+/// num só grafo, escolhidos por `use_cache_branch`) e o simples, sem cache.
+pub const DECODER_FILES: [&str; 2] = ["decoder_model_merged.onnx", "decoder_model.onnx"];
+
+/// Tokens gerados no máximo quando a configuração do modelo não diz.
+pub const DEFAULT_MAX_LENGTH: usize = 128;
+
+/// Prefixo das entradas de cache do decoder; a saída correspondente troca
+/// o prefixo por `present` (`past_key_values.0.decoder.key` → `present.0.decoder.key`).
+const PAST_PREFIX: &str = "past_key_values";
+const PRESENT_PREFIX: &str = "present";
+
+/// Como escolher o próximo token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecodeStrategy {
+    /// O token mais provável a cada passo
+    Greedy,
+    /// As `width` sequências mais prováveis a cada passo
+    Beam { width: usize },
+}
+
+/// Parâmetros da decodificação autorregressiva.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecodeOptions {
+    pub strategy: DecodeStrategy,
+    /// Tokens gerados no máximo (sem contar o inicial)
+    pub max_length: usize,
+    pub decoder_start_token_id: u32,
+    pub eos_token_id: u32,
+    /// Expoente do comprimento na nota final de cada hipótese do beam
+    /// (`0` favorece respostas curtas; `1` é a média por token)
+    pub length_penalty: f32,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            strategy: DecodeStrategy::Greedy,
+            max_length: DEFAULT_MAX_LENGTH,
+            // Convenção do T5: o pad inicia a decodificação e `</s>` é o 1
+            decoder_start_token_id: 0,
+            eos_token_id: 1,
+            length_penalty: 1.0,
+        }
+    }
+}
+
+impl DecodeOptions {
+    /// Tokens especiais e comprimento lidos do `generation_config.json` e do
+    /// `config.json` do diretório do modelo (como exportados do Hugging
+    /// Face); o que faltar fica no padrão.
+    pub fn from_model_dir(dir: &Path) -> Self {
+        let mut options = Self::default();
+        // O `generation_config.json` vem por último: tem precedência
+        for file in ["config.json", "generation_config.json"] {
+            let Ok(text) = std::fs::read_to_string(dir.join(file)) else {
+                continue;
+            };
+            let Ok(config) = serde_json::from_str::<serde_json::Value>(&text) else {
+                continue;
+            };
+            // `eos_token_id` pode ser um número ou uma lista
+            let token = |key: &str| {
+                let value = config.get(key)?;
+                value
+                    .as_u64()
+                    .or_else(|| value.as_array()?.first()?.as_u64())
+                    .and_then(|id| u32::try_from(id).ok())
+            };
+            if let Some(id) = token("decoder_start_token_id") {
+                options.decoder_start_token_id = id;
+            }
+            if let Some(id) = token("eos_token_id") {
+                options.eos_token_id = id;
+            }
+            if let Some(length) = config.get("max_length").and_then(|v| v.as_u64()) {
+                options.max_length = length as usize;
+            }
+            if let Some(beams) = config.get("num_beams").and_then(|v| v.as_u64()) {
+                if beams > 1 {
+                    options.strategy = DecodeStrategy::Beam {
+                        width: beams as usize,
+                    };
+                }
+            }
+        }
+        options
+    }
+}
+
+/// Decodificação autorregressiva, independente do modelo.
+///
+/// `step` recebe as hipóteses vivas (todas do mesmo tamanho, começando pelo
+/// token inicial) e, para cada uma, o índice da hipótese do passo anterior
+/// de que ela descende (para reordenar um cache); devolve os logits do
+/// próximo token de cada hipótese. O resultado são os tokens gerados, sem o
+/// inicial e sem o de fim.
+pub fn decode<E>(
+    options: &DecodeOptions,
+    step: impl FnMut(&[Vec<u32>], &[usize]) -> Result<Vec<Vec<f32>>, E>,
+) -> Result<Vec<u32>, E> {
+    let scored = decode_scored(options, step)?;
+    Ok(scored.into_iter().map(|(token, _)| token).collect())
+}
+
+/// Igual a [`decode`], com a probabilidade de cada token gerado (dado o
+/// que veio antes dele na hipótese escolhida).
+pub fn decode_scored<E>(
+    options: &DecodeOptions,
+    mut step: impl FnMut(&[Vec<u32>], &[usize]) -> Result<Vec<Vec<f32>>, E>,
+) -> Result<Vec<(u32, f32)>, E> {
+    let mut search = Search::new(options);
+    while !search.is_done(options) {
+        let logits = step(&search.sequences(), &search.parents)?;
+        search.advance(options, &logits);
+    }
+    Ok(search.best(options))
+}
+
+/// [`decode_scored`] de vários textos juntos. A cada passo, `step` recebe
+/// as hipóteses vivas de todos eles, com o texto de cada uma e a linha da
+/// chamada anterior de que ela descende (para reordenar um cache); um texto
+/// sai das chamadas quando termina. Um resultado por texto.
+pub fn decode_batch<E>(
+    options: &DecodeOptions,
+    texts: usize,
+    mut step: impl FnMut(&[usize], &[Vec<u32>], &[usize]) -> Result<Vec<Vec<f32>>, E>,
+) -> Result<Vec<Vec<(u32, f32)>>, E> {
+    let mut searches: Vec<Search> = (0..texts).map(|_| Search::new(options)).collect();
+    // Primeira linha de cada texto na chamada anterior
+    let mut offsets = vec![0; texts];
+    loop {
+        let active: Vec<usize> = (0..texts)
+            .filter(|&text| !searches[text].is_done(options))
+            .collect();
+        if active.is_empty() {
+            break;
+        }
+        let (mut owners, mut sequences, mut parents) = (Vec::new(), Vec::new(), Vec::new());
+        for &text in &active {
+            let search = &searches[text];
+            let first = owners.len();
+            for sequence in search.sequences() {
+                owners.push(text);
+                sequences.push(sequence);
+            }
+            parents.extend(search.parents.iter().map(|&parent| offsets[text] + parent));
+            offsets[text] = first;
+        }
+        let mut logits = step(&owners, &sequences, &parents)?.into_iter();
+        for &text in &active {
+            let rows: Vec<Vec<f32>> = logits.by_ref().take(searches[text].beams.len()).collect();
+            searches[text].advance(options, &rows);
+        }
+    }
+    Ok(searches
+        .into_iter()
+        .map(|search| search.best(options))
+        .collect())
+}
+
+/// Busca em feixe de um texto: as hipóteses vivas (tokens, soma dos
+/// log-probs, log-prob de cada token gerado), as terminadas e de que
+/// hipótese do passo anterior cada viva descende.
+struct Search {
+    width: usize,
+    beams: Vec<(Vec<u32>, f32, Vec<f32>)>,
+    parents: Vec<usize>,
+    finished: Vec<(Vec<u32>, Vec<f32>, f32)>,
+    steps: usize,
+}
+
+impl Search {
+    fn new(options: &DecodeOptions) -> Self {
+        let width = match options.strategy {
+            DecodeStrategy::Greedy => 1,
+            DecodeStrategy::Beam { width } => width.max(1),
+        };
+        Self {
+            width,
+            beams: vec![(vec![options.decoder_start_token_id], 0.0, Vec::new())],
+            parents: vec![0],
+            finished: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    fn is_done(&self, options: &DecodeOptions) -> bool {
+        self.steps >= options.max_length
+            || self.beams.is_empty()
+            || self.finished.len() >= self.width
+    }
+
+    fn sequences(&self) -> Vec<Vec<u32>> {
+        self.beams
+            .iter()
+            .map(|(tokens, ..)| tokens.clone())
+            .collect()
+    }
+
+    fn score(options: &DecodeOptions, log_prob: f32, generated: usize) -> f32 {
+        log_prob / (generated.max(1) as f32).powf(options.length_penalty)
+    }
+
+    /// Um passo, com os logits do próximo token de cada hipótese viva.
+    fn advance(&mut self, options: &DecodeOptions, logits: &[Vec<f32>]) {
+        let width = self.width;
+        // (log-prob da hipótese, hipótese, token, log-prob do token)
+        let mut candidates: Vec<(f32, usize, u32, f32)> = Vec::new();
+        for (i, row) in logits.iter().enumerate().take(self.beams.len()) {
+            let log_probs = log_softmax(row);
+            for (token, log_prob) in top_k(&log_probs, width + 1) {
+                candidates.push((self.beams[i].1 + log_prob, i, token, log_prob));
+            }
+        }
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut next = Vec::with_capacity(width);
+        self.parents.clear();
+        for (rank, (log_prob, i, token, token_log_prob)) in candidates.into_iter().enumerate() {
+            if next.len() == width {
+                break;
+            }
+            let (tokens, _, steps) = &self.beams[i];
+            if token == options.eos_token_id {
+                // Fim de frase só conta entre os `width` melhores candidatos
+                if rank < width {
+                    let generated = tokens[1..].to_vec();
+                    let length = generated.len() + 1;
+                    let score = Self::score(options, log_prob, length);
+                    self.finished.push((generated, steps.clone(), score));
+                }
+                continue;
+            }
+            let mut tokens = tokens.clone();
+            tokens.push(token);
+            let mut steps = steps.clone();
+            steps.push(token_log_prob);
+            next.push((tokens, log_prob, steps));
+            self.parents.push(i);
+        }
+        self.beams = next;
+        self.steps += 1;
+    }
+
+    /// A melhor hipótese, com a probabilidade de cada token.
+    fn best(mut self, options: &DecodeOptions) -> Vec<(u32, f32)> {
+        // Com hipóteses terminadas suficientes, as vivas já perderam; senão
+        // (limite de tamanho), elas também concorrem
+        if self.finished.len() >= self.width {
+            self.beams.clear();
+        }
+        let alive = self.beams.into_iter().map(|(tokens, log_prob, steps)| {
+            let generated = tokens[1..].to_vec();
+            let length = generated.len();
+            (generated, steps, Self::score(options, log_prob, length))
+        });
+        self.finished
+            .into_iter()
+            .chain(alive)
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(tokens, steps, _)| {
+                tokens
+                    .into_iter()
+                    .zip(steps.into_iter().map(f32::exp))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+pub(crate) fn log_softmax(logits: &[f32]) -> Vec<f32> {
+    let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let sum: f32 = logits.iter().map(|&l| (l - max).exp()).sum();
+    let log_sum = max + sum.ln();
+    logits.iter().map(|&l| l - log_sum).collect()
+}
+
+/// Os `k` maiores valores com seus índices, do maior para o menor.
+fn top_k(values: &[f32], k: usize) -> Vec<(u32, f32)> {
+    let mut indexed: Vec<(u32, f32)> = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| (i as u32, v))
+        .collect();
+    let k = k.min(indexed.len());
+    if k == 0 {
+        return Vec::new();
+    }
+    indexed.select_nth_unstable_by(k - 1, |a, b| b.1.total_cmp(&a.1));
+    indexed.truncate(k);
+    indexed.sort_by(|a, b| b.1.total_cmp(&a.1));
+    indexed
+}
+
+/// Uma entrada de cache do decoder e a saída que a alimenta no passo seguinte.
+#[derive(Debug, Clone)]
+struct CacheSlot {
+    input: String,
+    output: String,
+    /// Dimensões declaradas (`-1` nas dinâmicas)
+    dims: Vec<i64>,
+}
+
+/// Conteúdo de uma entrada de cache entre dois passos: forma e dados, com
+/// a primeira dimensão sendo a hipótese.
+type CacheValue = (Vec<i64>, Vec<f32>);
+
+/// Entradas do decoder que [`Seq2SeqModel::generate`] sabe alimentar, além
+/// das de cache.
+const DECODER_INPUTS: [&str; 4] = [
+    "input_ids",
+    "encoder_attention_mask",
+    "encoder_hidden_states",
+    "use_cache_branch",
+];
+
+/// Modelo encoder-decoder (T5, BART...) em dois grafos ONNX.
+pub struct Seq2SeqModel {
+    encoder: Session,
+    decoder: Session,
+    inputs: ModelInputs,
+    logits: String,
+    /// Entradas que o decoder declara
+    decoder_inputs: Vec<String>,
+    cache: Vec<CacheSlot>,
+}
+
+impl Seq2SeqModel {
+    /// Carrega e confere os grafos do manifesto: as entradas do encoder são
+    /// as de [`ModelManifest::inputs`]; as do decoder, as dos exports do
+    /// Optimum, com os logits em [`ModelManifest::outputs`].
+    pub fn load(base_dir: &Path, manifest: &ModelManifest) -> Result<Self, StageCError> {
+        let encoder_path = base_dir.join(&manifest.files.encoder);
+        let decoder_path = base_dir.join(&manifest.files.decoder);
+        let encoder = open_session(&encoder_path, manifest.threads)?;
+        let decoder = open_session(&decoder_path, manifest.threads)?;
+
+        let inputs = manifest.inputs.clone();
+        let mut required = vec![inputs.input_ids.as_str()];
+        required.extend(inputs.attention_mask.as_deref());
+        // A saída do encoder são os estados ocultos, não logits; basta a forma
+        manifest::check_session(
+            &encoder,
+            &encoder_path,
+            &|name| required.contains(&name),
+            &required,
+            None,
+        )?;
+        let logits = manifest
+            .outputs
+            .logits
+            .clone()
+            .unwrap_or_else(|| "logits".to_string());
+        manifest::check_session(
+            &decoder,
+            &decoder_path,
+            &|name| DECODER_INPUTS.contains(&name) || name.starts_with(PAST_PREFIX),
+            &["input_ids"],
+            Some(&logits),
+        )?;
+
+        let decoder_inputs: Vec<String> = decoder
+            .inputs()
+            .iter()
+            .map(|i| i.name().to_string())
+            .collect();
+        let cache = decoder
+            .inputs()
+            .iter()
+            .filter_map(|input| {
+                let rest = input.name().strip_prefix(PAST_PREFIX)?;
+                let dims = match input.dtype() {
+                    ValueType::Tensor { shape, .. } => shape.to_vec(),
+                    _ => return None,
+                };
+                Some(CacheSlot {
+                    input: input.name().to_string(),
+                    output: format!("{}{}", PRESENT_PREFIX, rest),
+                    dims,
+                })
+            })
+            .collect();
+        Ok(Self {
+            encoder,
+            decoder,
+            inputs,
+            logits,
+            decoder_inputs,
+            cache,
+        })
+    }
+
+    /// Se o decoder reaproveita as chaves e valores dos passos anteriores.
+    pub fn uses_cache(&self) -> bool {
+        !self.cache.is_empty()
+    }
+
+    /// Gera a sequência de saída para os tokens de entrada, com a
+    /// probabilidade de cada token (ver [`decode_scored`]).
+    pub fn generate(
+        &mut self,
+        input_ids: &[i64],
+        options: &DecodeOptions,
+    ) -> ort::Result<Vec<(u32, f32)>> {
+        let mut outputs = self.generate_batch(&[input_ids], 0, options)?;
+        Ok(outputs.pop().unwrap_or_default())
+    }
+
+    /// Gera as saídas de vários textos de uma vez: o encoder roda uma vez
+    /// sobre as entradas preenchidas com `pad_id` até o mesmo tamanho, com a
+    /// máscara de atenção, e cada passo do decoder leva as hipóteses vivas
+    /// de todos os textos, cada uma com os estados e a máscara do seu texto
+    /// (ver [`decode_batch`]). Sem máscara no encoder ou no decoder, o
+    /// preenchimento mudaria o resultado: textos de tamanhos diferentes vão
+    /// um por vez.
+    pub fn generate_batch(
+        &mut self,
+        rows: &[&[i64]],
+        pad_id: i64,
+        options: &DecodeOptions,
+    ) -> ort::Result<Vec<Vec<(u32, f32)>>> {
+        let source_len = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let masked = self.inputs.attention_mask.is_some()
+            && self
+                .decoder_inputs
+                .iter()
+                .any(|i| i == "encoder_attention_mask");
+        if !masked && rows.iter().any(|row| row.len() != source_len) {
+            return rows.iter().map(|row| self.generate(row, options)).collect();
+        }
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let batch = rows.len();
+        let mut ids = Vec::with_capacity(batch * source_len);
+        let mut mask = Vec::with_capacity(batch * source_len);
+        for row in rows {
+            ids.extend_from_slice(row);
+            ids.resize(ids.len() + source_len - row.len(), pad_id);
+            mask.extend((0..source_len).map(|i| i64::from(i < row.len())));
+        }
+        let mut encoder_inputs: Vec<(String, SessionInputValue)> = vec![(
+            self.inputs.input_ids.clone(),
+            Tensor::from_array(([batch, source_len], ids))?.into(),
+        )];
+        if let Some(name) = &self.inputs.attention_mask {
+            encoder_inputs.push((
+                name.clone(),
+                Tensor::from_array(([batch, source_len], mask.clone()))?.into(),
+            ));
+        }
+        let outputs = self.encoder.run(encoder_inputs)?;
+        let (shape, data) = outputs[0].try_extract_tensor::<f32>()?;
+        let hidden = shape.get(2).copied().unwrap_or(0) as usize;
+        let encoded = data.to_vec();
+        drop(outputs);
+        let states_len = source_len * hidden;
+
+        let Self {
+            decoder,
+            decoder_inputs,
+            logits: logits_name,
+            cache: slots,
+            ..
+        } = self;
+        let declared = |name: &str| decoder_inputs.iter().any(|i| i == name);
+        let mut cache: Option<Vec<CacheValue>> = None;
+
+        decode_batch(options, batch, |owners, sequences, parents| {
+            let n = sequences.len();
+            let len = sequences[0].len();
+            // Com cache, só o último token de cada hipótese entra
+            let (ids, ids_len): (Vec<i64>, usize) = match cache {
+                Some(_) => (sequences.iter().map(|s| s[len - 1] as i64).collect(), 1),
+                None => (sequences.iter().flatten().map(|&t| t as i64).collect(), len),
+            };
+
+            let mut inputs: Vec<(String, SessionInputValue<'static>)> = vec![(
+                "input_ids".into(),
+                Tensor::from_array(([n, ids_len], ids))?.into(),
+            )];
+            // Estados e máscara do texto de cada hipótese
+            if declared("encoder_attention_mask") {
+                let rows: Vec<i64> = owners
+                    .iter()
+                    .flat_map(|&t| mask[t * source_len..(t + 1) * source_len].iter().copied())
+                    .collect();
+                inputs.push((
+                    "encoder_attention_mask".into(),
+                    Tensor::from_array(([n, source_len], rows))?.into(),
+                ));
+            }
+            if declared("encoder_hidden_states") {
+                let states: Vec<f32> = owners
+                    .iter()
+                    .flat_map(|&t| {
+                        encoded[t * states_len..(t + 1) * states_len]
+                            .iter()
+                            .copied()
+                    })
+                    .collect();
+                inputs.push((
+                    "encoder_hidden_states".into(),
+                    Tensor::from_array(([n, source_len, hidden], states))?.into(),
+                ));
+            }
+            if !slots.is_empty() {
+                for (i, slot) in slots.iter().enumerate() {
+                    let (dims, data) = match &cache {
+                        Some(values) => reorder(&values[i], parents),
+                        // Primeiro passo: cache vazio (dimensões dinâmicas em 0)
+                        None => {
+                            let dims: Vec<i64> = slot
+                                .dims
+                                .iter()
+                                .enumerate()
+                                .map(|(d, &size)| match (d, size) {
+                                    (0, _) => n as i64,
+                                    (_, size) if size < 0 => 0,
+                                    (_, size) => size,
+                                })
+                                .collect();
+                            (dims, Vec::new())
+                        }
+                    };
+                    inputs.push((slot.input.clone(), Tensor::from_array((dims, data))?.into()));
+                }
+                if declared("use_cache_branch") {
+                    inputs.push((
+                        "use_cache_branch".into(),
+                        Tensor::from_array(([1usize], vec![cache.is_some()]))?.into(),
+                    ));
+                }
+            }
+
+            let outputs = decoder.run(inputs)?;
+            let logits = &outputs[logits_name.as_str()];
+            let (shape, data) = logits.try_extract_tensor::<f32>()?;
+            let vocab = shape.get(2).copied().unwrap_or(0) as usize;
+            let rows = (0..n)
+                .map(|i| {
+                    let start = (i * ids_len + ids_len - 1) * vocab;
+                    data.get(start..start + vocab).unwrap_or_default().to_vec()
+                })
+                .collect();
+
+            if !slots.is_empty() {
+                let previous = cache.take();
+                let mut next = Vec::with_capacity(slots.len());
+                for (i, slot) in slots.iter().enumerate() {
+                    match outputs.get(&slot.output) {
+                        Some(value) => {
+                            let (shape, data) = value.try_extract_tensor::<f32>()?;
+                            next.push((shape.to_vec(), data.to_vec()));
+                        }
+                        // Saídas que o grafo não repete (o cache do encoder,
+                        // em alguns exports) seguem as hipóteses
+                        None => next.push(match &previous {
+                            Some(values) => reorder(&values[i], parents),
+                            None => (Vec::new(), Vec::new()),
+                        }),
+                    }
+                }
+                cache = Some(next);
+            }
+            Ok(rows)
+        })
+    }
+}
+
+/// Reordena a primeira dimensão de um cache para as hipóteses novas.
+fn reorder((dims, data): &CacheValue, parents: &[usize]) -> CacheValue {
+    let rows = dims.first().copied().unwrap_or(0).max(1) as usize;
+    let row = data.len() / rows;
+    let mut dims = dims.clone();
+    if let Some(first) = dims.first_mut() {
+        *first = parents.len() as i64;
+    }
+    let data = parents
+        .iter()
+        .flat_map(|&p| data[p * row..(p + 1) * row].iter().copied())
+        .collect();
+    (dims, data)
+}
@@ -0,0 +1,69 @@
+use crate::stage_a::StageA;
+use arc_swap::ArcSwap;
+use std::sync::{Arc, Mutex};
+
+/// Engine compartilhado entre threads (hook de teclado, threads de correção,
+/// servidor IPC).
+///
+/// Leitores pegam um snapshot imutável (`Arc<StageA>`) e corrigem sem lock
+/// nenhum: o ponteiro para o snapshot atual é trocado atomicamente
+/// ([`ArcSwap`]). Escritores montam uma cópia nova (barata, ver [`StageA`])
+/// e a publicam de uma vez; correções em andamento continuam no snapshot
+/// antigo.
+pub struct SharedEngine {
+    current: ArcSwap<StageA>,
+    /// Serializa as atualizações para que duas não se percam uma à outra.
+    writer: Mutex<()>,
+}
+
+impl SharedEngine {
+    pub fn new(engine: StageA) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(engine),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Snapshot atual do engine.
+    pub fn snapshot(&self) -> Arc<StageA> {
+        self.current.load_full()
+    }
+
+    /// Atalho para `snapshot().correct(..)`.
+    pub fn correct(&self, word: &str, aggressiveness: u32) -> String {
+        self.snapshot().correct(word, aggressiveness)
+    }
+
+    /// Aplica `f` numa cópia do snapshot atual e publica o resultado.
+    ///
+    /// Feito para mudanças pequenas (ex.: [`StageA::add_user_word`]). Mexer
+    /// no dicionário base aqui copia o dicionário inteiro; para recargas
+    /// completas monte um `StageA` novo e use [`replace`](Self::replace).
+    pub fn update<R>(&self, f: impl FnOnce(&mut StageA) -> R) -> R {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let mut next = StageA::clone(&self.snapshot());
+        let result = f(&mut next);
+        self.publish(next);
+        result
+    }
+
+    /// Troca o engine inteiro (ex.: depois de recarregar os dados). As
+    /// palavras do usuário e as camadas ligadas/desligadas em tempo de
+    /// execução passam do snapshot atual para o novo engine.
+    pub fn replace(&self, mut engine: StageA) {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        engine.inherit_runtime_state(&self.snapshot());
+        self.publish(engine);
+    }
+
+    fn publish(&self, engine: StageA) {
+        // O snapshot antigo é liberado pelo último leitor
+        self.current.store(Arc::new(engine));
+    }
+}
+
+impl Default for SharedEngine {
+    fn default() -> Self {
+        Self::new(StageA::new())
+    }
+}
@@ -1,47 +1,695 @@
-use crate::phonetic::PhoneticIndex;
-use crate::trie::Trie;
+use crate::blocklist::Blocklist;
+use crate::glossary::{self, Glossary, GlossaryConflict, GlossaryEntry};
+use crate::layers::{DictionaryLayer, LayerKind};
+use crate::orthography::{self, OrthographyRule};
+use crate::phonetic::PhoneticNormalizer;
+use crate::trace::{CorrectionTrace, TraceCandidate, TraceEvent};
+use crate::trie::SearchBudget;
 use crate::typo_model::TypoModel;
-use std::collections::HashMap;
+use crate::variant::{self, Variant};
+use serde::{Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Etapa do pipeline que decidiu o resultado de [`StageA::correct`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CorrectionStep {
+    /// Termo do glossário da equipe (grafia e caixa impostas)
+    Glossary,
+    /// Grafia exclusiva da outra norma ortográfica (`contato` → `contacto`)
+    Variant,
+    /// Grafia anterior ao Acordo Ortográfico de 1990 (`idéia` → `ideia`)
+    Orthography,
+    /// Par explícito do `TypoModel` (palavra inteira ou sufixo)
+    TypoModel,
+    /// Palavra encontrada no dicionário e mantida
+    Dictionary,
+    /// Palavra válida trocada por uma muito mais frequente
+    FrequencyUpgrade,
+    /// Troca de letras adjacentes
+    Transposition,
+    /// Equivalência fonética (S/SS/Ç/Z...)
+    Phonetic,
+    /// Busca fuzzy à distância 1
+    FuzzyDistance1,
+    /// Busca fuzzy à distância 2 (modo agressivo)
+    FuzzyDistance2,
+    /// Nenhuma etapa encontrou correção; a palavra volta inalterada
+    NoMatch,
+}
+
+impl CorrectionStep {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CorrectionStep::Glossary => "glossary",
+            CorrectionStep::Variant => "variant",
+            CorrectionStep::Orthography => "orthography",
+            CorrectionStep::TypoModel => "typo_model",
+            CorrectionStep::Dictionary => "dictionary",
+            CorrectionStep::FrequencyUpgrade => "frequency_upgrade",
+            CorrectionStep::Transposition => "transposition",
+            CorrectionStep::Phonetic => "phonetic",
+            CorrectionStep::FuzzyDistance1 => "fuzzy_1",
+            CorrectionStep::FuzzyDistance2 => "fuzzy_2",
+            CorrectionStep::NoMatch => "no_match",
+        }
+    }
+}
+
+impl Serialize for CorrectionStep {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Correção possível para uma palavra, de [`StageA::candidates`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candidate {
+    /// Já na forma final (caixa da entrada, glossário, norma)
+    pub word: String,
+    /// Etapa do pipeline que a propôs
+    pub step: CorrectionStep,
+    /// Distância de edição (0 nas etapas por regra e nas fonéticas)
+    pub distance: usize,
+    pub frequency: u32,
+    /// Log-probabilidade média no contexto da frase, se reclassificado
+    /// (ver [`rerank`](crate::rerank))
+    pub context_score: Option<f32>,
+}
+
+/// Registra um evento no trace, se houver um ativo. O evento só é
+/// construído quando o trace está ligado.
+fn record(trace: &mut Option<Vec<TraceEvent>>, event: impl FnOnce() -> TraceEvent) {
+    if let Some(events) = trace {
+        events.push(event());
+    }
+}
+
+fn fuzzy_event(
+    max_distance: usize,
+    result: &LayeredSearch<'_>,
+    chosen: &Option<String>,
+) -> TraceEvent {
+    TraceEvent::Fuzzy {
+        max_distance,
+        incomplete: result.incomplete,
+        candidates: result
+            .suggestions
+            .iter()
+            .map(Suggestion::to_trace)
+            .collect(),
+        chosen: chosen.clone(),
+    }
+}
+
+/// Candidato da busca fuzzy, com a camada que forneceu a palavra.
+struct Suggestion<'a> {
+    word: String,
+    distance: usize,
+    frequency: u32,
+    layer: &'a DictionaryLayer,
+}
+
+impl Suggestion<'_> {
+    fn to_trace(&self) -> TraceCandidate {
+        TraceCandidate {
+            word: self.word.clone(),
+            distance: self.distance,
+            frequency: self.frequency,
+            layer: Some(self.layer.name().to_string()),
+        }
+    }
+}
+
+/// Busca fuzzy em todas as camadas ligadas, já mesclada.
+struct LayeredSearch<'a> {
+    /// Ordenados por distância e frequência; empates na ordem das camadas
+    suggestions: Vec<Suggestion<'a>>,
+    incomplete: bool,
+}
 
 /// Limiar de frequência para "upgrade" de palavra válida.
 /// Se a alternativa é N vezes mais frequente, corrige para ela.
 const FREQ_UPGRADE_RATIO: u32 = 15;
 
+/// Prazo sugerido para as buscas no trie durante a digitação: a correção
+/// roda a cada espaço e não pode atrasar o texto visivelmente.
+pub const KEYSTROKE_SEARCH_TIMEOUT: Duration = Duration::from_millis(15);
+
+/// Nome da camada do dicionário base.
+pub const BASE_LAYER: &str = "base";
+/// Nome da camada de palavras do usuário.
+pub const USER_LAYER: &str = "usuario";
+/// Nome da camada com os termos de uma palavra do glossário.
+pub const GLOSSARY_LAYER: &str = "glossario";
+/// Nome da camada com as palavras da norma ortográfica escolhida.
+pub const VARIANT_LAYER: &str = "variante";
+
+/// Engine da Stage A. Clonar é barato: as estruturas grandes (camadas de
+/// dicionário, `TypoModel`, frequências) ficam em `Arc` e são compartilhadas
+/// entre cópias. Isso permite publicar snapshots imutáveis via
+/// [`SharedEngine`](crate::shared::SharedEngine).
+///
+/// O vocabulário é uma pilha de camadas ([`DictionaryLayer`]): o dicionário
+/// base, pacotes de domínio, a camada da equipe e as palavras do usuário.
+/// Cada uma pode ser ligada, desligada ou repriorizada em tempo de execução
+/// sem recarregar as demais.
+#[derive(Clone)]
 pub struct StageA {
-    trie: Trie,
-    typo_model: TypoModel,
-    phonetic_index: PhoneticIndex,
+    /// Camadas em ordem decrescente de prioridade (empates: ordem de inclusão)
+    layers: Vec<DictionaryLayer>,
+    glossary: Arc<Glossary>,
+    /// Norma ortográfica das correções
+    variant: Variant,
+    blocklist: Arc<Blocklist>,
+    typo_model: Arc<TypoModel>,
     /// Mapa de frequência: palavra → score (maior = mais comum)
-    frequency: HashMap<String, u32>,
+    frequency: Arc<HashMap<String, u32>>,
+    /// Tempo máximo gasto nas buscas no trie de uma chamada de `correct`
+    search_timeout: Option<Duration>,
+    /// Nós visitados no máximo por busca no trie
+    search_max_nodes: Option<usize>,
+}
+
+impl Default for StageA {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StageA {
     pub fn new() -> Self {
         Self {
-            trie: Trie::new(),
-            typo_model: TypoModel::new(),
-            phonetic_index: PhoneticIndex::new(),
-            frequency: HashMap::new(),
+            layers: vec![
+                DictionaryLayer::new(USER_LAYER, LayerKind::User),
+                DictionaryLayer::new(GLOSSARY_LAYER, LayerKind::Glossary),
+                DictionaryLayer::new(VARIANT_LAYER, LayerKind::Variant),
+                DictionaryLayer::new(BASE_LAYER, LayerKind::Base),
+            ],
+            glossary: Arc::new(Glossary::new()),
+            variant: Variant::default(),
+            blocklist: Arc::new(Blocklist::new()),
+            typo_model: Arc::new(TypoModel::new()),
+            frequency: Arc::new(HashMap::new()),
+            search_timeout: None,
+            search_max_nodes: None,
+        }
+    }
+
+    /// Limita o trabalho das buscas no trie (upgrade de frequência e fuzzy).
+    /// O prazo vale para a chamada inteira de `correct`; o limite de nós,
+    /// para cada busca. Ao estourar, usa os candidatos achados até ali.
+    /// `None` nos dois (o padrão) = sem limite.
+    pub fn set_search_limits(&mut self, timeout: Option<Duration>, max_nodes: Option<usize>) {
+        self.search_timeout = timeout;
+        self.search_max_nodes = max_nodes;
+    }
+
+    fn search_budget(&self) -> SearchBudget {
+        SearchBudget {
+            deadline: self.search_timeout.map(|t| Instant::now() + t),
+            max_nodes: self.search_max_nodes,
         }
     }
 
     /// Carrega dados de frequência.
+    ///
+    /// Assim como `load_dictionary*`, altera o dicionário base: se houver
+    /// outros snapshots vivos, ele é copiado antes (copy-on-write). Para
+    /// mudanças pequenas em tempo de execução use [`add_user_word`](Self::add_user_word).
     pub fn load_frequency_data(&mut self, entries: &[(String, u32)]) {
+        let frequency = Arc::make_mut(&mut self.frequency);
+        let base = Self::layer_of_kind(&mut self.layers, LayerKind::Base).lexicon_mut();
         for (word, freq) in entries {
             let lower = word.to_lowercase();
-            self.frequency.insert(lower.clone(), *freq);
-            self.trie.insert_with_frequency(&lower, *freq);
+            frequency.insert(lower.clone(), *freq);
+            base.trie.insert_with_frequency(&lower, *freq);
+        }
+        base.trie.shrink_to_fit();
+    }
+
+    /// Troca o `TypoModel` (ex.: depois de recarregar o arquivo de regras).
+    pub fn set_typo_model(&mut self, model: TypoModel) {
+        self.typo_model = Arc::new(model);
+    }
+
+    /// Troca o glossário da equipe.
+    pub fn set_glossary(&mut self, glossary: Glossary) {
+        self.glossary = Arc::new(glossary);
+        self.rebuild_glossary_layer();
+    }
+
+    /// Acrescenta termos ao glossário em tempo de execução. Grafias que já
+    /// pertencem a outro termo não são trocadas e voltam como conflito.
+    pub fn add_glossary_entries(&mut self, entries: &[GlossaryEntry]) -> Vec<GlossaryConflict> {
+        let conflicts = Arc::make_mut(&mut self.glossary).add(entries, glossary::RUNTIME_SOURCE);
+        self.rebuild_glossary_layer();
+        conflicts
+    }
+
+    pub fn glossary(&self) -> &Glossary {
+        &self.glossary
+    }
+
+    /// Escolhe a norma ortográfica: a camada de variante passa a ter as
+    /// palavras embutidas dessa norma (as incluídas com
+    /// [`add_variant_words`](Self::add_variant_words) são descartadas) e as
+    /// grafias exclusivas da outra norma passam a ser convertidas.
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+        let words: Vec<(String, u32)> = variant.words().into_iter().map(|w| (w, 0)).collect();
+        let layer = Self::layer_of_kind(&mut self.layers, LayerKind::Variant);
+        *layer = DictionaryLayer::from_entries(VARIANT_LAYER, LayerKind::Variant, &words)
+            .with_enabled(layer.is_enabled())
+            .with_priority(layer.priority());
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Acrescenta palavras à camada da norma escolhida (ex.: a lista
+    /// `variants/pt_pt.txt`). Frequência 0 = usar a da tabela de frequências.
+    pub fn add_variant_words(&mut self, entries: &[(String, u32)]) {
+        let layer = Self::layer_of_kind(&mut self.layers, LayerKind::Variant);
+        for (word, frequency) in entries {
+            layer.insert(word, *frequency);
+        }
+    }
+
+    /// Troca a lista de correções proibidas.
+    pub fn set_blocklist(&mut self, blocklist: Blocklist) {
+        self.blocklist = Arc::new(blocklist);
+    }
+
+    pub fn blocklist(&self) -> &Blocklist {
+        &self.blocklist
+    }
+
+    /// Grafia da norma escolhida para uma palavra da base, reconhecida pelas
+    /// famílias de [`variant`] (registado, económicos em pt_pt).
+    fn is_variant_spelling(&self, word: &str) -> bool {
+        self.variant != Variant::PtBr
+            && variant::convert_word(word, Variant::PtBr).is_some_and(|base| self.is_known(&base))
+    }
+
+    /// Se a lista de bloqueios proíbe corrigir `from` para `to` (minúsculas),
+    /// ou para a palavra em que [`finish`](Self::finish) transforma `to`.
+    fn blocked(&self, from: &str, to: &str) -> bool {
+        !self.blocklist.is_empty()
+            && (self.blocklist.blocks(from, to)
+                || self
+                    .finished_word(to)
+                    .is_some_and(|finished| self.blocklist.blocks(from, &finished)))
+    }
+
+    /// Registra os candidatos de uma etapa descartados pela lista de bloqueios.
+    fn record_blocked<'w>(
+        &self,
+        trace: &mut Option<Vec<TraceEvent>>,
+        step: CorrectionStep,
+        from: &str,
+        candidates: impl IntoIterator<Item = &'w str>,
+    ) {
+        let Some(events) = trace else { return };
+        let words: Vec<String> = candidates
+            .into_iter()
+            .filter(|c| self.blocked(from, c))
+            .map(str::to_string)
+            .collect();
+        if !words.is_empty() {
+            events.push(TraceEvent::Blocked {
+                step: step.as_str(),
+                words,
+            });
+        }
+    }
+
+    /// Os termos de uma palavra entram numa camada própria, para que a
+    /// busca fuzzy também os encontre quando digitados com erro.
+    fn rebuild_glossary_layer(&mut self) {
+        let words: Vec<(String, u32)> = self.glossary.single_words().map(|w| (w, 0)).collect();
+        let layer = Self::layer_of_kind(&mut self.layers, LayerKind::Glossary);
+        *layer = DictionaryLayer::from_entries(GLOSSARY_LAYER, LayerKind::Glossary, &words)
+            .with_enabled(layer.is_enabled())
+            .with_priority(layer.priority());
+    }
+
+    /// Adiciona uma palavra à camada do usuário. Retorna `false` se ela já
+    /// era conhecida.
+    pub fn add_user_word(&mut self, word: &str) -> bool {
+        let lower = word.to_lowercase();
+        if lower.is_empty() || self.is_known(&lower) {
+            return false;
+        }
+        let freq = self.get_frequency(&lower);
+        Self::layer_of_kind(&mut self.layers, LayerKind::User).insert(&lower, freq);
+        true
+    }
+
+    /// Copia o estado de tempo de execução de outro engine (usado ao
+    /// recarregar os dados): a camada do usuário, os termos de glossário
+    /// incluídos em tempo de execução e, para as camadas com o mesmo nome,
+    /// se estão ligadas e a prioridade.
+    pub(crate) fn inherit_runtime_state(&mut self, from: &StageA) {
+        let runtime: Vec<GlossaryEntry> = from
+            .glossary
+            .entries_from(glossary::RUNTIME_SOURCE)
+            .cloned()
+            .collect();
+        if !runtime.is_empty() {
+            self.add_glossary_entries(&runtime);
+        }
+        for layer in &mut self.layers {
+            let Some(old) = from.layer(layer.name()) else {
+                continue;
+            };
+            if layer.kind() == LayerKind::User {
+                *layer = old.clone();
+            } else {
+                layer.set_enabled(old.is_enabled());
+                layer.set_priority(old.priority());
+            }
+        }
+        self.sort_layers();
+    }
+
+    /// Quantidade de palavras na camada do usuário.
+    pub fn user_word_count(&self) -> usize {
+        self.layers
+            .iter()
+            .find(|l| l.kind() == LayerKind::User)
+            .map_or(0, DictionaryLayer::len)
+    }
+
+    /// Camadas em ordem decrescente de prioridade.
+    pub fn layers(&self) -> &[DictionaryLayer] {
+        &self.layers
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&DictionaryLayer> {
+        self.layers.iter().find(|l| l.name() == name)
+    }
+
+    /// Inclui uma camada (ex.: um pacote de domínio), substituindo a que
+    /// tiver o mesmo nome. As camadas base, do glossário e do usuário são
+    /// mantidas pelo próprio engine e não podem ser substituídas; retorna
+    /// `false` nesse caso.
+    pub fn add_layer(&mut self, layer: DictionaryLayer) -> bool {
+        if !matches!(layer.kind(), LayerKind::Pack | LayerKind::Team) {
+            return false;
+        }
+        match self.layers.iter_mut().find(|l| l.name() == layer.name()) {
+            Some(existing) if !matches!(existing.kind(), LayerKind::Pack | LayerKind::Team) => {
+                return false
+            }
+            Some(existing) => *existing = layer,
+            None => self.layers.push(layer),
+        }
+        self.sort_layers();
+        true
+    }
+
+    /// Remove uma camada de pacote ou da equipe.
+    pub fn remove_layer(&mut self, name: &str) -> Option<DictionaryLayer> {
+        let index = self.layers.iter().position(|l| {
+            l.name() == name && matches!(l.kind(), LayerKind::Pack | LayerKind::Team)
+        })?;
+        Some(self.layers.remove(index))
+    }
+
+    /// Liga ou desliga uma camada. Retorna `false` se ela não existe.
+    pub fn set_layer_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.layers.iter_mut().find(|l| l.name() == name) {
+            Some(layer) => {
+                layer.set_enabled(enabled);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Muda a prioridade de uma camada. Retorna `false` se ela não existe.
+    pub fn set_layer_priority(&mut self, name: &str, priority: i32) -> bool {
+        match self.layers.iter_mut().find(|l| l.name() == name) {
+            Some(layer) => {
+                layer.set_priority(priority);
+                self.sort_layers();
+                true
+            }
+            None => false,
         }
     }
 
+    fn sort_layers(&mut self) {
+        self.layers.sort_by_key(|l| std::cmp::Reverse(l.priority()));
+    }
+
+    /// As camadas base, do glossário e do usuário existem sempre (não podem
+    /// ser removidas).
+    fn layer_of_kind(layers: &mut [DictionaryLayer], kind: LayerKind) -> &mut DictionaryLayer {
+        layers
+            .iter_mut()
+            .find(|l| l.kind() == kind)
+            .expect("camadas fixas sempre presentes")
+    }
+
+    fn enabled_layers(&self) -> impl Iterator<Item = &DictionaryLayer> {
+        self.layers.iter().filter(|l| l.is_enabled())
+    }
+
+    /// Camada ligada de maior prioridade que conhece a palavra, com a
+    /// frequência que ela dá.
+    fn lookup(&self, word: &str) -> Option<(&DictionaryLayer, u32)> {
+        self.enabled_layers().find_map(|layer| {
+            let frequency = layer.lexicon().trie.frequency(word)?;
+            Some((layer, self.effective_frequency(layer, word, frequency)))
+        })
+    }
+
+    fn is_known(&self, word: &str) -> bool {
+        self.lookup(word).is_some()
+    }
+
+    /// Camadas sem frequência própria para a palavra (0) usam a da tabela;
+    /// a base já foi carregada com ela.
+    fn effective_frequency(
+        &self,
+        layer: &DictionaryLayer,
+        word: &str,
+        layer_frequency: u32,
+    ) -> u32 {
+        if layer_frequency > 0 || layer.kind() == LayerKind::Base {
+            layer_frequency
+        } else {
+            self.frequency.get(word).copied().unwrap_or(0)
+        }
+    }
+
+    /// Busca fuzzy em todas as camadas ligadas. Cada palavra aparece uma vez,
+    /// atribuída à camada de maior prioridade que a contém.
+    fn search(&self, word: &str, max_distance: usize, budget: SearchBudget) -> LayeredSearch<'_> {
+        let layers: Vec<&DictionaryLayer> =
+            self.enabled_layers().filter(|l| !l.is_empty()).collect();
+        // Com uma camada só (o caso comum) não há duplicatas a filtrar
+        let dedupe = layers.len() > 1;
+        let mut seen = HashSet::new();
+        let mut suggestions = Vec::new();
+        let mut incomplete = false;
+        for layer in layers {
+            let result = layer.lexicon().trie.search(word, max_distance, budget);
+            incomplete |= result.incomplete;
+            for (candidate, distance, frequency) in result.suggestions {
+                if !dedupe || seen.insert(candidate.clone()) {
+                    suggestions.push(Suggestion {
+                        frequency: self.effective_frequency(layer, &candidate, frequency),
+                        word: candidate,
+                        distance,
+                        layer,
+                    });
+                }
+            }
+        }
+        // Estável: empates ficam na ordem de prioridade das camadas
+        suggestions.sort_by(|a, b| {
+            a.distance
+                .cmp(&b.distance)
+                .then_with(|| b.frequency.cmp(&a.frequency))
+        });
+        LayeredSearch {
+            suggestions,
+            incomplete,
+        }
+    }
+
+    /// Palavras com a mesma chave fonética, com a camada de cada uma.
+    fn phonetic_matches(&self, word: &str) -> Vec<(String, &DictionaryLayer)> {
+        let mut seen = HashSet::new();
+        let mut matches = Vec::new();
+        for layer in self.enabled_layers() {
+            for w in layer.lexicon().phonetic_index.find_matches(word) {
+                if seen.insert(w.clone()) {
+                    matches.push((w, layer));
+                }
+            }
+        }
+        matches
+    }
+
     fn get_frequency(&self, word: &str) -> u32 {
-        self.frequency.get(word).copied().unwrap_or(0)
+        match self.lookup(word) {
+            Some((_, frequency)) => frequency,
+            None => self.frequency.get(word).copied().unwrap_or(0),
+        }
+    }
+
+    /// Se a palavra (em qualquer caixa) está em alguma camada ligada.
+    /// Compostos com hífen obrigatório valem se a segunda parte vale.
+    pub fn contains_word(&self, word: &str) -> bool {
+        let lower = word.to_lowercase();
+        self.is_known(&lower)
+            || orthography::hyphenated_base(&lower).is_some_and(|base| self.is_known(base))
     }
 
     /// Pipeline de correção completo.
     pub fn correct(&self, word: &str, aggressiveness: u32) -> String {
+        self.correct_with_step(word, aggressiveness).0
+    }
+
+    /// Igual a [`correct`](Self::correct), mas informa também qual etapa
+    /// do pipeline decidiu o resultado.
+    pub fn correct_with_step(&self, word: &str, aggressiveness: u32) -> (String, CorrectionStep) {
+        self.run_pipeline(word, aggressiveness, &mut None)
+    }
+
+    /// Lista curta de correções para uma palavra fora do dicionário: a de
+    /// [`correct`](Self::correct) primeiro, depois as transposições, as
+    /// fonéticas e as das buscas fuzzy que o pipeline deixou para trás, na
+    /// ordem em que ele as consideraria. Palavras conhecidas (ou sem
+    /// correção) não têm candidatos.
+    pub fn candidates(&self, word: &str, aggressiveness: u32, limit: usize) -> Vec<Candidate> {
+        let (corrected, step) = self.correct_with_step(word, aggressiveness);
+        if matches!(step, CorrectionStep::Dictionary | CorrectionStep::NoMatch) {
+            return Vec::new();
+        }
+        let first_char_upper = word.chars().next().is_some_and(char::is_uppercase);
+        let all_upper = word.chars().all(|c| c.is_uppercase() || !c.is_alphabetic());
+        let word_lower = word.to_lowercase();
+        let budget = self.search_budget();
+
+        // (palavra, etapa, distância), já na ordem do pipeline
+        let mut raw: Vec<(String, CorrectionStep, usize)> = Vec::new();
+        let chars: Vec<char> = word_lower.chars().collect();
+        let mut transposed: Vec<String> = (0..chars.len().saturating_sub(1))
+            .map(|i| {
+                let mut swapped = chars.clone();
+                swapped.swap(i, i + 1);
+                swapped.into_iter().collect()
+            })
+            .filter(|candidate: &String| self.is_known(candidate))
+            .collect();
+        transposed.sort_by_key(|w| std::cmp::Reverse(self.get_frequency(w)));
+        raw.extend(
+            transposed
+                .into_iter()
+                .map(|w| (w, CorrectionStep::Transposition, 1)),
+        );
+        let mut phonetic: Vec<String> = self
+            .phonetic_matches(&word_lower)
+            .into_iter()
+            .map(|(w, _)| w)
+            .collect();
+        phonetic.sort_by_key(|w| std::cmp::Reverse(self.get_frequency(w)));
+        raw.extend(
+            phonetic
+                .into_iter()
+                .map(|w| (w, CorrectionStep::Phonetic, 0)),
+        );
+        if word_lower.len() >= 3 {
+            let result = self.search(&word_lower, 1, budget);
+            raw.extend(
+                result
+                    .suggestions
+                    .into_iter()
+                    .map(|s| (s.word, CorrectionStep::FuzzyDistance1, s.distance)),
+            );
+        }
+        if aggressiveness > 0 && word_lower.len() >= 4 {
+            let result = self.search(&word_lower, 2, budget);
+            raw.extend(
+                result
+                    .suggestions
+                    .into_iter()
+                    .filter(|s| s.distance == 2)
+                    .map(|s| (s.word, CorrectionStep::FuzzyDistance2, s.distance)),
+            );
+        }
+
+        let mut candidates: Vec<Candidate> = Vec::new();
+        for (candidate, candidate_step, distance) in raw {
+            if self.blocked(&word_lower, &candidate) {
+                continue;
+            }
+            let fixed = self.finish(&candidate, word, first_char_upper, all_upper);
+            if candidates.iter().any(|c| c.word == fixed) {
+                continue;
+            }
+            candidates.push(Candidate {
+                frequency: self.get_frequency(&candidate),
+                word: fixed,
+                step: candidate_step,
+                distance,
+                context_score: None,
+            });
+        }
+
+        // A escolha do pipeline vai na frente (as etapas por regra não
+        // aparecem nas buscas)
+        match candidates.iter().position(|c| c.word == corrected) {
+            Some(i) => {
+                let chosen = candidates.remove(i);
+                candidates.insert(0, Candidate { step, ..chosen });
+            }
+            None => candidates.insert(
+                0,
+                Candidate {
+                    frequency: self.get_frequency(&corrected.to_lowercase()),
+                    word: corrected,
+                    step,
+                    distance: 0,
+                    context_score: None,
+                },
+            ),
+        }
+        candidates.truncate(limit.max(1));
+        candidates
+    }
+
+    /// Executa o pipeline registrando cada etapa visitada: entrada do
+    /// `TypoModel`, pertinência ao dicionário, candidatos de upgrade de
+    /// frequência (com razões), transposições, matches fonéticos e listas
+    /// fuzzy com distâncias e frequências.
+    pub fn explain(&self, word: &str, aggressiveness: u32) -> CorrectionTrace {
+        let mut events = Some(Vec::new());
+        let (output, step) = self.run_pipeline(word, aggressiveness, &mut events);
+        CorrectionTrace {
+            input: word.to_string(),
+            aggressiveness,
+            events: events.unwrap_or_default(),
+            output,
+            step,
+        }
+    }
+
+    fn run_pipeline(
+        &self,
+        word: &str,
+        aggressiveness: u32,
+        trace: &mut Option<Vec<TraceEvent>>,
+    ) -> (String, CorrectionStep) {
         if word.is_empty() {
-            return word.to_string();
+            return (word.to_string(), CorrectionStep::NoMatch);
         }
 
         let first_char_upper = word
@@ -51,116 +699,341 @@ impl StageA {
             .unwrap_or(false);
         let all_upper = word.chars().all(|c| c.is_uppercase() || !c.is_alphabetic());
         let word_lower = word.to_lowercase();
+        let budget = self.search_budget();
+
+        // 0. Glossário da equipe: grafia e caixa exatas, antes de tudo
+        if !self.glossary.is_empty() {
+            let hit = self
+                .glossary
+                .canonical(word)
+                .map(|canonical| glossary::match_case(canonical, word));
+            record(trace, || TraceEvent::Glossary { hit: hit.clone() });
+            if let Some(hit) = hit {
+                return (hit, CorrectionStep::Glossary);
+            }
+        }
+
+        // 0b. Grafia exclusiva da outra norma (contato → contacto em pt_pt)
+        if let Some(converted) = variant::convert_word(&word_lower, self.variant)
+            .filter(|converted| !self.blocked(&word_lower, converted))
+        {
+            record(trace, || TraceEvent::Variant {
+                variant: self.variant,
+                hit: converted.clone(),
+            });
+            let fixed = self.finish(&converted, word, first_char_upper, all_upper);
+            return (fixed, CorrectionStep::Variant);
+        }
+
+        // 0c. Grafia anterior ao Acordo de 1990 (idéia → ideia, auto-escola → autoescola).
+        //     Antes do dicionário: as formas antigas ainda aparecem nas frequências
+        //     Compostos com hífen que o dicionário conhece (nomes, siglas: anti-spam) ficam
+        let known_compound = |modern: &orthography::Modernization| {
+            modern.rules == [OrthographyRule::PrefixHyphen]
+                && word_lower.contains('-')
+                && self.is_known(&word_lower)
+        };
+        if let Some(modern) = orthography::modernize(&word_lower)
+            .filter(|modern| !self.blocked(&word_lower, &modern.word) && !known_compound(modern))
+        {
+            record(trace, || TraceEvent::Orthography {
+                rules: modern.rules.clone(),
+                hit: modern.word.clone(),
+            });
+            let fixed = self.finish(&modern.word, word, first_char_upper, all_upper);
+            return (fixed, CorrectionStep::Orthography);
+        }
 
         // 1. TypoModel PRIMEIRO — pares explícitos de confusão (par→para, etc.)
         //    Checa ANTES do dicionário para capturar palavras válidas-mas-erradas
-        if let Some(correction) = self.typo_model.get_correction(&word_lower) {
-            if correction != word_lower {
-                return Self::restore_case(&correction, first_char_upper, all_upper);
-            }
+        let typo_hit = self
+            .typo_model
+            .get_correction(&word_lower)
+            .filter(|correction| *correction != word_lower);
+        record(trace, || TraceEvent::TypoModel {
+            hit: typo_hit.clone(),
+        });
+        self.record_blocked(
+            trace,
+            CorrectionStep::TypoModel,
+            &word_lower,
+            typo_hit.as_deref(),
+        );
+        let typo_hit = typo_hit.filter(|correction| !self.blocked(&word_lower, correction));
+        if let Some(correction) = typo_hit {
+            let fixed = self.finish(&correction, word, first_char_upper, all_upper);
+            return (fixed, CorrectionStep::TypoModel);
         }
 
         // 2. Palavra no dicionário? Verificar se faz "upgrade" de frequência
-        if self.trie.contains(&word_lower) {
+        // Compostos com hífen obrigatório (micro-ondas) valem se a segunda parte vale
+        let known = self.is_known(&word_lower)
+            || orthography::hyphenated_base(&word_lower).is_some_and(|base| self.is_known(base))
+            || self.is_variant_spelling(&word_lower);
+        record(trace, || TraceEvent::Dictionary {
+            known,
+            frequency: self.get_frequency(&word_lower),
+            layer: self.lookup(&word_lower).map(|(l, _)| l.name().to_string()),
+        });
+        if known {
             // Tentar upgrade: se existe palavra MUITO mais comum à distância 1
             if word_lower.len() >= 2 && word_lower.len() <= 6 {
-                if let Some(upgrade) = self.try_frequency_upgrade(&word_lower) {
-                    return Self::restore_case(&upgrade, first_char_upper, all_upper);
+                if let Some(upgrade) = self.try_frequency_upgrade(&word_lower, budget, trace) {
+                    let fixed = self.finish(&upgrade, word, first_char_upper, all_upper);
+                    return (fixed, CorrectionStep::FrequencyUpgrade);
                 }
+            } else {
+                record(trace, || TraceEvent::Skipped {
+                    step: "frequency_upgrade",
+                    reason: "só para palavras de 2 a 6 bytes".into(),
+                });
             }
-            return word.to_string(); // Palavra está OK
+            return (word.to_string(), CorrectionStep::Dictionary); // Palavra está OK
         }
 
         // 3. Detecção de TRANSPOSIÇÃO (teh→the, tabalho→trabalho)
-        if let Some(transposed) = self.try_transpositions(&word_lower) {
-            return Self::restore_case(&transposed, first_char_upper, all_upper);
+        if let Some(transposed) = self.try_transpositions(&word_lower, trace) {
+            let fixed = self.finish(&transposed, word, first_char_upper, all_upper);
+            return (fixed, CorrectionStep::Transposition);
         }
 
         // 4. Busca Fonética (S/SS/Ç/Z/SC, X/CH, G/J)
-        let phonetic_matches = self.phonetic_index.find_matches(&word_lower);
-        if !phonetic_matches.is_empty() {
-            let best = phonetic_matches
+        let phonetic_matches = self.phonetic_matches(&word_lower);
+        let best = phonetic_matches
+            .iter()
+            .map(|(w, _)| w)
+            .filter(|w| !self.blocked(&word_lower, w))
+            .max_by_key(|w| self.get_frequency(w))
+            .cloned();
+        record(trace, || TraceEvent::Phonetic {
+            key: PhoneticNormalizer::normalize(&word_lower),
+            candidates: phonetic_matches
                 .iter()
-                .max_by_key(|w| self.get_frequency(w))
-                .unwrap();
-            return Self::restore_case(best, first_char_upper, all_upper);
+                .map(|(w, layer)| TraceCandidate {
+                    word: w.clone(),
+                    distance: 0,
+                    frequency: self.get_frequency(w),
+                    layer: Some(layer.name().to_string()),
+                })
+                .collect(),
+            chosen: best.clone(),
+        });
+        self.record_blocked(
+            trace,
+            CorrectionStep::Phonetic,
+            &word_lower,
+            phonetic_matches.iter().map(|(w, _)| w.as_str()),
+        );
+        if let Some(best) = best {
+            let fixed = self.finish(&best, word, first_char_upper, all_upper);
+            return (fixed, CorrectionStep::Phonetic);
         }
 
         // 5. Busca Fuzzy (Distância 1)
         if word_lower.len() >= 3 {
-            let suggestions = self.trie.get_suggestions(&word_lower, 1);
+            let result = self.search(&word_lower, 1, budget);
+            let mut allowed = result
+                .suggestions
+                .iter()
+                .filter(|s| !self.blocked(&word_lower, &s.word));
             let best = if word_lower.len() <= 3 {
-                suggestions.into_iter().find(|(_, _, f)| *f > 40000)
+                allowed.find(|s| s.frequency > 40000)
             } else {
-                suggestions.into_iter().next()
+                allowed.next()
             };
-            if let Some((best_word, _, _)) = best {
-                return Self::restore_case(&best_word, first_char_upper, all_upper);
+            let best = best.map(|s| s.word.clone());
+            record(trace, || fuzzy_event(1, &result, &best));
+            self.record_blocked(
+                trace,
+                CorrectionStep::FuzzyDistance1,
+                &word_lower,
+                result.suggestions.iter().map(|s| s.word.as_str()),
+            );
+            if let Some(best_word) = best {
+                let fixed = self.finish(&best_word, word, first_char_upper, all_upper);
+                return (fixed, CorrectionStep::FuzzyDistance1);
             }
+        } else {
+            record(trace, || TraceEvent::Skipped {
+                step: "fuzzy_1",
+                reason: "palavra com menos de 3 bytes".into(),
+            });
         }
 
         // 6. Busca Fuzzy (Distância 2) — modo agressivo
         if aggressiveness > 0 && word_lower.len() >= 4 {
-            let suggestions = self.trie.get_suggestions(&word_lower, 2);
-            let best = suggestions
-                .into_iter()
-                .filter(|(candidate, _, freq)| {
-                    *freq > 0
-                        && (candidate.len() as i32 - word_lower.len() as i32).unsigned_abs() <= 2
+            let result = self.search(&word_lower, 2, budget);
+            let best = result
+                .suggestions
+                .iter()
+                .find(|s| {
+                    s.frequency > 0
+                        && (s.word.len() as i32 - word_lower.len() as i32).unsigned_abs() <= 2
+                        && !self.blocked(&word_lower, &s.word)
                 })
-                .next();
-            if let Some((best_word, _, _)) = best {
-                return Self::restore_case(&best_word, first_char_upper, all_upper);
+                .map(|s| s.word.clone());
+            record(trace, || fuzzy_event(2, &result, &best));
+            self.record_blocked(
+                trace,
+                CorrectionStep::FuzzyDistance2,
+                &word_lower,
+                result.suggestions.iter().map(|s| s.word.as_str()),
+            );
+            if let Some(best_word) = best {
+                let fixed = self.finish(&best_word, word, first_char_upper, all_upper);
+                return (fixed, CorrectionStep::FuzzyDistance2);
             }
+        } else {
+            record(trace, || TraceEvent::Skipped {
+                step: "fuzzy_2",
+                reason: if aggressiveness == 0 {
+                    "modo conservador".into()
+                } else {
+                    "palavra com menos de 4 bytes".into()
+                },
+            });
         }
 
         // Nenhuma correção encontrada
-        word.to_string()
+        (word.to_string(), CorrectionStep::NoMatch)
     }
 
     /// Tenta "upgrade de frequência": se existe uma palavra
     /// MUITO mais comum (>15x) à distância de edição 1,
     /// corrige para ela. Exemplo: "par"(rara) → "para"(muito comum)
-    fn try_frequency_upgrade(&self, word: &str) -> Option<String> {
+    fn try_frequency_upgrade(
+        &self,
+        word: &str,
+        budget: SearchBudget,
+        trace: &mut Option<Vec<TraceEvent>>,
+    ) -> Option<String> {
         let my_freq = self.get_frequency(word);
         if my_freq == 0 {
+            record(trace, || TraceEvent::Skipped {
+                step: "frequency_upgrade",
+                reason: "palavra sem dados de frequência".into(),
+            });
             return None; // Sem dados de frequência, não fazer upgrade
         }
 
-        let suggestions = self.trie.get_suggestions(word, 1);
+        let result = self.search(word, 1, budget);
 
-        for (candidate, _dist, cand_freq) in &suggestions {
-            if candidate == word {
-                continue;
-            }
-            // Candidato deve ser significativamente mais frequente
-            if *cand_freq > my_freq.saturating_mul(FREQ_UPGRADE_RATIO) {
-                return Some(candidate.clone());
-            }
-        }
+        // Candidato deve ser significativamente mais frequente
+        let chosen = result
+            .suggestions
+            .iter()
+            .find(|s| {
+                s.word != word
+                    && s.frequency > my_freq.saturating_mul(FREQ_UPGRADE_RATIO)
+                    && !self.blocked(word, &s.word)
+            })
+            .map(|s| s.word.clone());
+
+        record(trace, || TraceEvent::FrequencyUpgrade {
+            frequency: my_freq,
+            required_ratio: FREQ_UPGRADE_RATIO,
+            incomplete: result.incomplete,
+            candidates: result
+                .suggestions
+                .iter()
+                .filter(|s| s.word != word)
+                .map(|s| (s.to_trace(), s.frequency as f64 / my_freq as f64))
+                .collect(),
+            chosen: chosen.clone(),
+        });
+        self.record_blocked(
+            trace,
+            CorrectionStep::FrequencyUpgrade,
+            word,
+            result.suggestions.iter().map(|s| s.word.as_str()),
+        );
 
-        None
+        chosen
     }
 
     /// Tenta encontrar palavra válida trocando pares de letras adjacentes.
-    fn try_transpositions(&self, word: &str) -> Option<String> {
+    fn try_transpositions(
+        &self,
+        word: &str,
+        trace: &mut Option<Vec<TraceEvent>>,
+    ) -> Option<String> {
         let chars: Vec<char> = word.chars().collect();
+        let mut candidates: Vec<TraceCandidate> = Vec::new();
         let mut best: Option<(String, u32)> = None;
+        let mut blocked = Vec::new();
 
         for i in 0..chars.len().saturating_sub(1) {
             let mut swapped = chars.clone();
             swapped.swap(i, i + 1);
             let candidate: String = swapped.into_iter().collect();
 
-            if self.trie.contains(&candidate) {
-                let freq = self.get_frequency(&candidate);
-                if best.as_ref().map_or(true, |(_, f)| freq > *f) {
+            if let Some((layer, freq)) = self.lookup(&candidate) {
+                if self.blocked(word, &candidate) {
+                    blocked.push(candidate);
+                    continue;
+                }
+                if trace.is_some() {
+                    candidates.push(TraceCandidate {
+                        word: candidate.clone(),
+                        distance: 1,
+                        frequency: freq,
+                        layer: Some(layer.name().to_string()),
+                    });
+                }
+                if best.as_ref().is_none_or(|(_, f)| freq > *f) {
                     best = Some((candidate, freq));
                 }
             }
         }
 
-        best.map(|(word, _)| word)
+        let best = best.map(|(word, _)| word);
+        record(trace, || TraceEvent::Transposition {
+            candidates,
+            chosen: best.clone(),
+        });
+        self.record_blocked(
+            trace,
+            CorrectionStep::Transposition,
+            word,
+            blocked.iter().map(String::as_str),
+        );
+        best
+    }
+
+    /// Forma final de uma correção: termos do glossário (achados pela busca
+    /// fuzzy, por exemplo) saem na forma canônica, candidatos em grafia
+    /// antiga ou da outra norma são atualizados e a caixa segue a entrada.
+    fn finish(&self, corrected: &str, typed: &str, first_upper: bool, all_upper: bool) -> String {
+        if !self.glossary.is_empty() {
+            if let Some(canonical) = self.glossary.canonical(corrected) {
+                return glossary::match_case(canonical, typed);
+            }
+        }
+        match self.updated_spelling(corrected) {
+            Some(updated) => Self::restore_case(&updated, first_upper, all_upper),
+            None => Self::restore_case(corrected, first_upper, all_upper),
+        }
+    }
+
+    /// A palavra (em minúsculas) que [`finish`](Self::finish) emite no lugar
+    /// da correção, se for outra.
+    fn finished_word(&self, corrected: &str) -> Option<String> {
+        if !self.glossary.is_empty() {
+            if let Some(canonical) = self.glossary.canonical(corrected) {
+                return Some(canonical.to_lowercase()).filter(|c| c != corrected);
+            }
+        }
+        self.updated_spelling(corrected)
+    }
+
+    /// A correção na grafia atual da norma escolhida, se for outra.
+    fn updated_spelling(&self, corrected: &str) -> Option<String> {
+        let modern = orthography::modernize(corrected);
+        let word = modern.as_ref().map_or(corrected, |m| m.word.as_str());
+        match variant::convert_word(word, self.variant) {
+            Some(converted) => Some(converted),
+            None => (word != corrected).then(|| word.to_string()),
+        }
     }
 
     fn restore_case(corrected: &str, first_upper: bool, all_upper: bool) -> String {
@@ -181,20 +1054,21 @@ impl StageA {
     }
 
     pub fn load_dictionary(&mut self, words: &[&str]) {
-        for word in words {
-            let lower = word.to_lowercase();
-            let freq = self.get_frequency(&lower);
-            self.trie.insert_with_frequency(&lower, freq);
-            self.phonetic_index.insert(&lower);
-        }
+        self.load_words(words.iter().copied());
     }
 
     pub fn load_dictionary_strings(&mut self, words: &[String]) {
+        self.load_words(words.iter().map(String::as_str));
+    }
+
+    fn load_words<'a>(&mut self, words: impl Iterator<Item = &'a str>) {
+        let frequency = Arc::clone(&self.frequency);
+        let base = Self::layer_of_kind(&mut self.layers, LayerKind::Base).lexicon_mut();
         for word in words {
             let lower = word.to_lowercase();
-            let freq = self.get_frequency(&lower);
-            self.trie.insert_with_frequency(&lower, freq);
-            self.phonetic_index.insert(&lower);
+            let freq = frequency.get(&lower).copied().unwrap_or(0);
+            base.insert(&lower, freq);
         }
+        base.trie.shrink_to_fit();
     }
 }
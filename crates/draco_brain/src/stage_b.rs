@@ -1,8 +1,32 @@
-use crate::stage_c::StageC;
+use crate::cache::{CacheStats, RewriteCache};
+use crate::edit::{self, Edit};
+use crate::grammar::GrammarEngine;
+use crate::guard::{GuardOptions, RewriteGuard};
+use crate::shared::SharedEngine;
+use crate::stage_c::{Rewrite, StageC, StageCError};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::sync::mpsc;
-use tokio::time::sleep;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::time::{sleep_until, Instant};
+
+/// Id das edições vindas do Stage C.
+pub const MODEL_ID: &str = "stage_c";
+
+/// Confiança atribuída às reescritas do Stage C quando o modelo não
+/// informa a probabilidade dos tokens.
+pub const MODEL_CONFIDENCE: f32 = 0.5;
+
+/// Espera padrão depois da última mudança de um contexto antes de corrigi-lo.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Pedidos que cabem na fila antes de [`StageB::request_correction`] esperar.
+pub const QUEUE_CAPACITY: usize = 100;
+
+/// Pedidos por inferência, no máximo, quando vários esperam o modelo.
+pub const DEFAULT_MAX_BATCH: usize = 8;
 
 pub struct StageBRequest {
     pub text: String,
@@ -13,54 +37,408 @@ pub struct StageBResponse {
     pub original: String,
     pub corrected: String,
     pub context_id: u32,
+    /// Correções que levam `original` a `corrected`, em ordem de posição:
+    /// as das regras gramaticais ou, se nenhuma regra disparou, as da
+    /// reescrita do Stage C que passaram pelo [`RewriteGuard`]
+    pub edits: Vec<Edit>,
 }
 
+/// Configuração do [`StageB`].
+pub struct StageBOptions {
+    pub grammar: GrammarEngine,
+    /// Silêncio exigido num contexto antes de corrigir o texto mais recente
+    pub debounce: Duration,
+    /// Limites do filtro das edições do Stage C
+    pub guard: GuardOptions,
+    /// Pedidos que esperam o modelo e vão juntos numa inferência
+    pub max_batch: usize,
+}
+
+impl Default for StageBOptions {
+    fn default() -> Self {
+        Self {
+            grammar: GrammarEngine::builtin(),
+            debounce: DEFAULT_DEBOUNCE,
+            guard: GuardOptions::default(),
+            max_batch: DEFAULT_MAX_BATCH,
+        }
+    }
+}
+
+/// Reescrita de cada texto inteiro de um lote: o Stage C ou, nos testes,
+/// um substituto.
+pub(crate) type Model = Box<dyn FnMut(&[&str]) -> Vec<Option<Rewrite>> + Send>;
+
+/// Contadores do worker, para acompanhar a pressão sobre o Stage B.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct StageBMetrics {
+    /// Pedidos recebidos pelo worker
+    pub received: u64,
+    /// Descartados na espera por um texto mais novo do mesmo contexto
+    pub superseded: u64,
+    /// Respostas enviadas
+    pub processed: u64,
+    /// Textos enviados ao modelo
+    pub model_runs: u64,
+    /// Inferências (lotes de até [`StageBOptions::max_batch`] textos)
+    pub model_batches: u64,
+    /// Pedidos na fila do modelo agora
+    pub model_waiting: usize,
+    /// Pedidos que iam ao modelo mas ficaram sem resposta porque a thread
+    /// dele terminou
+    pub model_dropped: u64,
+    /// Pedidos deixados fora do lote porque ficaram velhos enquanto
+    /// esperavam o modelo
+    pub model_skipped: u64,
+    /// Corrigidos até o fim mas descartados por já haver texto mais novo
+    pub stale_dropped: u64,
+    /// Edições do modelo descartadas pelo [`RewriteGuard`]
+    pub guard_rejected: u64,
+    /// Vezes em que a fila estava cheia e o cliente teve de esperar
+    pub backpressure_waits: u64,
+    /// Contextos esperando o fim do debounce agora
+    pub pending: usize,
+    /// Maior valor já visto de `pending`
+    pub max_pending: usize,
+    /// Pedidos na fila ainda não lidos pelo worker
+    pub queued: usize,
+    /// Cache das reescritas do Stage C (zerado sem modelo)
+    pub cache: CacheStats,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    received: AtomicU64,
+    superseded: AtomicU64,
+    processed: AtomicU64,
+    model_runs: AtomicU64,
+    model_batches: AtomicU64,
+    model_waiting: AtomicUsize,
+    model_dropped: AtomicU64,
+    model_skipped: AtomicU64,
+    stale_dropped: AtomicU64,
+    guard_rejected: AtomicU64,
+    backpressure_waits: AtomicU64,
+    pending: AtomicUsize,
+    max_pending: AtomicUsize,
+}
+
+impl Counters {
+    fn bump(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn set_pending(&self, pending: usize) {
+        self.pending.store(pending, Ordering::Relaxed);
+        self.max_pending.fetch_max(pending, Ordering::Relaxed);
+    }
+}
+
+/// Pedido mais recente de cada contexto. Um pedido cuja sequência não é
+/// mais a do seu contexto foi substituído e seu trabalho pode ser largado.
+#[derive(Debug, Default)]
+struct Latest(Mutex<HashMap<u32, u64>>);
+
+impl Latest {
+    fn set(&self, context_id: u32, seq: u64) {
+        if let Ok(mut latest) = self.0.lock() {
+            latest.insert(context_id, seq);
+        }
+    }
+
+    fn is_current(&self, context_id: u32, seq: u64) -> bool {
+        self.0
+            .lock()
+            .is_ok_and(|latest| latest.get(&context_id) == Some(&seq))
+    }
+
+    /// Encerra o pedido; `false` se ele já tinha sido substituído.
+    fn finish(&self, context_id: u32, seq: u64) -> bool {
+        let Ok(mut latest) = self.0.lock() else {
+            return false;
+        };
+        let current = latest.get(&context_id) == Some(&seq);
+        if current {
+            latest.remove(&context_id);
+        }
+        current
+    }
+}
+
+/// Pedido cujo debounce terminou.
+struct Job {
+    request: StageBRequest,
+    seq: u64,
+}
+
+/// O que o worker e a thread do modelo compartilham.
+struct Shared {
+    grammar: GrammarEngine,
+    guard: RewriteGuard,
+    latest: Latest,
+    counters: Arc<Counters>,
+}
+
+impl Shared {
+    /// Corrige o texto pelas regras; se elas não acharem nada, devolve o
+    /// pedido para a fila do modelo.
+    fn check_grammar(&self, job: Job) -> Result<Option<StageBResponse>, Job> {
+        let edits: Vec<Edit> = self
+            .grammar
+            .check(&job.request.text)
+            .iter()
+            .map(|m| Edit::from_grammar(&job.request.text, m))
+            .collect();
+
+        // Se as regras locais do Stage B não mudarem nada, tentamos a IA (Stage C)
+        if edits.is_empty() {
+            return Err(job);
+        }
+        Ok(self.respond(job, edits))
+    }
+
+    /// Corrige pelo modelo, numa inferência só, pedidos em que as regras
+    /// não acharam nada.
+    fn run_model(&self, model: &mut Model, jobs: Vec<Job>) -> Vec<StageBResponse> {
+        let counters = &self.counters;
+        // O modelo pode ter ficado ocupado com outros lotes: se nesse
+        // meio-tempo chegou texto novo, o pedido nem entra no lote
+        let jobs: Vec<Job> = jobs
+            .into_iter()
+            .filter(|job| {
+                let current = self.latest.is_current(job.request.context_id, job.seq);
+                if !current {
+                    Counters::bump(&counters.model_skipped);
+                }
+                current
+            })
+            .collect();
+        if jobs.is_empty() {
+            return Vec::new();
+        }
+        Counters::bump(&counters.model_batches);
+        counters
+            .model_runs
+            .fetch_add(jobs.len() as u64, Ordering::Relaxed);
+        let texts: Vec<&str> = jobs.iter().map(|job| job.request.text.as_str()).collect();
+        let mut rewrites = model(&texts).into_iter();
+
+        jobs.into_iter()
+            .filter_map(|job| {
+                let mut edits = Vec::new();
+                if let Some(rewrite) = rewrites.next().flatten() {
+                    let text = &job.request.text;
+                    let proposed = Edit::from_model(text, &rewrite, MODEL_ID, MODEL_CONFIDENCE);
+                    let review = self.guard.review(text, &rewrite, proposed);
+                    counters
+                        .guard_rejected
+                        .fetch_add(review.rejected.len() as u64, Ordering::Relaxed);
+                    edits = review.accepted;
+                }
+                self.respond(job, edits)
+            })
+            .collect()
+    }
+
+    /// Resposta com as edições; `None` se o pedido foi substituído no
+    /// caminho.
+    fn respond(&self, job: Job, edits: Vec<Edit>) -> Option<StageBResponse> {
+        let Job { request, seq } = job;
+        if !self.latest.finish(request.context_id, seq) {
+            Counters::bump(&self.counters.stale_dropped);
+            return None;
+        }
+        Counters::bump(&self.counters.processed);
+        Some(StageBResponse {
+            corrected: edit::apply_edits(&request.text, &edits),
+            original: request.text,
+            context_id: request.context_id,
+            edits,
+        })
+    }
+}
+
+/// Revisão assíncrona de frases: regras gramaticais e, quando elas não
+/// acham nada, o modelo do Stage C.
+///
+/// Cada contexto (`context_id`) tem seu próprio debounce: só o texto mais
+/// recente é corrigido, depois de [`StageBOptions::debounce`] sem novidade.
+/// Pedidos substituídos na espera são descartados; os que já estavam sendo
+/// corrigidos pulam o modelo (se ainda não o chamaram) e não geram resposta.
+///
+/// O modelo roda numa thread própria. Os pedidos que chegam enquanto ele
+/// está ocupado esperam juntos e vão na inferência seguinte, num lote de
+/// até [`StageBOptions::max_batch`] textos.
 pub struct StageB {
     tx: mpsc::Sender<StageBRequest>,
-    #[allow(dead_code)]
-    engine_c: Arc<Mutex<StageC>>,
+    counters: Arc<Counters>,
+    cache: Option<Arc<RewriteCache>>,
+    /// Por que o modelo pedido não carregou
+    model_error: Option<StageCError>,
 }
 
 impl StageB {
-    pub fn new(response_tx: mpsc::Sender<StageBResponse>, model_dir: Option<String>) -> Self {
-        let (tx, mut rx) = mpsc::channel::<StageBRequest>(100);
-        let mut engine_c_raw = StageC::new();
+    /// Stage B com as regras gramaticais embutidas. As palavras que o
+    /// modelo introduz são conferidas no dicionário de `engine` (ver
+    /// [`RewriteGuard`]).
+    pub fn new(
+        response_tx: mpsc::Sender<StageBResponse>,
+        model_dir: Option<String>,
+        engine: Arc<SharedEngine>,
+    ) -> Self {
+        Self::with_options(response_tx, model_dir, StageBOptions::default(), engine)
+    }
 
-        if let Some(dir) = model_dir {
-            engine_c_raw.init_from_dir(dir);
-        }
+    /// Stage B com um motor de regras próprio (ex.: o de
+    /// [`EngineBuilder::build_grammar`](crate::EngineBuilder::build_grammar)).
+    pub fn with_grammar(
+        response_tx: mpsc::Sender<StageBResponse>,
+        model_dir: Option<String>,
+        grammar: GrammarEngine,
+        engine: Arc<SharedEngine>,
+    ) -> Self {
+        let options = StageBOptions {
+            grammar,
+            ..StageBOptions::default()
+        };
+        Self::with_options(response_tx, model_dir, options, engine)
+    }
+
+    pub fn with_options(
+        response_tx: mpsc::Sender<StageBResponse>,
+        model_dir: Option<String>,
+        options: StageBOptions,
+        engine: Arc<SharedEngine>,
+    ) -> Self {
+        let mut engine_c = StageC::new();
+        // Stage C é opcional: sem modelo, o Stage B segue só com as regras
+        let model_error = model_dir.and_then(|dir| engine_c.init_from_dir(dir).err());
+        let cache = Arc::clone(engine_c.cache());
+        let mut stage_b = Self::with_model(
+            response_tx,
+            options,
+            engine,
+            Box::new(move |texts| engine_c.rewrite_batch(texts)),
+        );
+        stage_b.cache = Some(cache);
+        stage_b.model_error = model_error;
+        stage_b
+    }
+
+    pub(crate) fn with_model(
+        response_tx: mpsc::Sender<StageBResponse>,
+        options: StageBOptions,
+        engine: Arc<SharedEngine>,
+        mut model: Model,
+    ) -> Self {
+        let (tx, mut rx) = mpsc::channel::<StageBRequest>(QUEUE_CAPACITY);
+        let counters = Arc::new(Counters::default());
+        let shared = Arc::new(Shared {
+            grammar: options.grammar,
+            guard: RewriteGuard::new(options.guard, engine),
+            latest: Latest::default(),
+            counters: Arc::clone(&counters),
+        });
+        let debounce = options.debounce;
+        let max_batch = options.max_batch.max(1);
 
-        let engine_c = Arc::new(Mutex::new(engine_c_raw));
-        let engine_c_clone = engine_c.clone();
+        // Thread do modelo: junta os pedidos que esperam e os corrige numa
+        // inferência só. Termina quando o worker e as tarefas acabam
+        let (model_tx, model_rx) = std::sync::mpsc::channel::<Job>();
+        let model_shared = Arc::clone(&shared);
+        let model_response_tx = response_tx.clone();
+        std::thread::spawn(move || {
+            let shared = model_shared;
+            while let Ok(first) = model_rx.recv() {
+                let mut jobs = vec![first];
+                while jobs.len() < max_batch {
+                    match model_rx.try_recv() {
+                        Ok(job) => jobs.push(job),
+                        Err(_) => break,
+                    }
+                }
+                shared
+                    .counters
+                    .model_waiting
+                    .fetch_sub(jobs.len(), Ordering::Relaxed);
+                for response in shared.run_model(&mut model, jobs) {
+                    if model_response_tx.blocking_send(response).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
 
         tokio::spawn(async move {
-            while let Some(req) = rx.recv().await {
-                // Debounce / Delay simulado
-                sleep(Duration::from_millis(150)).await;
-
-                // Lógica de correção gramatical assíncrona (Stage B)
-                let mut corrected = req.text.clone();
-
-                // Se as regras locais do Stage B não mudarem nada, tentamos a IA (Stage C)
-                if corrected == req.text {
-                    if let Ok(mut engine) = engine_c_clone.lock() {
-                        if let Some(ai_corrected) = engine.predict(&req.text) {
-                            corrected = ai_corrected;
+            let counters = &shared.counters;
+            // Contexto → (pedido mais recente, sequência, prazo do debounce)
+            let mut pending: HashMap<u32, (StageBRequest, u64, Instant)> = HashMap::new();
+            let mut seq = 0u64;
+            loop {
+                let next_deadline = pending.values().map(|&(_, _, deadline)| deadline).min();
+                tokio::select! {
+                    received = rx.recv() => {
+                        let Some(request) = received else { break };
+                        seq += 1;
+                        Counters::bump(&counters.received);
+                        shared.latest.set(request.context_id, seq);
+                        let deadline = Instant::now() + debounce;
+                        if pending.insert(request.context_id, (request, seq, deadline)).is_some() {
+                            Counters::bump(&counters.superseded);
+                        }
+                    }
+                    _ = sleep_until(next_deadline.unwrap_or_else(Instant::now)), if next_deadline.is_some() => {
+                        let now = Instant::now();
+                        let due: Vec<u32> = pending
+                            .iter()
+                            .filter(|(_, &(_, _, deadline))| deadline <= now)
+                            .map(|(&context_id, _)| context_id)
+                            .collect();
+                        for context_id in due {
+                            let Some((request, seq, _)) = pending.remove(&context_id) else {
+                                continue;
+                            };
+                            let job = Job { request, seq };
+                            let shared = Arc::clone(&shared);
+                            let model_tx = model_tx.clone();
+                            let response_tx = response_tx.clone();
+                            // As regras rodam fora do worker, que segue
+                            // recebendo (e marcando pedidos velhos)
+                            tokio::spawn(async move {
+                                let checked = tokio::task::spawn_blocking(move || {
+                                    shared.check_grammar(job).map_err(|job| {
+                                        let counters = &shared.counters;
+                                        counters.model_waiting.fetch_add(1, Ordering::Relaxed);
+                                        if model_tx.send(job).is_err() {
+                                            counters.model_waiting.fetch_sub(1, Ordering::Relaxed);
+                                            Counters::bump(&counters.model_dropped);
+                                        }
+                                    })
+                                })
+                                .await;
+                                if let Ok(Ok(Some(response))) = checked {
+                                    let _ = response_tx.send(response).await;
+                                }
+                            });
                         }
                     }
                 }
-
-                let _ = response_tx
-                    .send(StageBResponse {
-                        original: req.text,
-                        corrected,
-                        context_id: req.context_id,
-                    })
-                    .await;
+                counters.set_pending(pending.len());
             }
         });
 
-        Self { tx, engine_c }
+        Self {
+            tx,
+            counters,
+            cache: None,
+            model_error: None,
+        }
+    }
+
+    /// O erro ao carregar o modelo do Stage C, se um diretório foi dado e
+    /// o modelo não carregou. Nesse caso o Stage B roda só com as regras.
+    pub fn model_error(&self) -> Option<&StageCError> {
+        self.model_error.as_ref()
     }
 
     pub async fn request_correction(
@@ -68,6 +446,38 @@ impl StageB {
         text: String,
         context_id: u32,
     ) -> Result<(), mpsc::error::SendError<StageBRequest>> {
-        self.tx.send(StageBRequest { text, context_id }).await
+        match self.tx.try_send(StageBRequest { text, context_id }) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(request)) => {
+                Counters::bump(&self.counters.backpressure_waits);
+                self.tx.send(request).await
+            }
+            Err(TrySendError::Closed(request)) => Err(mpsc::error::SendError(request)),
+        }
+    }
+
+    pub fn metrics(&self) -> StageBMetrics {
+        let c = &self.counters;
+        StageBMetrics {
+            received: c.received.load(Ordering::Relaxed),
+            superseded: c.superseded.load(Ordering::Relaxed),
+            processed: c.processed.load(Ordering::Relaxed),
+            model_runs: c.model_runs.load(Ordering::Relaxed),
+            model_batches: c.model_batches.load(Ordering::Relaxed),
+            model_waiting: c.model_waiting.load(Ordering::Relaxed),
+            model_dropped: c.model_dropped.load(Ordering::Relaxed),
+            model_skipped: c.model_skipped.load(Ordering::Relaxed),
+            stale_dropped: c.stale_dropped.load(Ordering::Relaxed),
+            guard_rejected: c.guard_rejected.load(Ordering::Relaxed),
+            backpressure_waits: c.backpressure_waits.load(Ordering::Relaxed),
+            pending: c.pending.load(Ordering::Relaxed),
+            max_pending: c.max_pending.load(Ordering::Relaxed),
+            queued: self.tx.max_capacity() - self.tx.capacity(),
+            cache: self
+                .cache
+                .as_ref()
+                .map(|cache| cache.stats())
+                .unwrap_or_default(),
+        }
     }
 }
@@ -1,135 +1,773 @@
-use ort::session::Session;
+use crate::cache::{self, RewriteCache};
+use crate::chunk::{self, ChunkOptions, TokenCounts};
+use crate::manifest::{self, ModelManifest, ModelTask};
+use crate::rerank;
+use crate::seq2seq::{self, DecodeOptions, Seq2SeqModel};
+use crate::stage_a::Candidate;
+use ort::session::{Session, SessionInputValue};
 use ort::value::Tensor;
-use std::path::Path;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokenizers::Tokenizer;
 
+/// Modelo do Stage C num só grafo, que rotula cada token de entrada.
+pub const TAGGER_FILE: &str = "model.onnx";
+
+/// Tipo de modelo num diretório do Stage C.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelKind {
+    /// `model.onnx`: um token corrigido por token de entrada
+    Tagger(PathBuf),
+    /// `encoder_model.onnx` e um decoder: reescrita autorregressiva
+    Seq2Seq { encoder: PathBuf, decoder: PathBuf },
+}
+
+/// Descobre o tipo de modelo pelos arquivos do diretório. Um par
+/// encoder-decoder tem precedência sobre um `model.onnx`.
+pub fn detect_model_kind(dir: &Path) -> Option<ModelKind> {
+    let encoder = dir.join(seq2seq::ENCODER_FILE);
+    let decoder = seq2seq::DECODER_FILES
+        .iter()
+        .map(|file| dir.join(file))
+        .find(|path| path.exists());
+    match decoder {
+        Some(decoder) if encoder.exists() => Some(ModelKind::Seq2Seq { encoder, decoder }),
+        _ => Some(dir.join(TAGGER_FILE))
+            .filter(|path| path.exists())
+            .map(ModelKind::Tagger),
+    }
+}
+
+/// Frase reescrita pelo modelo, com a probabilidade dos tokens que a geraram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rewrite {
+    pub text: String,
+    /// Trechos de `text` (em bytes) e a probabilidade do token (ou dos
+    /// tokens, a menor delas) que os produziu; vazio se o modelo não informa
+    pub tokens: Vec<(Range<usize>, f32)>,
+}
+
+impl Rewrite {
+    /// Reescrita sem probabilidades.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            tokens: Vec::new(),
+        }
+    }
+
+    /// Monta o texto decodificando prefixos cada vez maiores de `tokens`:
+    /// o que cada token acrescenta ao texto é o seu trecho. Tokens que não
+    /// acrescentam um trecho próprio (especiais, bytes de um mesmo
+    /// caractere) passam sua probabilidade ao seguinte. O texto sai aparado.
+    pub fn from_tokens(
+        tokens: &[(u32, f32)],
+        decode: impl Fn(&[u32]) -> Option<String>,
+    ) -> Option<Self> {
+        let ids: Vec<u32> = tokens.iter().map(|&(id, _)| id).collect();
+        let text = decode(&ids)?;
+        let mut spans = Vec::new();
+        let mut done = 0;
+        let mut pending = 1.0f32;
+        for (i, &(_, probability)) in tokens.iter().enumerate() {
+            pending = pending.min(probability);
+            let Some(prefix) = decode(&ids[..=i]) else {
+                continue;
+            };
+            let end = prefix.len();
+            if end > done && text.is_char_boundary(end) && text[..end] == prefix {
+                spans.push((done..end, pending));
+                done = end;
+                pending = 1.0;
+            }
+        }
+
+        let trimmed = text.trim();
+        let offset = text.len() - text.trim_start().len();
+        let tokens = spans
+            .into_iter()
+            .filter_map(|(range, probability)| {
+                let start = range.start.max(offset) - offset;
+                let end = (range.end - offset).min(trimmed.len());
+                (start < end).then_some((start..end, probability))
+            })
+            .collect();
+        Some(Self {
+            text: trimmed.to_string(),
+            tokens,
+        })
+    }
+
+    /// `original` com as trocas (trecho de `original`, texto novo e a sua
+    /// probabilidade), em ordem de posição. Uma troca que começa antes do
+    /// fim da anterior é ignorada.
+    pub fn splice(
+        original: &str,
+        changes: impl IntoIterator<Item = (Range<usize>, String, Option<f32>)>,
+    ) -> Self {
+        let mut rewrite = Self::new(String::with_capacity(original.len()));
+        let mut copied = 0;
+        for (from, replacement, probability) in changes {
+            if from.start < copied {
+                continue;
+            }
+            rewrite.text.push_str(&original[copied..from.start]);
+            let start = rewrite.text.len();
+            rewrite.text.push_str(&replacement);
+            if let Some(probability) = probability {
+                rewrite
+                    .tokens
+                    .push((start..rewrite.text.len(), probability));
+            }
+            copied = from.end;
+        }
+        rewrite.text.push_str(&original[copied..]);
+        rewrite
+    }
+
+    /// Menor probabilidade entre os tokens que tocam o trecho; `None` sem
+    /// probabilidades. Um trecho vazio (remoção) usa os tokens vizinhos.
+    pub fn probability(&self, range: Range<usize>) -> Option<f32> {
+        if self.tokens.is_empty() {
+            return None;
+        }
+        let touches = |token: &Range<usize>| {
+            if range.is_empty() {
+                token.start <= range.start && range.start <= token.end
+            } else {
+                token.start < range.end && range.start < token.end
+            }
+        };
+        self.tokens
+            .iter()
+            .filter(|(token, _)| touches(token))
+            .map(|&(_, probability)| probability)
+            .reduce(f32::min)
+    }
+}
+
+/// Falha ao carregar o modelo do Stage C. Modelos incompatíveis são
+/// recusados aqui, e não na primeira frase.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StageCError {
+    /// O diretório não tem manifesto nem um modelo reconhecível
+    NoModel(PathBuf),
+    /// `model.json` ilegível ou inválido
+    Manifest {
+        path: PathBuf,
+        message: String,
+    },
+    /// Arquivo citado pelo manifesto que não existe
+    MissingFile(PathBuf),
+    /// O ONNX Runtime recusou o grafo
+    Runtime {
+        file: PathBuf,
+        message: String,
+    },
+    Tokenizer {
+        file: PathBuf,
+        message: String,
+    },
+    /// Entrada declarada no manifesto que o grafo não tem
+    MissingInput {
+        file: PathBuf,
+        name: String,
+    },
+    /// Entrada do grafo que o Stage C não sabe alimentar
+    UnexpectedInput {
+        file: PathBuf,
+        name: String,
+    },
+    MissingOutput {
+        file: PathBuf,
+        name: String,
+    },
+    /// Saída de logits que não é um tensor `f32` `[lote, posição, vocabulário]`
+    OutputType {
+        file: PathBuf,
+        name: String,
+        found: String,
+    },
+}
+
+impl fmt::Display for StageCError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoModel(dir) => write!(f, "nenhum modelo em {:?}", dir),
+            Self::Manifest { path, message } => {
+                write!(f, "manifesto {:?} inválido: {}", path, message)
+            }
+            Self::MissingFile(path) => write!(f, "arquivo {:?} não encontrado", path),
+            Self::Runtime { file, message } => {
+                write!(f, "falha ao carregar {:?}: {}", file, message)
+            }
+            Self::Tokenizer { file, message } => {
+                write!(f, "falha ao carregar o tokenizer {:?}: {}", file, message)
+            }
+            Self::MissingInput { file, name } => {
+                write!(f, "{:?} não tem a entrada `{}`", file, name)
+            }
+            Self::UnexpectedInput { file, name } => {
+                write!(f, "{:?} exige a entrada desconhecida `{}`", file, name)
+            }
+            Self::MissingOutput { file, name } => {
+                write!(f, "{:?} não tem a saída `{}`", file, name)
+            }
+            Self::OutputType { file, name, found } => write!(
+                f,
+                "a saída `{}` de {:?} devia ser f32 [lote, posição, vocabulário], mas é {}",
+                name, file, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StageCError {}
+
+/// Abre um grafo com o número de threads do manifesto.
+pub(crate) fn open_session(path: &Path, threads: usize) -> Result<Session, StageCError> {
+    let runtime = |e: ort::Error| StageCError::Runtime {
+        file: path.to_path_buf(),
+        message: e.to_string(),
+    };
+    Session::builder()
+        .and_then(|builder| builder.with_intra_threads(threads))
+        .and_then(|builder| builder.commit_from_file(path))
+        .map_err(runtime)
+}
+
+enum Model {
+    Tagger(Session),
+    Seq2Seq(Seq2SeqModel),
+    MaskedLm(Session),
+}
+
+/// A inferência de um texto falhou no ONNX Runtime. Diferente de "sem
+/// correção": o texto fica como está, mas não entra no cache.
+#[derive(Debug, Clone, Copy)]
+struct InferenceFailed;
+
+/// Resultado da inferência de um texto: `Ok(None)` quando não há correção.
+type Inferred = Result<Option<Rewrite>, InferenceFailed>;
+
+/// Entrada opcional dos modelos estilo BERT, preenchida com zeros.
+const TOKEN_TYPE_IDS: &str = "token_type_ids";
+
 pub struct StageC {
-    session: Option<Session>,
+    model: Option<Model>,
     tokenizer: Option<Tokenizer>,
+    manifest: Option<ModelManifest>,
+    /// Decodificação dos modelos encoder-decoder
+    pub options: DecodeOptions,
+    /// Divisão de textos maiores que o modelo aceita
+    pub chunking: ChunkOptions,
+    cache: Arc<RewriteCache>,
+}
+
+impl Default for StageC {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StageC {
     pub fn new() -> Self {
         Self {
-            session: None,
+            model: None,
             tokenizer: None,
+            manifest: None,
+            options: DecodeOptions::default(),
+            chunking: ChunkOptions::default(),
+            cache: Arc::new(RewriteCache::default()),
         }
     }
 
-    /// Inicializa o motor se os arquivos existirem no caminho especificado.
+    /// Carrega o modelo do diretório, descrito pelo seu `model.json` (ver
+    /// [`ModelManifest`]) ou, sem ele, deduzido dos arquivos presentes.
     /// Garante execução 100% local ao buscar apenas em arquivos locais.
-    pub fn init_from_dir<P: AsRef<Path>>(&mut self, base_dir: P) {
+    pub fn init_from_dir<P: AsRef<Path>>(&mut self, base_dir: P) -> Result<(), StageCError> {
         let base_dir = base_dir.as_ref();
-        let model_path = base_dir.join("model.onnx");
-        let tokenizer_path = base_dir.join("tokenizer.json");
+        let manifest = ModelManifest::from_dir(base_dir)?;
+        self.load(base_dir, manifest)
+    }
 
-        if model_path.exists() && tokenizer_path.exists() {
-            match self.load_model(&model_path, &tokenizer_path) {
-                Ok(_) => {
-                    // Modelo carregado com sucesso
-                }
-                Err(e) => {
-                    // Falha silenciosa: Stage C é opcional
-                    eprintln!("[StageC] Falha ao carregar modelo: {}", e);
+    /// Carrega o modelo descrito por `manifest`, com arquivos relativos a
+    /// `base_dir`. Em caso de erro, o modelo anterior (se havia) continua.
+    pub fn load(&mut self, base_dir: &Path, manifest: ModelManifest) -> Result<(), StageCError> {
+        let paths = manifest.paths(base_dir);
+        if let Some(missing) = paths.iter().find(|path| !path.exists()) {
+            return Err(StageCError::MissingFile(missing.clone()));
+        }
+        let tokenizer_path = base_dir.join(&manifest.files.tokenizer);
+        let tokenizer =
+            Tokenizer::from_file(&tokenizer_path).map_err(|e| StageCError::Tokenizer {
+                file: tokenizer_path.clone(),
+                message: e.to_string(),
+            })?;
+
+        let model = match manifest.task {
+            ModelTask::Tagger | ModelTask::MaskedLm => {
+                let path = base_dir.join(&manifest.files.model);
+                let session = open_session(&path, manifest.threads)?;
+                let inputs = &manifest.inputs;
+                let mut required = vec![inputs.input_ids.as_str()];
+                required.extend(inputs.attention_mask.as_deref());
+                manifest::check_session(
+                    &session,
+                    &path,
+                    &|name| required.contains(&name) || name == TOKEN_TYPE_IDS,
+                    &required,
+                    manifest.outputs.logits.as_deref(),
+                )?;
+                if manifest.task == ModelTask::MaskedLm {
+                    Model::MaskedLm(session)
+                } else {
+                    Model::Tagger(session)
                 }
             }
+            ModelTask::Seq2Seq => Model::Seq2Seq(Seq2SeqModel::load(base_dir, &manifest)?),
+        };
+
+        self.options = manifest.decode_options(base_dir);
+        self.chunking.max_tokens = manifest
+            .max_sequence_length
+            .unwrap_or(chunk::DEFAULT_MAX_TOKENS);
+        // Sem versão, as reescritas do modelo anterior seriam confundidas
+        // com as deste
+        if manifest.version.is_empty() {
+            self.cache.clear();
         }
+        self.model = Some(model);
+        self.tokenizer = Some(tokenizer);
+        self.manifest = Some(manifest);
+        Ok(())
     }
 
     pub fn is_ready(&self) -> bool {
-        self.session.is_some() && self.tokenizer.is_some()
+        self.model.is_some() && self.tokenizer.is_some()
     }
 
-    pub fn load_model<P: AsRef<Path>>(
-        &mut self,
-        model_path: P,
-        tokenizer_path: P,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let session = Session::builder()?
-            .with_intra_threads(1)?
-            .commit_from_file(model_path)?;
-
-        let tokenizer = Tokenizer::from_file(tokenizer_path).map_err(|e| e.to_string())?;
+    /// O manifesto do modelo carregado.
+    pub fn manifest(&self) -> Option<&ModelManifest> {
+        self.manifest.as_ref()
+    }
 
-        self.session = Some(session);
-        self.tokenizer = Some(tokenizer);
+    /// Cache das reescritas. Mudar `options` ou `chunking` depois de
+    /// carregar o modelo pede um [`RewriteCache::clear`].
+    pub fn cache(&self) -> &Arc<RewriteCache> {
+        &self.cache
+    }
 
-        Ok(())
+    /// Usa outro cache (ex.: um compartilhado entre instâncias).
+    pub fn set_cache(&mut self, cache: Arc<RewriteCache>) {
+        self.cache = cache;
     }
 
     /// Executa inferência ONNX para corrigir o texto.
     /// Retorna `Some(corrected)` se o modelo produziu uma correção diferente do input,
     /// ou `None` se o modelo não está disponível ou não alterou o texto.
     pub fn predict(&mut self, text: &str) -> Option<String> {
-        let tokenizer = self.tokenizer.as_ref()?;
-        let session = self.session.as_mut()?;
+        self.rewrite(text).map(|rewrite| rewrite.text)
+    }
+
+    /// Igual a [`predict`](Self::predict), com a probabilidade de cada
+    /// trecho da reescrita.
+    pub fn rewrite(&mut self, text: &str) -> Option<Rewrite> {
+        self.rewrite_batch(&[text]).pop().flatten()
+    }
+
+    /// Reescreve vários textos de uma vez, cada um com o resultado de
+    /// [`rewrite`](Self::rewrite). Os textos vão ao modelo com os espaços
+    /// normalizados (ver [`cache::normalize`]) e o resultado fica no cache,
+    /// pela versão do modelo, a não ser que a inferência falhe. Os que faltam no cache e cabem no modelo são
+    /// corrigidos num lote só; os maiores são divididos em trechos com
+    /// contexto sobreposto (ver [`chunk::chunk`]), que entram no mesmo lote
+    /// e depois são juntados nas posições do texto original.
+    pub fn rewrite_batch(&mut self, texts: &[&str]) -> Vec<Option<Rewrite>> {
+        let mut results = vec![None; texts.len()];
+        let Some(manifest) = self.manifest.as_ref() else {
+            return results;
+        };
+        // Só dá notas a candidatos
+        if !self.is_ready() || manifest.task == ModelTask::MaskedLm {
+            return results;
+        }
+        let version = manifest.version.clone();
+
+        let normalized: Vec<String> = texts.iter().map(|text| cache::normalize(text)).collect();
+        let mut missing: Vec<&str> = Vec::new();
+        for (i, text) in normalized.iter().enumerate() {
+            match self.cache.get(&version, text) {
+                Some(rewrite) => results[i] = rewrite,
+                None if !missing.contains(&text.as_str()) => missing.push(text),
+                None => {}
+            }
+        }
+        if !missing.is_empty() {
+            let rewrites = self.rewrite_normalized(&missing);
+            for (text, rewrite) in missing.iter().zip(rewrites) {
+                // Uma falha não é guardada: o texto é tentado de novo
+                let Ok(rewrite) = rewrite else { continue };
+                for (i, _) in normalized.iter().enumerate().filter(|(_, n)| n == text) {
+                    results[i] = rewrite.clone();
+                }
+                self.cache.insert(&version, text, rewrite);
+            }
+        }
+
+        results
+            .into_iter()
+            .zip(texts.iter().zip(&normalized))
+            .map(|(rewrite, (text, normalized))| {
+                let rewrite = cache::project(text, normalized, &rewrite?);
+                (rewrite.text != *text).then_some(rewrite)
+            })
+            .collect()
+    }
 
-        // 1. Tokenizar o texto de entrada
-        let encoding = tokenizer.encode(text, true).ok()?;
-        let input_ids: Vec<i64> = encoding.get_ids().iter().map(|&id| id as i64).collect();
-        let attention_mask: Vec<i64> = encoding
-            .get_attention_mask()
+    /// Reescreve textos já normalizados, dividindo os que não cabem.
+    /// Se a inferência de algum trecho falha, o texto inteiro falha.
+    fn rewrite_normalized(&mut self, texts: &[&str]) -> Vec<Inferred> {
+        let Some(tokenizer) = self.tokenizer.as_ref() else {
+            return vec![Err(InferenceFailed); texts.len()];
+        };
+        // Cada texto é tokenizado uma vez; os trechos são medidos nos offsets
+        let special = tokenizer
+            .encode("", true)
+            .map_or(0, |encoding| encoding.get_ids().len());
+        let chunks: Vec<Vec<chunk::Chunk>> = texts
+            .iter()
+            .map(|text| match tokenizer.encode(*text, false) {
+                Ok(encoding) => {
+                    let tokens = TokenCounts::new(encoding.get_offsets(), special);
+                    chunk::chunk(text, &self.chunking, &tokens)
+                }
+                // Sem tokens não há como dividir; a inferência vai recusá-lo
+                Err(_) => vec![chunk::Chunk {
+                    range: 0..text.len(),
+                    core: 0..text.len(),
+                }],
+            })
+            .collect();
+        let parts: Vec<&str> = texts
             .iter()
-            .map(|&m| m as i64)
+            .zip(&chunks)
+            .flat_map(|(text, chunks)| chunks.iter().map(|c| &text[c.range.clone()]))
             .collect();
+        let mut rewrites = self.infer(&parts).into_iter();
 
-        if input_ids.is_empty() {
-            return None;
-        }
+        texts
+            .iter()
+            .zip(&chunks)
+            .map(|(text, chunks)| {
+                // Todos os trechos do texto saem do iterador, mesmo depois de uma falha
+                let rewrites: Vec<Inferred> = rewrites.by_ref().take(chunks.len()).collect();
+                let rewrites: Vec<Option<Rewrite>> =
+                    rewrites.into_iter().collect::<Result<_, _>>()?;
+                Ok(match (chunks.as_slice(), rewrites.as_slice()) {
+                    ([single], [rewrite]) if single.range == (0..text.len()) => rewrite.clone(),
+                    _ if rewrites.iter().all(Option::is_none) => None,
+                    _ => {
+                        let rewrite = chunk::merge(text, chunks, &rewrites);
+                        (rewrite.text != *text).then_some(rewrite)
+                    }
+                })
+            })
+            .collect()
+    }
 
-        let seq_len = input_ids.len();
+    /// Uma inferência para textos que cabem no modelo, com o lote inteiro
+    /// preenchido e mascarado: o `tagger` roda uma vez; o `seq2seq` roda o
+    /// encoder uma vez e decodifica os textos juntos (ver
+    /// [`Seq2SeqModel::generate_batch`]).
+    fn infer(&mut self, texts: &[&str]) -> Vec<Inferred> {
+        let mut results = vec![Ok(None); texts.len()];
+        let (Some(tokenizer), Some(manifest), Some(model)) = (
+            self.tokenizer.as_ref(),
+            self.manifest.as_ref(),
+            self.model.as_mut(),
+        ) else {
+            return vec![Err(InferenceFailed); texts.len()];
+        };
 
-        // 2. Criar tensores de entrada [1, seq_len]
-        let input_ids_tensor =
-            Tensor::<i64>::from_array(([1usize, seq_len], input_ids.clone())).ok()?;
-        let attention_mask_tensor =
-            Tensor::<i64>::from_array(([1usize, seq_len], attention_mask)).ok()?;
+        // Textos vazios e palavras sozinhas maiores que o modelo aceita
+        // ficam como estão
+        let mut rows: Vec<(usize, Vec<i64>)> = Vec::new();
+        for (i, text) in texts.iter().enumerate() {
+            let Ok(encoding) = tokenizer.encode(*text, true) else {
+                continue;
+            };
+            let input_ids: Vec<i64> = encoding.get_ids().iter().map(|&id| id as i64).collect();
+            let too_long = manifest
+                .max_sequence_length
+                .is_some_and(|max| input_ids.len() > max);
+            if !input_ids.is_empty() && !too_long {
+                rows.push((i, input_ids));
+            }
+        }
+        if rows.is_empty() {
+            return results;
+        }
 
-        // 3. Executar a sessão ONNX
-        let outputs = session
-            .run(ort::inputs![
-                "input_ids" => input_ids_tensor,
-                "attention_mask" => attention_mask_tensor,
-            ])
-            .ok()?;
+        let ids: Vec<&[i64]> = rows.iter().map(|(_, ids)| ids.as_slice()).collect();
+        let tokens: Vec<Option<Vec<(u32, f32)>>> = match model {
+            Model::Tagger(session) => {
+                predict_tagger(session, manifest, &ids, pad_id(tokenizer, manifest))
+            }
+            Model::Seq2Seq(model) => {
+                match model.generate_batch(&ids, pad_id(tokenizer, manifest), &self.options) {
+                    Ok(outputs) => outputs.into_iter().map(Some).collect(),
+                    Err(_) => vec![None; ids.len()],
+                }
+            }
+            Model::MaskedLm(_) => return results,
+        };
 
-        // 4. Extrair logits do primeiro output (index 0)
-        // API do ort v2: try_extract_tensor retorna Result<(&Shape, &[T])>
-        // Shape implementa Deref<Target=[i64]>, então shape[0] = batch, shape[1] = seq_len, shape[2] = vocab_size
-        let (logits_shape, logits_data) = outputs[0].try_extract_tensor::<f32>().ok()?;
+        for ((i, _), tokens) in rows.iter().zip(tokens) {
+            let Some(tokens) = tokens else {
+                results[*i] = Err(InferenceFailed);
+                continue;
+            };
+            let rewrite = Rewrite::from_tokens(&tokens, |ids| tokenizer.decode(ids, true).ok());
+            // Só retorna se a correção for diferente do input
+            results[*i] = Ok(rewrite.filter(|r| r.text != texts[*i].trim() && !r.text.is_empty()));
+        }
+        results
+    }
 
-        if logits_shape.len() < 3 {
+    /// Notas de cada candidato para o trecho `slot` da frase, com um
+    /// modelo `masked_lm`: o trecho vira máscaras (uma por token do
+    /// candidato) e a nota é a log-probabilidade média dos tokens do
+    /// candidato nelas. `None` sem modelo mascarado ou se a frase não
+    /// cabe nele.
+    pub fn score_candidates(
+        &mut self,
+        sentence: &str,
+        slot: Range<usize>,
+        candidates: &[String],
+    ) -> Option<Vec<f32>> {
+        let tokenizer = self.tokenizer.as_ref()?;
+        let manifest = self.manifest.as_ref()?;
+        let Some(Model::MaskedLm(session)) = self.model.as_mut() else {
             return None;
-        }
+        };
+        let mask_id = manifest
+            .special_tokens
+            .mask
+            .or_else(|| tokenizer.token_to_id("[MASK]"))
+            .or_else(|| tokenizer.token_to_id("<mask>"))?;
+        let mask = tokenizer.id_to_token(mask_id)?;
+        let (before, after) = (sentence.get(..slot.start)?, sentence.get(slot.end..)?);
 
-        let vocab_size = logits_shape[2] as usize;
+        // Tokens de cada candidato como palavra solta (depois de um espaço)
+        let pieces: Vec<Vec<u32>> = candidates
+            .iter()
+            .map(|word| {
+                let encoding = tokenizer.encode(format!(" {}", word), false).ok()?;
+                Some(encoding.get_ids().to_vec())
+            })
+            .collect::<Option<_>>()?;
 
-        // 5. Argmax por posição para obter os token IDs corrigidos
-        let mut predicted_ids: Vec<u32> = Vec::with_capacity(seq_len);
-        for pos in 0..seq_len {
-            let start = pos * vocab_size;
-            let end = start + vocab_size;
-            if end > logits_data.len() {
-                break;
+        // Uma inferência por número de máscaras
+        let mut scores = vec![f32::NEG_INFINITY; candidates.len()];
+        let mut lengths: Vec<usize> = pieces.iter().map(Vec::len).filter(|&n| n > 0).collect();
+        lengths.sort_unstable();
+        lengths.dedup();
+        for length in lengths {
+            let masked = format!("{}{}{}", before, mask.repeat(length), after);
+            let encoding = tokenizer.encode(masked, true).ok()?;
+            let input_ids: Vec<i64> = encoding.get_ids().iter().map(|&id| id as i64).collect();
+            if manifest
+                .max_sequence_length
+                .is_some_and(|max| input_ids.len() > max)
+            {
+                return None;
+            }
+            let positions: Vec<usize> = (0..input_ids.len())
+                .filter(|&i| input_ids[i] == mask_id as i64)
+                .collect();
+            if positions.len() != length {
+                return None;
+            }
+            let (vocab, logits) = run_logits(session, manifest, &[&input_ids], 0)?;
+            for (score, ids) in scores.iter_mut().zip(&pieces) {
+                if ids.len() != length {
+                    continue;
+                }
+                let total: f32 = positions
+                    .iter()
+                    .zip(ids)
+                    .map(|(&position, &id)| {
+                        let row = logits.get(position * vocab..(position + 1) * vocab)?;
+                        seq2seq::log_softmax(row).get(id as usize).copied()
+                    })
+                    .sum::<Option<f32>>()?;
+                *score = total / length as f32;
             }
-            let slice = &logits_data[start..end];
-            let best_id = slice
-                .iter()
-                .enumerate()
-                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-                .map(|(idx, _)| idx as u32)
-                .unwrap_or(input_ids[pos] as u32);
-            predicted_ids.push(best_id);
         }
+        Some(scores)
+    }
 
-        // 6. Decodificar os IDs preditos de volta para texto
-        let corrected = tokenizer.decode(&predicted_ids, true).ok()?;
-
-        // 7. Só retorna se a correção for diferente do input
-        if corrected.trim() != text.trim() && !corrected.trim().is_empty() {
-            Some(corrected.trim().to_string())
-        } else {
-            None
+    /// Reordena os candidatos de [`StageA::candidates`](crate::stage_a::StageA::candidates)
+    /// para a palavra em `slot` pelo contexto da frase, quando os primeiros
+    /// estão perto (ver [`rerank::is_close`]).
+    ///
+    /// Só um modelo `masked_lm` dá notas, e ele não reescreve frases: quem
+    /// desempata candidatos carrega um `StageC` à parte, do diretório do
+    /// modelo mascarado, ao lado do que o [`StageB`](crate::stage_b::StageB)
+    /// usa para reescrever. Sem esse modelo, os candidatos voltam como vieram.
+    ///
+    /// ```no_run
+    /// use draco_brain::stage_a::StageA;
+    /// use draco_brain::stage_c::StageC;
+    ///
+    /// let mut engine = StageA::new();
+    /// engine.load_dictionary(&["casa", "caça", "cama"]);
+    /// let mut scorer = StageC::new();
+    /// scorer.init_from_dir("modelos/bert").unwrap();
+    ///
+    /// let sentence = "voltei para caza cedo";
+    /// let slot = 12..16;
+    /// let candidates = engine.candidates(&sentence[slot.clone()], 2, 5);
+    /// let ranked = scorer.rerank(sentence, slot, candidates);
+    /// if let Some(best) = ranked.first() {
+    ///     println!("{}", best.word);
+    /// }
+    /// ```
+    pub fn rerank(
+        &mut self,
+        sentence: &str,
+        slot: Range<usize>,
+        candidates: Vec<Candidate>,
+    ) -> Vec<Candidate> {
+        if !rerank::is_close(&candidates) {
+            return candidates;
         }
+        rerank::rerank(candidates, |words| {
+            self.score_candidates(sentence, slot, words)
+        })
+    }
+}
+
+/// Roda um grafo de uma saída de logits `[lote, seq_len, vocab]` sobre as
+/// frases, com os nomes do manifesto; devolve o tamanho do vocabulário e os
+/// logits, frase após frase (cada uma com `seq_len` posições).
+fn run_logits(
+    session: &mut Session,
+    manifest: &ModelManifest,
+    rows: &[&[i64]],
+    pad_id: i64,
+) -> Option<(usize, Vec<f32>)> {
+    let batch = rows.len();
+    let seq_len = rows.iter().map(|row| row.len()).max()?;
+    let shape = [batch, seq_len];
+
+    // 1. Criar tensores de entrada [lote, seq_len] com os nomes do
+    // manifesto; as frases mais curtas são completadas com `pad_id`, fora
+    // da máscara
+    let mut input_ids = vec![pad_id; batch * seq_len];
+    let mut attention_mask = vec![0i64; batch * seq_len];
+    for (r, row) in rows.iter().enumerate() {
+        input_ids[r * seq_len..r * seq_len + row.len()].copy_from_slice(row);
+        attention_mask[r * seq_len..r * seq_len + row.len()].fill(1);
+    }
+    let mut inputs: Vec<(String, SessionInputValue)> = vec![(
+        manifest.inputs.input_ids.clone(),
+        Tensor::<i64>::from_array((shape, input_ids)).ok()?.into(),
+    )];
+    if let Some(name) = &manifest.inputs.attention_mask {
+        inputs.push((
+            name.clone(),
+            Tensor::<i64>::from_array((shape, attention_mask))
+                .ok()?
+                .into(),
+        ));
+    }
+    if session.inputs().iter().any(|i| i.name() == TOKEN_TYPE_IDS) {
+        inputs.push((
+            TOKEN_TYPE_IDS.to_string(),
+            Tensor::<i64>::from_array((shape, vec![0i64; batch * seq_len]))
+                .ok()?
+                .into(),
+        ));
     }
+
+    // 2. Executar a sessão ONNX
+    let outputs = session.run(inputs).ok()?;
+
+    // 3. Extrair os logits (a saída do manifesto ou a primeira)
+    // API do ort v2: try_extract_tensor retorna Result<(&Shape, &[T])>
+    // Shape implementa Deref<Target=[i64]>, então shape[0] = batch, shape[1] = seq_len, shape[2] = vocab_size
+    let logits = match &manifest.outputs.logits {
+        Some(name) => outputs.get(name)?,
+        None => &outputs[0],
+    };
+    let (logits_shape, logits_data) = logits.try_extract_tensor::<f32>().ok()?;
+
+    if logits_shape.len() < 3 || logits_shape[1] as usize != seq_len {
+        return None;
+    }
+
+    Some((logits_shape[2] as usize, logits_data.to_vec()))
+}
+
+/// Token que completa as frases mais curtas de um lote.
+fn pad_id(tokenizer: &Tokenizer, manifest: &ModelManifest) -> i64 {
+    manifest
+        .special_tokens
+        .pad
+        .or_else(|| tokenizer.get_padding().map(|padding| padding.pad_id))
+        .or_else(|| tokenizer.token_to_id("[PAD]"))
+        .or_else(|| tokenizer.token_to_id("<pad>"))
+        .unwrap_or(0) as i64
+}
+
+/// Correção token a token: argmax dos logits em cada posição da entrada,
+/// com a sua probabilidade. Um resultado por frase do lote.
+fn predict_tagger(
+    session: &mut Session,
+    manifest: &ModelManifest,
+    rows: &[&[i64]],
+    pad_id: i64,
+) -> Vec<Option<Vec<(u32, f32)>>> {
+    // Sem máscara, o preenchimento mudaria a saída: uma frase por vez
+    if manifest.inputs.attention_mask.is_none() && rows.len() > 1 {
+        return rows
+            .iter()
+            .flat_map(|row| predict_tagger(session, manifest, &[row], pad_id))
+            .collect();
+    }
+    let Some((vocab_size, logits_data)) = run_logits(session, manifest, rows, pad_id) else {
+        return vec![None; rows.len()];
+    };
+    let seq_len = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    rows.iter()
+        .enumerate()
+        .map(|(r, row)| {
+            // Argmax por posição para obter os token IDs corrigidos
+            let mut predicted: Vec<(u32, f32)> = Vec::with_capacity(row.len());
+            for pos in 0..row.len() {
+                let start = (r * seq_len + pos) * vocab_size;
+                let end = start + vocab_size;
+                if end > logits_data.len() {
+                    break;
+                }
+                let slice = &logits_data[start..end];
+                let Some((best_id, &best)) = slice
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                else {
+                    break;
+                };
+                // Softmax só do vencedor: exp(best - max) / Σ exp(l - max), com max = best
+                let sum: f32 = slice.iter().map(|&l| (l - best).exp()).sum();
+                predicted.push((best_id as u32, 1.0 / sum));
+            }
+            Some(predicted)
+        })
+        .collect()
 }
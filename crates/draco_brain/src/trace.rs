@@ -0,0 +1,239 @@
+use crate::orthography::OrthographyRule;
+use crate::stage_a::CorrectionStep;
+use crate::variant::Variant;
+use serde::Serialize;
+use std::fmt;
+
+/// Quantos candidatos de cada lista são exibidos no `Display` do trace.
+const DISPLAY_LIMIT: usize = 10;
+
+/// Candidato avaliado por uma etapa do pipeline.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraceCandidate {
+    pub word: String,
+    /// Distância de edição até a entrada (0 quando não se aplica).
+    pub distance: usize,
+    pub frequency: u32,
+    /// Camada de dicionário que forneceu a palavra
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+}
+
+/// Um passo visitado por [`StageA::explain`](crate::stage_a::StageA::explain).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// Consulta ao glossário da equipe (só quando há um carregado).
+    Glossary { hit: Option<String> },
+    /// Grafia da outra norma convertida (só registrado quando há conversão).
+    Variant { variant: Variant, hit: String },
+    /// Grafia antiga atualizada pelo Acordo de 1990 (só registrado quando há
+    /// atualização), com as regras aplicadas.
+    Orthography {
+        rules: Vec<OrthographyRule>,
+        hit: String,
+    },
+    /// Consulta ao `TypoModel` (palavra inteira e sufixos).
+    TypoModel { hit: Option<String> },
+    /// Pertinência ao dicionário (e a camada que conhece a palavra).
+    Dictionary {
+        known: bool,
+        frequency: u32,
+        layer: Option<String>,
+    },
+    /// Busca de uma palavra muito mais frequente à distância 1.
+    FrequencyUpgrade {
+        frequency: u32,
+        /// Razão mínima exigida (`candidato > frequência × razão`).
+        required_ratio: u32,
+        /// Busca interrompida pelo orçamento (candidatos parciais).
+        incomplete: bool,
+        candidates: Vec<(TraceCandidate, f64)>,
+        chosen: Option<String>,
+    },
+    /// Trocas de letras adjacentes que formam palavras válidas.
+    Transposition {
+        candidates: Vec<TraceCandidate>,
+        chosen: Option<String>,
+    },
+    /// Palavras com a mesma chave fonética.
+    Phonetic {
+        key: String,
+        candidates: Vec<TraceCandidate>,
+        chosen: Option<String>,
+    },
+    /// Busca fuzzy no trie.
+    Fuzzy {
+        max_distance: usize,
+        /// Busca interrompida pelo orçamento (candidatos parciais).
+        incomplete: bool,
+        candidates: Vec<TraceCandidate>,
+        chosen: Option<String>,
+    },
+    /// Candidatos de uma etapa descartados pela lista de bloqueios.
+    Blocked {
+        step: &'static str,
+        words: Vec<String>,
+    },
+    /// Etapa pulada, com o motivo.
+    Skipped { step: &'static str, reason: String },
+}
+
+/// Registro completo de uma chamada de correção.
+#[derive(Debug, Clone, Serialize)]
+pub struct CorrectionTrace {
+    pub input: String,
+    pub aggressiveness: u32,
+    pub events: Vec<TraceEvent>,
+    pub output: String,
+    pub step: CorrectionStep,
+}
+
+fn fmt_candidates(f: &mut fmt::Formatter<'_>, candidates: &[TraceCandidate]) -> fmt::Result {
+    for c in candidates.iter().take(DISPLAY_LIMIT) {
+        writeln!(
+            f,
+            "       - {} (dist {}, freq {}){}",
+            c.word,
+            c.distance,
+            c.frequency,
+            fmt_layer(&c.layer)
+        )?;
+    }
+    if candidates.len() > DISPLAY_LIMIT {
+        writeln!(
+            f,
+            "       ... +{} candidatos",
+            candidates.len() - DISPLAY_LIMIT
+        )?;
+    }
+    Ok(())
+}
+
+fn fmt_layer(layer: &Option<String>) -> String {
+    match layer {
+        Some(name) => format!(" [{}]", name),
+        None => String::new(),
+    }
+}
+
+fn fmt_incomplete(incomplete: bool) -> &'static str {
+    if incomplete {
+        " [busca interrompida pelo orçamento]"
+    } else {
+        ""
+    }
+}
+
+fn fmt_chosen(chosen: &Option<String>) -> String {
+    match chosen {
+        Some(w) => format!("escolhido: {}", w),
+        None => "nenhum escolhido".to_string(),
+    }
+}
+
+impl fmt::Display for CorrectionTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "\"{}\" (agressividade {})",
+            self.input, self.aggressiveness
+        )?;
+
+        for (i, event) in self.events.iter().enumerate() {
+            write!(f, "  {}. ", i + 1)?;
+            match event {
+                TraceEvent::Glossary { hit: Some(hit) } => writeln!(f, "Glossário: → {}", hit)?,
+                TraceEvent::Glossary { hit: None } => writeln!(f, "Glossário: sem entrada")?,
+                TraceEvent::Variant { variant, hit } => {
+                    writeln!(f, "Norma ({}): → {}", variant.as_str(), hit)?
+                }
+                TraceEvent::Orthography { rules, hit } => writeln!(
+                    f,
+                    "Acordo de 1990 ({}): → {}",
+                    rules
+                        .iter()
+                        .map(|r| r.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    hit
+                )?,
+                TraceEvent::TypoModel { hit: Some(hit) } => writeln!(f, "TypoModel: → {}", hit)?,
+                TraceEvent::TypoModel { hit: None } => writeln!(f, "TypoModel: sem entrada")?,
+                TraceEvent::Dictionary {
+                    known,
+                    frequency,
+                    layer,
+                } => writeln!(
+                    f,
+                    "Dicionário: {} (freq {}){}",
+                    if *known { "conhecida" } else { "desconhecida" },
+                    frequency,
+                    fmt_layer(layer)
+                )?,
+                TraceEvent::FrequencyUpgrade {
+                    frequency,
+                    required_ratio,
+                    incomplete,
+                    candidates,
+                    chosen,
+                } => {
+                    writeln!(
+                        f,
+                        "Upgrade de frequência: freq {}, razão exigida > {}x, {}{}",
+                        frequency,
+                        required_ratio,
+                        fmt_chosen(chosen),
+                        fmt_incomplete(*incomplete)
+                    )?;
+                    for (c, ratio) in candidates.iter().take(DISPLAY_LIMIT) {
+                        writeln!(
+                            f,
+                            "       - {} (freq {}, {:.1}x){}",
+                            c.word,
+                            c.frequency,
+                            ratio,
+                            fmt_layer(&c.layer)
+                        )?;
+                    }
+                }
+                TraceEvent::Transposition { candidates, chosen } => {
+                    writeln!(f, "Transposição: {}", fmt_chosen(chosen))?;
+                    fmt_candidates(f, candidates)?;
+                }
+                TraceEvent::Phonetic {
+                    key,
+                    candidates,
+                    chosen,
+                } => {
+                    writeln!(f, "Fonética [{}]: {}", key, fmt_chosen(chosen))?;
+                    fmt_candidates(f, candidates)?;
+                }
+                TraceEvent::Fuzzy {
+                    max_distance,
+                    incomplete,
+                    candidates,
+                    chosen,
+                } => {
+                    writeln!(
+                        f,
+                        "Fuzzy (distância ≤ {}): {} candidatos, {}{}",
+                        max_distance,
+                        candidates.len(),
+                        fmt_chosen(chosen),
+                        fmt_incomplete(*incomplete)
+                    )?;
+                    fmt_candidates(f, candidates)?;
+                }
+                TraceEvent::Blocked { step, words } => {
+                    writeln!(f, "{}: bloqueados {}", step, words.join(", "))?
+                }
+                TraceEvent::Skipped { step, reason } => {
+                    writeln!(f, "{}: pulado ({})", step, reason)?
+                }
+            }
+        }
+
+        writeln!(f, "  => \"{}\" [{}]", self.output, self.step.as_str())
+    }
+}
@@ -1,21 +1,161 @@
-use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-#[derive(Default)]
-pub struct TrieNode {
-    pub is_end_of_word: bool,
-    pub frequency: u32, // 0 = sem frequência conhecida, maior = mais comum
-    pub children: HashMap<char, TrieNode>,
+/// Frequência de checagem do relógio durante a busca (em nós visitados):
+/// `Instant::now()` a cada nó custaria mais que a própria visita.
+const CLOCK_CHECK_INTERVAL: usize = 256;
+
+/// Limite de trabalho de uma busca de sugestões.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchBudget {
+    pub deadline: Option<Instant>,
+    pub max_nodes: Option<usize>,
 }
 
+impl SearchBudget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            deadline: Some(Instant::now() + timeout),
+            max_nodes: None,
+        }
+    }
+
+    pub fn with_max_nodes(max_nodes: usize) -> Self {
+        Self {
+            deadline: None,
+            max_nodes: Some(max_nodes),
+        }
+    }
+
+    fn exhausted(&self, visited: usize) -> bool {
+        if self.max_nodes.is_some_and(|max| visited >= max) {
+            return true;
+        }
+        match self.deadline {
+            Some(deadline) if visited.is_multiple_of(CLOCK_CHECK_INTERVAL) => {
+                Instant::now() >= deadline
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Resultado de [`Trie::search`].
+#[derive(Debug, Clone, Default)]
+pub struct SearchResult {
+    /// (palavra, distância, frequência), na ordem de `get_suggestions`
+    pub suggestions: Vec<(String, usize, u32)>,
+    /// `true` se o orçamento acabou antes de percorrer todo o trie
+    pub incomplete: bool,
+    pub visited_nodes: usize,
+}
+
+/// Índice de nó na arena.
+type NodeId = u32;
+const ROOT: NodeId = 0;
+
+/// Nó da arena. Os filhos de um nó ocupam posições seguidas da arena,
+/// ordenados pela letra (`children..children + child_count`), e são
+/// achados por busca binária. Um nó custa 20 bytes fixos em vez de um
+/// `HashMap` próprio.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    letter: char,
+    frequency: u32, // 0 = sem frequência conhecida, maior = mais comum
+    children: NodeId,
+    child_count: u16,
+    /// Posições reservadas para os filhos; as que passam de `child_count`
+    /// estão livres para o próximo filho
+    child_capacity: u16,
+    is_end_of_word: bool,
+}
+
+impl Node {
+    fn new(letter: char) -> Self {
+        Self {
+            letter,
+            frequency: 0,
+            children: 0,
+            child_count: 0,
+            child_capacity: 0,
+            is_end_of_word: false,
+        }
+    }
+}
+
+/// Trie em arena: todos os nós vivem num único `Vec` e se referenciam por
+/// índice. Carregar o dicionário completo faz poucas realocações grandes em
+/// vez de milhões de alocações pequenas.
+///
+/// Um nó que ganha mais filhos do que reservou muda o bloco de filhos para o
+/// fim da arena (com o dobro de posições) e deixa o antigo para trás;
+/// [`shrink_to_fit`](Self::shrink_to_fit) compacta a arena depois de uma
+/// carga grande.
+#[derive(Debug, Clone)]
 pub struct Trie {
-    root: TrieNode,
+    nodes: Vec<Node>,
+    words: usize,
+}
+
+impl Default for Trie {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Trie {
     pub fn new() -> Self {
         Self {
-            root: TrieNode::default(),
+            nodes: vec![Node::new('\0')],
+            words: 0,
+        }
+    }
+
+    /// Número de palavras distintas inseridas.
+    pub fn len(&self) -> usize {
+        self.words
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words == 0
+    }
+
+    /// Número de posições na arena (inclui a raiz e, antes de
+    /// [`shrink_to_fit`](Self::shrink_to_fit), as reservas e os blocos
+    /// abandonados).
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Memória ocupada pela arena, em bytes (capacidade reservada).
+    pub fn heap_bytes(&self) -> usize {
+        self.nodes.capacity() * std::mem::size_of::<Node>()
+    }
+
+    /// Compacta a arena depois de uma carga grande: regrava os nós em
+    /// largura, com os blocos de filhos do tamanho exato, e libera a
+    /// capacidade extra.
+    pub fn shrink_to_fit(&mut self) {
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        let mut root = self.nodes[ROOT as usize];
+        root.child_capacity = root.child_count;
+        nodes.push(root);
+        let mut next = 0;
+        while next < nodes.len() {
+            let parent = nodes[next];
+            let start = nodes.len();
+            nodes.extend_from_slice(self.children(&parent));
+            for child in &mut nodes[start..] {
+                child.child_capacity = child.child_count;
+            }
+            nodes[next].children = start as NodeId;
+            next += 1;
         }
+        nodes.shrink_to_fit();
+        self.nodes = nodes;
     }
 
     pub fn insert(&mut self, word: &str) {
@@ -23,103 +163,179 @@ impl Trie {
     }
 
     pub fn insert_with_frequency(&mut self, word: &str, frequency: u32) {
-        let mut node = &mut self.root;
+        let mut node = ROOT;
         for c in word.chars() {
-            node = node.children.entry(c).or_insert_with(TrieNode::default);
+            node = self.child_or_insert(node, c);
+        }
+        let node = &mut self.nodes[node as usize];
+        if !node.is_end_of_word {
+            node.is_end_of_word = true;
+            self.words += 1;
         }
-        node.is_end_of_word = true;
         if frequency > node.frequency {
             node.frequency = frequency;
         }
     }
 
     pub fn contains(&self, word: &str) -> bool {
-        let mut node = &self.root;
+        self.frequency(word).is_some()
+    }
+
+    /// Frequência gravada para a palavra, ou `None` se ela não está no trie.
+    pub fn frequency(&self, word: &str) -> Option<u32> {
+        let mut node = ROOT;
         for c in word.chars() {
-            if let Some(n) = node.children.get(&c) {
-                node = n;
-            } else {
-                return false;
-            }
+            node = self.child(node, c)?;
         }
-        node.is_end_of_word
+        let node = &self.nodes[node as usize];
+        node.is_end_of_word.then_some(node.frequency)
+    }
+
+    /// Bloco de filhos do nó, em ordem alfabética.
+    fn children(&self, node: &Node) -> &[Node] {
+        let start = node.children as usize;
+        &self.nodes[start..start + node.child_count as usize]
+    }
+
+    fn child(&self, parent: NodeId, letter: char) -> Option<NodeId> {
+        let node = &self.nodes[parent as usize];
+        let i = self
+            .children(node)
+            .binary_search_by_key(&letter, |child| child.letter)
+            .ok()?;
+        Some(node.children + i as NodeId)
+    }
+
+    /// Devolve o filho `letter` de `parent`, criando-o na posição ordenada.
+    /// Criar um filho move os irmãos seguintes uma posição adiante.
+    fn child_or_insert(&mut self, parent: NodeId, letter: char) -> NodeId {
+        let node = self.nodes[parent as usize];
+        let i = match self
+            .children(&node)
+            .binary_search_by_key(&letter, |child| child.letter)
+        {
+            Ok(i) => return node.children + i as NodeId,
+            Err(i) => i,
+        };
+
+        let count = node.child_count as usize;
+        let mut start = node.children as usize;
+        if node.child_count == node.child_capacity {
+            let capacity = node.child_capacity.saturating_mul(2).max(1);
+            assert!(
+                capacity > node.child_capacity,
+                "nó do trie com filhos demais"
+            );
+            let moved = self.nodes.len();
+            assert!(
+                moved + capacity as usize <= NodeId::MAX as usize,
+                "trie excedeu 2^32 nós"
+            );
+            self.nodes.extend_from_within(start..start + count);
+            self.nodes
+                .resize(moved + capacity as usize, Node::new('\0'));
+            start = moved;
+            let parent = &mut self.nodes[parent as usize];
+            parent.children = start as NodeId;
+            parent.child_capacity = capacity;
+        }
+        self.nodes
+            .copy_within(start + i..start + count, start + i + 1);
+        self.nodes[start + i] = Node::new(letter);
+        self.nodes[parent as usize].child_count += 1;
+        (start + i) as NodeId
+    }
+
+    /// Empilha os filhos de `parent` de modo que saiam da pilha em ordem alfabética.
+    fn push_children(&self, stack: &mut Vec<(NodeId, usize)>, parent: NodeId, depth: usize) {
+        let node = &self.nodes[parent as usize];
+        let start = node.children;
+        stack.extend(
+            (0..node.child_count as NodeId)
+                .rev()
+                .map(|i| (start + i, depth)),
+        );
     }
 
     /// Retorna sugestões de palavras com distância de Levenshtein <= max_distance.
     /// Ordenadas por: (distância crescente, frequência decrescente).
     /// Isso garante que palavras COMUNS sejam preferidas quando há empate de distância.
     pub fn get_suggestions(&self, word: &str, max_distance: usize) -> Vec<(String, usize, u32)> {
-        let mut suggestions = Vec::new();
-        let word_chars: Vec<char> = word.chars().collect();
-        let current_row: Vec<usize> = (0..=word_chars.len()).collect();
-
-        for (&c, child) in &self.root.children {
-            self.search_recursive(
-                child,
-                c,
-                &word_chars,
-                &current_row,
-                &mut String::new(),
-                max_distance,
-                &mut suggestions,
-            );
-        }
+        self.search(word, max_distance, SearchBudget::unlimited())
+            .suggestions
+    }
 
-        // Ordenar por: distância crescente, depois frequência decrescente
-        suggestions.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| b.2.cmp(&a.2)));
-        suggestions
-    }
-
-    fn search_recursive(
-        &self,
-        node: &TrieNode,
-        letter: char,
-        word_chars: &[char],
-        previous_row: &[usize],
-        current_word: &mut String,
-        max_distance: usize,
-        suggestions: &mut Vec<(String, usize, u32)>,
-    ) {
-        current_word.push(letter);
+    /// Busca com orçamento de trabalho: para ao atingir o prazo ou o número
+    /// máximo de nós visitados e devolve o que já encontrou, com
+    /// `incomplete = true`. Mesma ordenação de [`get_suggestions`](Self::get_suggestions).
+    ///
+    /// A caminhada é iterativa (pilha explícita) e reaproveita um único buffer
+    /// de linhas da matriz de Levenshtein: a linha da profundidade `d` fica em
+    /// `rows[d * colunas..]`, sobrescrita a cada nó visitado naquela profundidade.
+    pub fn search(&self, word: &str, max_distance: usize, budget: SearchBudget) -> SearchResult {
+        let word_chars: Vec<char> = word.chars().collect();
         let columns = word_chars.len() + 1;
-        let mut current_row = vec![0usize; columns];
-        current_row[0] = previous_row[0] + 1;
-
-        for i in 1..columns {
-            let insert_cost = current_row[i - 1] + 1;
-            let delete_cost = previous_row[i] + 1;
-            let replace_cost = if word_chars[i - 1] == letter {
-                previous_row[i - 1]
-            } else {
-                previous_row[i - 1] + 1
-            };
-
-            current_row[i] = insert_cost.min(delete_cost).min(replace_cost);
-        }
 
-        if current_row[columns - 1] <= max_distance && node.is_end_of_word {
-            suggestions.push((
-                current_word.clone(),
-                current_row[columns - 1],
-                node.frequency,
-            ));
-        }
+        // Linha 0: distância da palavra vazia até cada prefixo da entrada
+        let mut rows: Vec<usize> = (0..columns).collect();
+        let mut current_word: Vec<char> = Vec::new();
+        let mut stack: Vec<(NodeId, usize)> = Vec::new();
+        self.push_children(&mut stack, ROOT, 1);
+
+        let mut suggestions = Vec::new();
+        let mut visited = 0usize;
+        let mut incomplete = false;
+
+        while let Some((id, depth)) = stack.pop() {
+            if budget.exhausted(visited) {
+                incomplete = true;
+                break;
+            }
+            visited += 1;
+            let node = &self.nodes[id as usize];
+            let letter = node.letter;
 
-        // Poda: só continua se ainda há chance de encontrar uma palavra dentro do limite
-        if *current_row.iter().min().unwrap() <= max_distance {
-            for (&c, child) in &node.children {
-                self.search_recursive(
-                    child,
-                    c,
-                    word_chars,
-                    &current_row,
-                    current_word,
-                    max_distance,
-                    suggestions,
-                );
+            if rows.len() < (depth + 1) * columns {
+                rows.resize((depth + 1) * columns, 0);
+            }
+            let (previous, rest) = rows[(depth - 1) * columns..].split_at_mut(columns);
+            let current = &mut rest[..columns];
+
+            current[0] = previous[0] + 1;
+            let mut row_min = current[0];
+            for i in 1..columns {
+                let insert_cost = current[i - 1] + 1;
+                let delete_cost = previous[i] + 1;
+                let replace_cost = if word_chars[i - 1] == letter {
+                    previous[i - 1]
+                } else {
+                    previous[i - 1] + 1
+                };
+                current[i] = insert_cost.min(delete_cost).min(replace_cost);
+                row_min = row_min.min(current[i]);
+            }
+
+            current_word.truncate(depth - 1);
+            current_word.push(letter);
+
+            let distance = current[columns - 1];
+            if distance <= max_distance && node.is_end_of_word {
+                suggestions.push((current_word.iter().collect(), distance, node.frequency));
+            }
+
+            // Poda: só continua se ainda há chance de encontrar uma palavra dentro do limite
+            if row_min <= max_distance {
+                self.push_children(&mut stack, id, depth + 1);
             }
         }
 
-        current_word.pop();
+        // Ordenar por: distância crescente, depois frequência decrescente
+        // (empates ficam em ordem alfabética, a ordem da caminhada)
+        suggestions.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| b.2.cmp(&a.2)));
+        SearchResult {
+            suggestions,
+            incomplete,
+            visited_nodes: visited,
+        }
     }
 }
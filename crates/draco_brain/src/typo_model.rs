@@ -1,4 +1,124 @@
 use std::collections::HashMap;
+use std::fmt;
+
+/// SEÇÃO 1 do modelo: abreviações de internet / chat (abreviação → forma plena).
+/// Exposta para que geradores de corpus possam produzir o caminho inverso.
+pub const CHAT_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("vc", "você"),
+    ("vcs", "vocês"),
+    ("tb", "também"),
+    ("tbm", "também"),
+    ("tmb", "também"),
+    ("td", "tudo"),
+    ("mt", "muito"),
+    ("mto", "muito"),
+    ("mta", "muita"),
+    ("mts", "muitos"),
+    ("mtas", "muitas"),
+    ("msm", "mesmo"),
+    ("msg", "mensagem"),
+    ("msgs", "mensagens"),
+    ("pq", "porque"),
+    ("qdo", "quando"),
+    ("qnd", "quando"),
+    ("qto", "quanto"),
+    ("qta", "quanta"),
+    ("qtos", "quantos"),
+    ("qtas", "quantas"),
+    ("qq", "qualquer"),
+    ("cmg", "comigo"),
+    ("ctg", "contigo"),
+    ("hj", "hoje"),
+    ("dps", "depois"),
+    ("obg", "obrigado"),
+    ("obgd", "obrigado"),
+    ("blz", "beleza"),
+    ("flw", "falou"),
+    ("vlw", "valeu"),
+    ("agr", "agora"),
+    ("nd", "nada"),
+    ("nda", "nada"),
+    ("ngm", "ninguém"),
+    ("pfv", "por favor"),
+    ("pfvr", "por favor"),
+    ("pf", "por favor"),
+    ("tdo", "tudo"),
+    ("tda", "toda"),
+    ("tds", "todos"),
+    ("amg", "amigo"),
+    ("amgs", "amigos"),
+];
+
+/// Regra extra do `TypoModel`, lida de um arquivo de regras.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypoRule {
+    /// Palavra inteira: `erro<TAB>correção`
+    Word { from: String, to: String },
+    /// Terminação: `-erro<TAB>-correção`
+    Suffix { from: String, to: String },
+}
+
+/// Erro de sintaxe num arquivo de regras, com a linha (a partir de 1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "linha {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+/// Lê regras no formato TSV, uma por linha:
+///
+/// ```text
+/// # comentário
+/// vc<TAB>você
+/// -cao<TAB>-ção
+/// ```
+///
+/// O arquivo inteiro é rejeitado no primeiro erro, para que uma edição pela
+/// metade não substitua as regras em uso.
+pub fn parse_rules(text: &str) -> Result<Vec<TypoRule>, RuleError> {
+    let mut rules = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: &str| RuleError {
+            line: i + 1,
+            message: message.to_string(),
+        };
+        let line = line.trim_start_matches('\u{feff}').trim_end_matches('\r');
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split('\t');
+        let (from, to) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(from), Some(to), None) => (from.trim().to_lowercase(), to.trim().to_lowercase()),
+            (_, None, _) => return Err(error("esperado `erro<TAB>correção`")),
+            _ => return Err(error("colunas demais (esperadas 2)")),
+        };
+        if from.is_empty() || to.is_empty() {
+            return Err(error("campo vazio"));
+        }
+        if from == to {
+            return Err(error(&format!("regra inútil: \"{}\" → \"{}\"", from, to)));
+        }
+
+        rules.push(match (from.strip_prefix('-'), to.strip_prefix('-')) {
+            (Some(f), Some(t)) if !f.is_empty() && !t.is_empty() => TypoRule::Suffix {
+                from: f.to_string(),
+                to: t.to_string(),
+            },
+            (None, None) => TypoRule::Word { from, to },
+            _ => return Err(error("sufixo precisa de `-` nos dois lados")),
+        });
+    }
+    Ok(rules)
+}
 
 /// Modelo de correção de erros de digitação comuns no PT-BR.
 /// Mapeia palavras completas digitadas sem acentos para a forma correta,
@@ -8,11 +128,21 @@ use std::collections::HashMap;
 /// - Nunca inserir entradas "word → word" (inúteis e bloqueiam pipeline)
 /// - Palavras ambíguas (nos/nós, esta/está) ficam FORA — tratadas por frequência
 /// - Sem duplicatas
+///
+/// Regras extras podem ser carregadas de arquivo com [`parse_rules`] e
+/// [`TypoModel::add_rules`].
+#[derive(Clone)]
 pub struct TypoModel {
     word_map: HashMap<String, String>,
     suffix_map: Vec<(String, String)>,
 }
 
+impl Default for TypoModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TypoModel {
     pub fn new() -> Self {
         let mut m = HashMap::with_capacity(512);
@@ -20,49 +150,9 @@ impl TypoModel {
         // ═══════════════════════════════════════════════════
         // SEÇÃO 1: Abreviações de internet / chat
         // ═══════════════════════════════════════════════════
-        m.insert("vc".into(), "você".into());
-        m.insert("vcs".into(), "vocês".into());
-        m.insert("tb".into(), "também".into());
-        m.insert("tbm".into(), "também".into());
-        m.insert("tmb".into(), "também".into());
-        m.insert("td".into(), "tudo".into());
-        m.insert("mt".into(), "muito".into());
-        m.insert("mto".into(), "muito".into());
-        m.insert("mta".into(), "muita".into());
-        m.insert("mts".into(), "muitos".into());
-        m.insert("mtas".into(), "muitas".into());
-        m.insert("msm".into(), "mesmo".into());
-        m.insert("msg".into(), "mensagem".into());
-        m.insert("msgs".into(), "mensagens".into());
-        m.insert("pq".into(), "porque".into());
-        m.insert("qdo".into(), "quando".into());
-        m.insert("qnd".into(), "quando".into());
-        m.insert("qto".into(), "quanto".into());
-        m.insert("qta".into(), "quanta".into());
-        m.insert("qtos".into(), "quantos".into());
-        m.insert("qtas".into(), "quantas".into());
-        m.insert("qq".into(), "qualquer".into());
-        m.insert("cmg".into(), "comigo".into());
-        m.insert("ctg".into(), "contigo".into());
-        m.insert("hj".into(), "hoje".into());
-        m.insert("dps".into(), "depois".into());
-        m.insert("obg".into(), "obrigado".into());
-        m.insert("obgd".into(), "obrigado".into());
-        m.insert("blz".into(), "beleza".into());
-        m.insert("flw".into(), "falou".into());
-        m.insert("vlw".into(), "valeu".into());
-        m.insert("agr".into(), "agora".into());
-        m.insert("nd".into(), "nada".into());
-        m.insert("nda".into(), "nada".into());
-        m.insert("ngm".into(), "ninguém".into());
-        m.insert("pfv".into(), "por favor".into());
-        m.insert("pfvr".into(), "por favor".into());
-        m.insert("pf".into(), "por favor".into());
-        m.insert("tdo".into(), "tudo".into());
-        m.insert("tda".into(), "toda".into());
-        m.insert("tds".into(), "todos".into());
-        m.insert("amg".into(), "amigo".into());
-        m.insert("amgs".into(), "amigos".into());
+        for (abbr, full) in CHAT_ABBREVIATIONS {
+            m.insert(abbr.to_string(), full.to_string());
+        }
 
         // ═══════════════════════════════════════════════════
         // SEÇÃO 2: Pares de confusão (palavra válida → mais provável)
@@ -468,6 +558,21 @@ impl TypoModel {
         }
     }
 
+    /// Acrescenta regras (as de arquivo têm precedência sobre as embutidas).
+    pub fn add_rules(&mut self, rules: &[TypoRule]) {
+        for rule in rules {
+            match rule {
+                TypoRule::Word { from, to } => {
+                    self.word_map.insert(from.clone(), to.clone());
+                }
+                TypoRule::Suffix { from, to } => {
+                    self.suffix_map.retain(|(suffix, _)| suffix != from);
+                    self.suffix_map.insert(0, (from.clone(), to.clone()));
+                }
+            }
+        }
+    }
+
     /// Tenta corrigir uma palavra completa (já em minúsculas).
     pub fn get_correction(&self, word_lower: &str) -> Option<String> {
         // 1. Verificação exata
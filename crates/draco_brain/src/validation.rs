@@ -62,4 +62,1933 @@ mod tests {
         // Garantir que a latência é < 1ms (1000 µs)
         assert!(avg < 1000.0, "Latência muito alta: {} µs", avg);
     }
+
+    #[test]
+    fn test_explain_matches_correct() {
+        use crate::stage_a::CorrectionStep;
+        use crate::trace::TraceEvent;
+
+        let mut engine = StageA::new();
+        engine.load_dictionary(&["casa", "carro", "computador", "você"]);
+
+        // O trace deve chegar ao mesmo resultado de `correct`
+        for word in ["pro", "casa", "computaodr", "cassa", "xyzw"] {
+            let trace = engine.explain(word, 1);
+            assert_eq!(trace.output, engine.correct(word, 1), "trace de '{}'", word);
+        }
+
+        let trace = engine.explain("pro", 1);
+        assert_eq!(trace.step, CorrectionStep::TypoModel);
+        assert_eq!(
+            trace.events,
+            vec![TraceEvent::TypoModel {
+                hit: Some("pró".into())
+            }]
+        );
+
+        let trace = engine.explain("computaodr", 1);
+        assert_eq!(trace.step, CorrectionStep::Transposition);
+        assert!(matches!(
+            trace.events.last(),
+            Some(TraceEvent::Transposition { chosen: Some(w), .. }) if w == "computador"
+        ));
+    }
+
+    #[test]
+    fn test_bounded_search() {
+        use crate::trace::TraceEvent;
+        use crate::trie::{SearchBudget, Trie};
+
+        let mut trie = Trie::new();
+        for (word, freq) in [("casa", 100), ("caso", 500), ("cama", 50), ("carro", 10)] {
+            trie.insert_with_frequency(word, freq);
+        }
+
+        // Sem orçamento: busca completa, mesma ordem de get_suggestions
+        let full = trie.search("cas", 1, SearchBudget::unlimited());
+        assert!(!full.incomplete);
+        assert_eq!(full.suggestions, trie.get_suggestions("cas", 1));
+        assert_eq!(full.suggestions[0], ("caso".to_string(), 1, 500));
+        assert_eq!(full.suggestions[1], ("casa".to_string(), 1, 100));
+
+        // Blocos de filhos crescem fora de ordem e sobrevivem à compactação
+        let mut grown = Trie::new();
+        for word in [
+            "zebra", "casa", "abacate", "cama", "caso", "cabo", "cá", "c",
+        ] {
+            grown.insert_with_frequency(word, 1);
+        }
+        let before = grown.node_count();
+        grown.shrink_to_fit();
+        assert!(grown.node_count() < before);
+        grown.insert_with_frequency("carro", 10);
+        grown.insert("cabana");
+        for word in [
+            "zebra", "casa", "abacate", "cama", "caso", "cabo", "cá", "c", "carro",
+        ] {
+            assert!(grown.contains(word), "{}", word);
+        }
+        assert!(!grown.contains("ca") && !grown.contains("cab"));
+        assert_eq!(grown.frequency("carro"), Some(10));
+        assert_eq!(grown.len(), 10);
+        let mut plain = Trie::new();
+        for word in [
+            "c", "cabana", "cabo", "cama", "caso", "casa", "cá", "abacate",
+        ] {
+            plain.insert_with_frequency(word, 1);
+        }
+        plain.insert_with_frequency("carro", 10);
+        plain.insert_with_frequency("zebra", 1);
+        assert_eq!(
+            grown.get_suggestions("cas", 2),
+            plain.get_suggestions("cas", 2)
+        );
+        assert_eq!(grown.get_suggestions("cas", 2).len(), 6);
+
+        // Orçamento de nós estourado: parcial e marcado como incompleto
+        let partial = trie.search("cas", 1, SearchBudget::with_max_nodes(2));
+        assert!(partial.incomplete);
+        assert_eq!(partial.visited_nodes, 2);
+        assert!(partial.suggestions.is_empty());
+
+        // Prazo já vencido: nenhuma visita
+        let expired = trie.search(
+            "cas",
+            2,
+            SearchBudget::with_timeout(std::time::Duration::ZERO),
+        );
+        assert!(expired.incomplete);
+        assert_eq!(expired.visited_nodes, 0);
+
+        // No StageA o trace reporta a busca interrompida
+        let mut engine = StageA::new();
+        engine.load_dictionary(&["computador", "comprador"]);
+        engine.set_search_limits(None, Some(1));
+        let trace = engine.explain("computadr", 1);
+        assert!(trace.events.iter().any(|e| matches!(
+            e,
+            TraceEvent::Fuzzy {
+                incomplete: true,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn test_shared_engine_snapshots() {
+        use crate::shared::SharedEngine;
+        use std::sync::Arc;
+
+        let mut engine = StageA::new();
+        engine.load_dictionary(&["casa", "carro"]);
+        let shared = Arc::new(SharedEngine::new(engine));
+
+        let before = shared.snapshot();
+        assert_eq!(before.correct("draconis", 1), "draconis");
+
+        assert!(shared.update(|e| e.add_user_word("Draconiz")));
+        assert!(!shared.update(|e| e.add_user_word("casa")));
+
+        // O snapshot antigo não enxerga a mudança; o novo, sim
+        assert_eq!(before.user_word_count(), 0);
+        assert_eq!(shared.snapshot().user_word_count(), 1);
+        assert_eq!(shared.correct("draconis", 1), "draconiz");
+        assert_eq!(shared.correct("Draconiz", 1), "Draconiz");
+
+        // Leituras concorrentes com atualizações
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let shared = Arc::clone(&shared);
+                std::thread::spawn(move || {
+                    for n in 0..50 {
+                        if i == 0 {
+                            shared.update(|e| e.add_user_word(&format!("palavra{}", n)));
+                        } else {
+                            assert_eq!(shared.correct("carro", 1), "carro");
+                        }
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(shared.snapshot().user_word_count(), 51);
+
+        // Recarregar o dicionário base mantém as palavras do usuário
+        shared.replace(StageA::new());
+        assert_eq!(shared.snapshot().user_word_count(), 51);
+        assert_eq!(shared.correct("draconis", 1), "draconiz");
+    }
+
+    #[test]
+    fn test_engine_builder_report() {
+        use crate::builder::{
+            EngineConfig, Resource, BLOCKLIST_FILE, DICTIONARY_FILE, FREQUENCY_FILE, PACKS_DIR,
+            TYPO_RULES_FILE,
+        };
+        use crate::EngineBuilder;
+
+        let dir = std::env::temp_dir().join(format!("draco_builder_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(DICTIONARY_FILE), "casa\ncaso\ncarro\n").unwrap();
+
+        // Sem arquivo de frequências (e sem dados embutidos): reportado como ausente
+        let (_, report) = EngineBuilder::new().data_dir(&dir).build().unwrap();
+        assert_eq!(report.entries(Resource::Dictionary), 3);
+        let missing: Vec<_> = report.missing().map(|r| r.resource).collect();
+        if !crate::embedded::AVAILABLE {
+            assert_eq!(missing, vec![Resource::Frequency]);
+        }
+
+        // Com frequências: carregadas antes do dicionário, decidem o empate
+        std::fs::write(dir.join(FREQUENCY_FILE), "casa 900\ncaso 10\n").unwrap();
+        let config: EngineConfig =
+            serde_json::from_str(&format!("{{\"data_dir\": {:?}}}", dir)).unwrap();
+        let (engine, report) = EngineBuilder::from_config(config).build().unwrap();
+        assert_eq!(report.entries(Resource::Frequency), 2);
+        assert_eq!(report.missing().count(), 0);
+        assert_eq!(engine.correct("cas", 1), "casa");
+
+        // Recursos opcionais quebrados ficam de fora com um aviso; os bons entram
+        std::fs::write(dir.join(BLOCKLIST_FILE), "a\tb\tc\n").unwrap();
+        std::fs::write(dir.join(TYPO_RULES_FILE), "sem tab\n").unwrap();
+        std::fs::create_dir_all(dir.join(PACKS_DIR)).unwrap();
+        std::fs::write(dir.join(PACKS_DIR).join("vazio.txt"), "").unwrap();
+        std::fs::write(dir.join(PACKS_DIR).join("ti.txt"), "kubernetes 50\n").unwrap();
+        let (engine, report) = EngineBuilder::new().data_dir(&dir).build().unwrap();
+        for resource in [Resource::Blocklist, Resource::TypoRules, Resource::Packs] {
+            let r = report.get(resource).unwrap();
+            assert_eq!(r.warnings.len(), 1, "{:?}: {:?}", resource, r.warnings);
+        }
+        assert!(!report.get(Resource::Blocklist).unwrap().loaded);
+        assert_eq!(report.entries(Resource::Packs), 1);
+        assert!(engine.layer("ti").is_some());
+        assert!(engine.layer("vazio").is_none());
+        assert_eq!(engine.correct("cas", 1), "casa");
+
+        // Sem dicionário utilizável não há engine
+        std::fs::write(dir.join(DICTIONARY_FILE), "").unwrap();
+        let Err(error) = EngineBuilder::new().data_dir(&dir).build() else {
+            panic!("dicionário vazio deveria falhar");
+        };
+        assert_eq!(error.resource, Resource::Dictionary);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_path_resolution_order() {
+        use crate::builder::{EngineConfig, Resource, DICTIONARY_FILE, FREQUENCY_FILE};
+        use crate::paths::{Origin, PathResolver};
+
+        let root = std::env::temp_dir().join(format!("draco_paths_{}", std::process::id()));
+        let (env, user, exe) = (root.join("env"), root.join("user"), root.join("bin"));
+        for dir in [&env, &user, &exe.join("data")] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::fs::write(user.join(DICTIONARY_FILE), "casa\n").unwrap();
+        std::fs::write(exe.join("data").join(DICTIONARY_FILE), "casa\n").unwrap();
+        std::fs::write(exe.join("data").join(FREQUENCY_FILE), "casa 1\n").unwrap();
+
+        let resolver = PathResolver::from_environment()
+            .with_env_dir(&env)
+            .with_user_dir(&user)
+            .with_exe_dir(&exe);
+
+        // Cada recurso é resolvido sozinho
+        let dict = resolver.resolve(Resource::Dictionary);
+        assert_eq!(dict.origin(), Some(Origin::UserDir));
+        assert_eq!(dict.candidates[0].origin, Origin::Env);
+        assert_eq!(dict.candidates[0].rejected.as_deref(), Some("não existe"));
+        assert!(dict.candidates[2]
+            .rejected
+            .as_deref()
+            .unwrap()
+            .starts_with("ignorado"));
+        let freq = resolver.resolve(Resource::Frequency);
+        assert_eq!(freq.origin(), Some(Origin::Executable));
+
+        // Modelos não têm versão embutida
+        let models = resolver.resolve(Resource::Models);
+        assert_eq!(models.origin(), None);
+        assert_ne!(models.candidates.last().unwrap().origin, Origin::Embedded);
+
+        // A configuração explícita vence todas as outras origens
+        let config = EngineConfig {
+            dictionary: Some(exe.join("data").join(DICTIONARY_FILE)),
+            ..EngineConfig::default()
+        };
+        let dict = resolver
+            .clone()
+            .with_config(&config)
+            .resolve(Resource::Dictionary);
+        assert_eq!(dict.origin(), Some(Origin::Config));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_front_coding_roundtrip() {
+        use crate::front_coding::{decode, encode};
+
+        let words = ["a", "casa", "casamento", "casar", "ção", "çãozinha", "z"];
+        let data = encode(words);
+        assert_eq!(decode(&data).unwrap(), words);
+        // Prefixos compartilhados não são repetidos
+        let plain: usize = words.iter().map(|w| w.len() + 1).sum();
+        assert!(data.len() - 8 < plain);
+
+        assert_eq!(decode(&data[..data.len() - 1]), None);
+        assert_eq!(decode(b"lixo"), None);
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        use crate::builder::{
+            Resource, BLOCKLIST_FILE, DICTIONARY_FILE, FREQUENCY_FILE, PACKS_DIR, TYPO_RULES_FILE,
+        };
+        use crate::shared::SharedEngine;
+        use crate::watcher::{DataWatcher, ReloadEvent};
+        use crate::EngineBuilder;
+        use std::sync::{mpsc, Arc};
+        use std::time::Duration;
+
+        let dir = std::env::temp_dir().join(format!("draco_reload_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(DICTIONARY_FILE), "casa\n").unwrap();
+        std::fs::write(dir.join(FREQUENCY_FILE), "casa 900\n").unwrap();
+        std::fs::write(dir.join(TYPO_RULES_FILE), "# regras\nxpto\tcasa\n").unwrap();
+
+        let builder = EngineBuilder::new().data_dir(&dir);
+        let (engine, _) = builder.build().unwrap();
+        let shared = Arc::new(SharedEngine::new(engine));
+        assert_eq!(shared.correct("xpto", 1), "casa");
+        assert_eq!(shared.correct("gatu", 1), "gatu");
+
+        let (tx, rx) = mpsc::channel();
+        let watcher = DataWatcher::spawn(
+            builder,
+            Arc::clone(&shared),
+            Duration::from_millis(20),
+            move |event| {
+                let _ = tx.send(event);
+            },
+        );
+        let next = || rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // Palavra nova no dicionário: engine inteiro recarregado
+        std::fs::write(dir.join(DICTIONARY_FILE), "casa\ngato\n").unwrap();
+        match next() {
+            ReloadEvent::Reloaded { resources, report } => {
+                assert_eq!(resources, vec![Resource::Dictionary]);
+                assert_eq!(report.entries(Resource::Dictionary), 2);
+            }
+            other => panic!("esperava recarga: {}", other),
+        }
+        assert_eq!(shared.correct("gatu", 1), "gato");
+
+        // Arquivo de regras quebrado: rejeitado, as regras antigas continuam
+        std::fs::write(dir.join(TYPO_RULES_FILE), "xpto\tcasa\nsem tab\n").unwrap();
+        match next() {
+            ReloadEvent::Rejected { resources, error } => {
+                assert_eq!(resources, vec![Resource::TypoRules]);
+                assert!(error.message.starts_with("linha 2"), "{}", error);
+            }
+            other => panic!("esperava rejeição: {}", other),
+        }
+        assert_eq!(shared.correct("xpto", 1), "casa");
+        assert_eq!(shared.correct("gatu", 1), "gato");
+
+        // Corrigido: só o TypoModel é trocado
+        std::fs::write(dir.join(TYPO_RULES_FILE), "xpto\tgato\n").unwrap();
+        assert!(matches!(next(), ReloadEvent::Reloaded { .. }));
+        assert_eq!(shared.correct("xpto", 1), "gato");
+
+        // Lista de bloqueios nova: só ela é remontada, numa cópia do engine
+        // atual (a palavra do usuário e a camada desligada continuam)
+        shared.update(|e| e.add_user_word("xablau"));
+        std::fs::create_dir_all(dir.join(PACKS_DIR)).unwrap();
+        std::fs::write(dir.join(PACKS_DIR).join("pets.txt"), "petshop 10\n").unwrap();
+        assert!(matches!(next(), ReloadEvent::Reloaded { .. }));
+        assert!(shared.update(|e| e.set_layer_enabled("pets", true)));
+        std::fs::write(dir.join(BLOCKLIST_FILE), "gatu\tgato\n").unwrap();
+        match next() {
+            ReloadEvent::Reloaded { resources, report } => {
+                assert_eq!(resources, vec![Resource::Blocklist]);
+                let loaded: Vec<_> = report.resources.iter().map(|r| r.resource).collect();
+                assert_eq!(loaded, vec![Resource::Blocklist]);
+            }
+            other => panic!("esperava recarga: {}", other),
+        }
+        assert_eq!(shared.correct("gatu", 1), "gatu");
+        let snapshot = shared.snapshot();
+        assert!(snapshot.contains_word("xablau"));
+        assert!(snapshot.layer("pets").unwrap().is_enabled());
+
+        // Pacote alterado: a camada é trocada e continua ligada
+        std::fs::write(
+            dir.join(PACKS_DIR).join("pets.txt"),
+            "petshop 10\nracao 5\n",
+        )
+        .unwrap();
+        match next() {
+            ReloadEvent::Reloaded { resources, .. } => {
+                assert_eq!(resources, vec![Resource::Packs])
+            }
+            other => panic!("esperava recarga: {}", other),
+        }
+        let pets = shared.snapshot().layer("pets").unwrap().clone();
+        assert!(pets.is_enabled());
+        assert_eq!(pets.len(), 2);
+
+        // Pacote quebrado: rejeitado, a camada anterior continua
+        std::fs::write(dir.join(PACKS_DIR).join("pets.txt"), "").unwrap();
+        assert!(matches!(next(), ReloadEvent::Rejected { .. }));
+        assert_eq!(shared.snapshot().layer("pets").unwrap().len(), 2);
+        std::fs::write(dir.join(PACKS_DIR).join("pets.txt"), "petshop 10\n").unwrap();
+        assert!(matches!(next(), ReloadEvent::Reloaded { .. }));
+
+        // Lista quebrada: rejeitada, a anterior continua
+        std::fs::write(dir.join(BLOCKLIST_FILE), "a\tb\tc\n").unwrap();
+        assert!(matches!(next(), ReloadEvent::Rejected { .. }));
+        assert_eq!(shared.correct("gatu", 1), "gatu");
+        std::fs::write(dir.join(BLOCKLIST_FILE), "gatu\tgato\n").unwrap();
+        assert!(matches!(next(), ReloadEvent::Reloaded { .. }));
+
+        // Frequências vazias: na recarga completa também é erro, não aviso
+        std::fs::write(dir.join(FREQUENCY_FILE), "").unwrap();
+        match next() {
+            ReloadEvent::Rejected { resources, error } => {
+                assert_eq!(resources, vec![Resource::Frequency]);
+                assert_eq!(error.resource, Resource::Frequency);
+            }
+            other => panic!("esperava rejeição: {}", other),
+        }
+        assert_eq!(shared.correct("xpto", 1), "gato");
+        assert!(shared.snapshot().contains_word("xablau"));
+        std::fs::write(dir.join(FREQUENCY_FILE), "casa 900\n").unwrap();
+        assert!(matches!(next(), ReloadEvent::Reloaded { .. }));
+
+        // Dicionário e regras quebradas na mesma volta: nada é trocado
+        std::fs::write(dir.join(DICTIONARY_FILE), "casa\ngato\nmesa\n").unwrap();
+        std::fs::write(dir.join(TYPO_RULES_FILE), "sem tab\n").unwrap();
+        match next() {
+            ReloadEvent::Rejected { resources, error } => {
+                assert!(resources.contains(&Resource::Dictionary));
+                assert_eq!(error.resource, Resource::TypoRules);
+            }
+            other => panic!("esperava rejeição: {}", other),
+        }
+        assert_eq!(shared.correct("xpto", 1), "gato");
+        assert!(!shared.snapshot().contains_word("mesa"));
+
+        watcher.stop();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dictionary_layers() {
+        use crate::builder::{
+            EngineConfig, Resource, DICTIONARY_FILE, FREQUENCY_FILE, PACKS_DIR,
+            TEAM_DICTIONARY_FILE,
+        };
+        use crate::layers::LayerKind;
+        use crate::shared::SharedEngine;
+        use crate::trace::TraceEvent;
+        use crate::EngineBuilder;
+
+        let dir = std::env::temp_dir().join(format!("draco_layers_{}", std::process::id()));
+        let packs = dir.join(PACKS_DIR);
+        std::fs::create_dir_all(&packs).unwrap();
+        std::fs::write(dir.join(DICTIONARY_FILE), "casa\ncarro\n").unwrap();
+        // Frequências próprias para que os dados embutidos não entrem na base
+        std::fs::write(dir.join(FREQUENCY_FILE), "casa 10\n").unwrap();
+        std::fs::write(dir.join(TEAM_DICTIONARY_FILE), "draconiz\n").unwrap();
+        std::fs::write(
+            packs.join("juridico.txt"),
+            "# termos\nliminar 3000\ncarro\n",
+        )
+        .unwrap();
+        std::fs::write(packs.join("medico.txt"), "anamnese\n").unwrap();
+        std::fs::write(packs.join("leiame.md"), "não é pacote\n").unwrap();
+
+        let config = EngineConfig {
+            data_dir: Some(dir.clone()),
+            enabled_packs: vec!["juridico".into()],
+            ..EngineConfig::default()
+        };
+        let (engine, report) = EngineBuilder::from_config(config).build().unwrap();
+        assert_eq!(report.entries(Resource::Packs), 3);
+        assert_eq!(report.entries(Resource::TeamDictionary), 1);
+
+        // Ordem de prioridade: usuário > glossário > equipe > pacotes > norma > base
+        let order: Vec<_> = engine.layers().iter().map(|l| l.name()).collect();
+        assert_eq!(
+            order,
+            [
+                "usuario",
+                "glossario",
+                "equipe",
+                "juridico",
+                "medico",
+                "variante",
+                "base"
+            ]
+        );
+        assert_eq!(engine.layer("medico").unwrap().kind(), LayerKind::Pack);
+        assert!(!engine.layer("medico").unwrap().is_enabled());
+
+        // Pacote ligado corrige; o desligado não
+        assert_eq!(engine.correct("liminr", 1), "liminar");
+        assert_eq!(engine.correct("anamnes", 1), "anamnes");
+        assert_eq!(engine.correct("draconis", 1), "draconiz");
+
+        // O trace registra a camada de cada candidato
+        let trace = engine.explain("liminr", 1);
+        let fuzzy = trace.events.iter().find_map(|e| match e {
+            TraceEvent::Fuzzy { candidates, .. } => candidates.first(),
+            _ => None,
+        });
+        assert_eq!(fuzzy.unwrap().layer.as_deref(), Some("juridico"));
+        assert_eq!(fuzzy.unwrap().frequency, 3000);
+
+        // Palavra em duas camadas: vale a de maior prioridade
+        let layer_of = |engine: &StageA, word: &str| {
+            engine.explain(word, 1).events.iter().find_map(|e| match e {
+                TraceEvent::Dictionary { layer, .. } => layer.clone(),
+                _ => None,
+            })
+        };
+        assert_eq!(layer_of(&engine, "carro").as_deref(), Some("juridico"));
+
+        // Troca em tempo de execução, sem recarregar a base
+        let shared = SharedEngine::new(engine);
+        shared.update(|e| {
+            assert!(e.set_layer_enabled("medico", true));
+            assert!(e.set_layer_priority("juridico", -1));
+        });
+        assert_eq!(shared.correct("anamnes", 1), "anamnese");
+        assert_eq!(
+            layer_of(&shared.snapshot(), "carro").as_deref(),
+            Some("base")
+        );
+        assert!(!shared.update(|e| e.set_layer_enabled("inexistente", true)));
+
+        // Recarregar os dados mantém o que foi trocado em tempo de execução
+        let (reloaded, _) = EngineBuilder::new().data_dir(&dir).build().unwrap();
+        shared.replace(reloaded);
+        assert_eq!(shared.correct("anamnes", 1), "anamnese");
+        assert_eq!(shared.snapshot().layer("juridico").unwrap().priority(), -1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_team_glossary() {
+        use crate::builder::{Resource, DICTIONARY_FILE, FREQUENCY_FILE, GLOSSARY_DIR};
+        use crate::glossary::{parse_glossary, GlossaryEntry};
+        use crate::shared::SharedEngine;
+        use crate::stage_a::CorrectionStep;
+        use crate::EngineBuilder;
+
+        let dir = std::env::temp_dir().join(format!("draco_glossary_{}", std::process::id()));
+        let shared_dir = dir.join(GLOSSARY_DIR);
+        std::fs::create_dir_all(&shared_dir).unwrap();
+        std::fs::write(dir.join(DICTIONARY_FILE), "casa\nsobre\n").unwrap();
+        std::fs::write(dir.join(FREQUENCY_FILE), "casa 10\n").unwrap();
+        std::fs::write(
+            shared_dir.join("a.tsv"),
+            "# produto\nWhatsApp\twhatsap\tzap zap\ne-mail\temail\n\
+             Nota Fiscal Eletrônica\tnota fiscal eletronica\tnfe\n",
+        )
+        .unwrap();
+        std::fs::write(shared_dir.join("b.tsv"), "Email\tmail\n").unwrap();
+
+        let (engine, report) = EngineBuilder::new().data_dir(&dir).build().unwrap();
+        let glossary = report.get(Resource::Glossary).unwrap();
+        assert_eq!(glossary.entries, 4);
+        // b.tsv discorda de a.tsv sobre "email": a.tsv (carregado antes) vale
+        assert_eq!(glossary.warnings.len(), 1);
+        assert!(
+            glossary.warnings[0].contains("\"email\""),
+            "{:?}",
+            glossary.warnings
+        );
+        assert!(report.to_string().contains("b.tsv"));
+
+        // Grafia e caixa impostas pela forma canônica
+        assert_eq!(engine.correct("whatsapp", 1), "WhatsApp");
+        assert_eq!(engine.correct("WHATSAPP", 1), "WhatsApp");
+        assert_eq!(engine.correct("whatsap", 1), "WhatsApp");
+        assert_eq!(engine.correct("nfe", 1), "Nota Fiscal Eletrônica");
+        assert_eq!(engine.correct("mail", 1), "Email");
+        // Forma canônica em minúsculas: a caixa de quem digitou é mantida
+        assert_eq!(engine.correct("email", 1), "e-mail");
+        assert_eq!(engine.correct("Email", 1), "E-mail");
+        assert_eq!(engine.correct("EMAIL", 1), "E-MAIL");
+        // Erros fora da lista também chegam à forma canônica pela busca fuzzy
+        assert_eq!(engine.correct("whatsaap", 1), "WhatsApp");
+        assert_eq!(engine.explain("whatsapp", 1).step, CorrectionStep::Glossary);
+
+        // Texto corrido: expressões de várias palavras, a mais longa primeiro
+        assert_eq!(
+            engine
+                .glossary()
+                .apply("Mandei um email e um zap zap sobre a nota fiscal eletronica."),
+            "Mandei um e-mail e um WhatsApp sobre a Nota Fiscal Eletrônica."
+        );
+
+        // Termos incluídos em tempo de execução sobrevivem à recarga
+        let shared = SharedEngine::new(engine);
+        let conflicts = shared.update(|e| {
+            e.add_glossary_entries(&[GlossaryEntry {
+                canonical: "Draco".into(),
+                variants: vec!["drako".into(), "whatsapp".into()],
+            }])
+        });
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(shared.correct("drako", 1), "Draco");
+        let (reloaded, _) = EngineBuilder::new().data_dir(&dir).build().unwrap();
+        shared.replace(reloaded);
+        assert_eq!(shared.correct("drako", 1), "Draco");
+        assert_eq!(shared.correct("whatsapp", 1), "WhatsApp");
+
+        let error = parse_glossary("ok\tvariante\n\tsem forma\n").unwrap_err();
+        assert_eq!(error.line, 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_blocklist() {
+        use crate::blocklist::{parse_blocklist, BlockEntry, Blocklist};
+        use crate::builder::{Resource, BLOCKLIST_FILE, DICTIONARY_FILE, FREQUENCY_FILE};
+        use crate::stage_a::CorrectionStep;
+        use crate::trace::TraceEvent;
+        use crate::typo_model::{parse_rules, TypoModel};
+        use crate::variant::Variant;
+        use crate::EngineBuilder;
+
+        let mut engine = StageA::new();
+        engine.load_frequency_data(&[
+            ("casa".into(), 1000),
+            ("cama".into(), 800),
+            ("cas".into(), 1),
+        ]);
+        engine.load_dictionary(&["casa", "cama", "cas", "mesa"]);
+        let mut model = TypoModel::new();
+        model.add_rules(&parse_rules("kasa\tcasa\n").unwrap());
+        engine.set_typo_model(model);
+
+        // Sem bloqueios, cada entrada chega a "casa" por um caminho diferente
+        let cases = [
+            ("kasa", CorrectionStep::TypoModel),
+            ("cas", CorrectionStep::FrequencyUpgrade),
+            ("csaa", CorrectionStep::Transposition),
+            ("caza", CorrectionStep::Phonetic),
+            ("cada", CorrectionStep::FuzzyDistance1),
+            ("casaes", CorrectionStep::FuzzyDistance2),
+        ];
+        for (input, step) in cases {
+            assert_eq!(
+                engine.correct_with_step(input, 1),
+                ("casa".to_string(), step),
+                "{}",
+                input
+            );
+        }
+
+        // Palavra proibida: nenhum caminho a produz, e o trace diz onde caiu
+        let mut blocked = engine.clone();
+        blocked.set_blocklist(Blocklist::from_entries(&[BlockEntry::Word("casa".into())]));
+        for (input, step) in cases {
+            let trace = blocked.explain(input, 1);
+            assert_ne!(trace.output, "casa", "{}", input);
+            assert!(
+                trace.events.iter().any(|e| matches!(
+                    e,
+                    TraceEvent::Blocked { step: s, words } if *s == step.as_str() && words == &["casa"]
+                )),
+                "{}",
+                trace
+            );
+        }
+        // Digitada pelo usuário, a palavra fica
+        assert_eq!(blocked.correct("Casa", 1), "Casa");
+        // Bloquear não atrapalha as outras correções
+        assert_eq!(blocked.correct("cmaa", 1), "cama");
+
+        // Par proibido: só aquela correção some
+        let mut paired = engine.clone();
+        paired.set_blocklist(Blocklist::from_entries(&[BlockEntry::Pair {
+            from: "cada".into(),
+            to: "casa".into(),
+        }]));
+        assert_eq!(paired.correct("cada", 1), "cama");
+        assert_eq!(paired.correct("caza", 1), "casa");
+
+        // O bloqueio vale para a palavra emitida, mesmo quando ela só aparece
+        // ao atualizar a grafia do candidato (idéia → ideia, contato → contacto)
+        let mut updated = StageA::new();
+        updated.load_frequency_data(&[
+            ("idéia".into(), 900),
+            ("idéias".into(), 300),
+            ("contato".into(), 900),
+            ("contatos".into(), 300),
+        ]);
+        updated.load_dictionary(&["idéia", "idéias", "contato", "contatos"]);
+        updated.set_variant(Variant::PtPt);
+        assert_eq!(updated.correct("idéa", 1), "ideia");
+        assert_eq!(updated.correct("contatu", 1), "contacto");
+        updated.set_blocklist(Blocklist::from_entries(&[
+            BlockEntry::Word("ideia".into()),
+            BlockEntry::Pair {
+                from: "contatu".into(),
+                to: "contacto".into(),
+            },
+        ]));
+        assert_ne!(updated.correct("idéa", 1), "ideia");
+        assert_ne!(updated.correct("contatu", 1), "contacto");
+        for (input, target) in [("idéa", "ideia"), ("contatu", "contacto")] {
+            let candidates = updated.candidates(input, 1, 10);
+            assert!(
+                candidates.iter().all(|c| c.word != target),
+                "{}: {:?}",
+                input,
+                candidates
+            );
+        }
+
+        // Arquivo de dados, recarregável como os demais
+        let dir = std::env::temp_dir().join(format!("draco_blocklist_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(DICTIONARY_FILE), "casa\ncama\n").unwrap();
+        std::fs::write(dir.join(FREQUENCY_FILE), "casa 1000\ncama 800\n").unwrap();
+        std::fs::write(dir.join(BLOCKLIST_FILE), "# proibidas\nCASA\ncmaa\tcama\n").unwrap();
+        let (built, report) = EngineBuilder::new().data_dir(&dir).build().unwrap();
+        assert_eq!(report.entries(Resource::Blocklist), 2);
+        assert_eq!(built.blocklist().len(), 2);
+        assert_ne!(built.correct("cssa", 1), "casa");
+        assert_ne!(built.correct("cmaa", 1), "cama");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(parse_blocklist("a\tb\tc\n").unwrap_err().line, 1);
+        assert_eq!(parse_blocklist("ok\n\nx\tx\n").unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn test_orthographic_variant() {
+        use crate::builder::{Resource, DICTIONARY_FILE, FREQUENCY_FILE, VARIANTS_DIR};
+        use crate::stage_a::CorrectionStep;
+        use crate::variant::{convert, convert_word, Variant};
+        use crate::EngineBuilder;
+
+        let dir = std::env::temp_dir().join(format!("draco_variant_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join(VARIANTS_DIR)).unwrap();
+        std::fs::write(
+            dir.join(DICTIONARY_FILE),
+            "contato\neconômico\nfato\nação\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join(FREQUENCY_FILE),
+            "contato 500\neconômico 300\nfato 400\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join(VARIANTS_DIR).join("pt_pt.txt"), "telemóvel\n").unwrap();
+
+        // Brasil (padrão): grafias europeias viram as brasileiras
+        let (br, report) = EngineBuilder::new().data_dir(&dir).build().unwrap();
+        assert_eq!(br.variant(), Variant::PtBr);
+        assert_eq!(report.entries(Resource::Variants), 0);
+        assert_eq!(br.correct("contato", 1), "contato");
+        assert_eq!(
+            br.correct_with_step("Contacto", 1),
+            ("Contato".to_string(), CorrectionStep::Variant)
+        );
+
+        // Portugal: as grafias europeias valem e as brasileiras são convertidas
+        let (pt, report) = EngineBuilder::new()
+            .data_dir(&dir)
+            .variant(Variant::PtPt)
+            .build()
+            .unwrap();
+        assert_eq!(report.entries(Resource::Variants), 1);
+        for word in ["contacto", "facto", "acção", "ação", "ecrã", "fato"] {
+            assert_eq!(pt.correct(word, 1), word);
+        }
+        assert_eq!(
+            pt.correct_with_step("Contato", 1),
+            ("Contacto".to_string(), CorrectionStep::Variant)
+        );
+        // Candidatos achados na base brasileira saem na grafia europeia
+        assert_eq!(pt.correct("economico", 1), "económico");
+        assert_eq!(pt.correct("telemovel", 1), "telemóvel");
+        assert!(pt.explain("contato", 1).to_string().contains("pt_pt"));
+
+        // Conversor de texto corrido, nos dois sentidos
+        assert_eq!(
+            convert("O Contato econômico, por favor.", Variant::PtPt),
+            "O Contacto económico, por favor."
+        );
+        assert_eq!(
+            convert("A acção actual do CONTACTO", Variant::PtBr),
+            "A ação atual do CONTATO"
+        );
+
+        // Formas flexionadas, pelas famílias de radical e de terminação
+        for (br, pt) in [
+            ("registrado", "registado"),
+            ("registraram", "registaram"),
+            ("contatou", "contactou"),
+            ("contatando", "contactando"),
+            ("recepções", "receções"),
+            ("perceptível", "percetível"),
+            ("agronômicas", "agronómicas"),
+            ("patrimônios", "patrimónios"),
+            ("termômetros", "termómetros"),
+        ] {
+            assert_eq!(
+                convert_word(br, Variant::PtPt).as_deref(),
+                Some(pt),
+                "{}",
+                br
+            );
+            assert_eq!(
+                convert_word(pt, Variant::PtBr).as_deref(),
+                Some(br),
+                "{}",
+                pt
+            );
+        }
+        assert_eq!(
+            convert_word("actualizados", Variant::PtBr).as_deref(),
+            Some("atualizados")
+        );
+        assert_eq!(
+            convert_word("receções", Variant::PtBr).as_deref(),
+            Some("recepções")
+        );
+        // Só no começo da palavra (seção existe nas duas normas), e palavras
+        // comuns às duas ficam
+        for word in ["seção", "facção", "registrador", "contatoz", "tônico"] {
+            assert_eq!(convert_word(word, Variant::PtBr), None, "{}", word);
+        }
+        assert_eq!(convert_word("seção", Variant::PtPt), None);
+        assert_eq!(convert_word("ficção", Variant::PtPt), None);
+
+        // No engine: flexões da base saem na grafia europeia, e as europeias
+        // digitadas são aceitas
+        let mut inflected = StageA::new();
+        inflected.load_frequency_data(&[("registrado".into(), 500), ("recepções".into(), 200)]);
+        inflected.load_dictionary(&["registrado", "recepções"]);
+        inflected.set_variant(Variant::PtPt);
+        assert_eq!(
+            inflected.correct_with_step("Registrado", 1),
+            ("Registado".to_string(), CorrectionStep::Variant)
+        );
+        assert_eq!(inflected.correct("registadoo", 1), "registado");
+        for word in ["registado", "receções"] {
+            assert_eq!(
+                inflected.correct_with_step(word, 1),
+                (word.to_string(), CorrectionStep::Dictionary)
+            );
+        }
+        inflected.set_variant(Variant::PtBr);
+        assert_eq!(inflected.correct("receções", 1), "recepções");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_orthographic_agreement() {
+        use crate::orthography::{modernize, OrthographyRule};
+        use crate::stage_a::CorrectionStep;
+        use crate::trace::TraceEvent;
+
+        let cases = [
+            ("lingüiça", "linguiça", OrthographyRule::Diaeresis),
+            ("cinqüenta", "cinquenta", OrthographyRule::Diaeresis),
+            ("idéia", "ideia", OrthographyRule::OpenDiphthong),
+            ("heróicos", "heroicos", OrthographyRule::OpenDiphthong),
+            ("apóiam", "apoiam", OrthographyRule::OpenDiphthong),
+            ("vôo", "voo", OrthographyRule::Hiatus),
+            ("crêem", "creem", OrthographyRule::Hiatus),
+            ("feiúra", "feiura", OrthographyRule::StressedVowel),
+            ("averigúe", "averigue", OrthographyRule::StressedVowel),
+            ("pára", "para", OrthographyRule::DifferentialAccent),
+            ("pêlo", "pelo", OrthographyRule::DifferentialAccent),
+            ("auto-escola", "autoescola", OrthographyRule::PrefixHyphen),
+            ("anti-social", "antissocial", OrthographyRule::PrefixHyphen),
+            (
+                "contra-regra",
+                "contrarregra",
+                OrthographyRule::PrefixHyphen,
+            ),
+            (
+                "antiinflamatório",
+                "anti-inflamatório",
+                OrthographyRule::PrefixHyphen,
+            ),
+            ("microondas", "micro-ondas", OrthographyRule::PrefixHyphen),
+        ];
+        for (old, modern, rule) in cases {
+            let m = modernize(old).unwrap_or_else(|| panic!("{}", old));
+            assert_eq!((m.word.as_str(), m.rules.as_slice()), (modern, &[rule][..]));
+        }
+        // Já na grafia atual, ou com acento que o Acordo manteve
+        for word in [
+            "ideia",
+            "papéis",
+            "herói",
+            "destróier",
+            "maiúsculo",
+            "piauí",
+            "pôde",
+            "saída",
+            "müller",
+            "anti-inflamatório",
+            "micro-ondas",
+            "anti-higiênico",
+            "antiético",
+            "anteontem",
+            "casa",
+        ] {
+            assert_eq!(modernize(word), None, "{}", word);
+        }
+        // Mais de uma regra na mesma palavra
+        let m = modernize("anti-heróico").unwrap();
+        assert_eq!(m.word, "anti-heroico");
+
+        // No pipeline: etapa própria, antes do dicionário (as formas antigas
+        // ainda aparecem nas frequências) e com as regras no trace
+        let mut engine = StageA::new();
+        engine.load_frequency_data(&[("idéia".into(), 900), ("ideia".into(), 1000)]);
+        engine.load_dictionary(&["ideia", "ondas", "escola", "autoescola"]);
+        assert_eq!(
+            engine.correct_with_step("Idéia", 1),
+            ("Ideia".to_string(), CorrectionStep::Orthography)
+        );
+        assert_eq!(engine.correct("AUTO-ESCOLA", 1), "AUTOESCOLA");
+        let trace = engine.explain("vôo", 1);
+        assert_eq!(trace.output, "voo");
+        assert!(trace.events.contains(&TraceEvent::Orthography {
+            rules: vec![OrthographyRule::Hiatus],
+            hit: "voo".into(),
+        }));
+        assert!(trace.to_string().contains("hiatus"));
+        // Composto com hífen obrigatório é aceito pela segunda parte
+        assert_eq!(
+            engine.correct_with_step("micro-ondas", 1),
+            ("micro-ondas".to_string(), CorrectionStep::Dictionary)
+        );
+        // Candidato antigo achado pela busca fuzzy sai atualizado
+        assert_eq!(engine.correct("idéai", 1), "ideia");
+    }
+
+    #[test]
+    fn test_grammar_rules() {
+        use crate::builder::{Resource, GRAMMAR_DIR};
+        use crate::grammar::GrammarEngine;
+        use crate::EngineBuilder;
+
+        let grammar = GrammarEngine::builtin();
+        assert!(!grammar.is_empty());
+
+        let fixes = [
+            // Crase
+            ("Começou à chover.", "Começou a chover."),
+            ("Entreguei o livro à ela.", "Entreguei o livro a ela."),
+            ("Fomos à pé.", "Fomos a pé."),
+            ("A reunião é as 10h.", "A reunião é às 10h."),
+            ("Chego as 8 horas.", "Chego às 8 horas."),
+            ("As vezes eu esqueço.", "Às vezes eu esqueço."),
+            (
+                "Ficou, a medida que o tempo passou, mais calmo.",
+                "Ficou, à medida que o tempo passou, mais calmo.",
+            ),
+            ("Andava a toa.", "Andava à toa."),
+            ("Saíram as pressas.", "Saíram às pressas."),
+            // Há / a
+            (
+                "Isso foi a dois anos atrás.",
+                "Isso foi há dois anos atrás.",
+            ),
+            ("Não o vejo a muito tempo.", "Não o vejo há muito tempo."),
+            ("Moro aqui à anos.", "Moro aqui há anos."),
+            ("Volto daqui há pouco.", "Volto daqui a pouco."),
+            // Mas / mais
+            ("Quis ir, mais não pude.", "Quis ir, mas não pude."),
+            ("Está mas ou menos.", "Está mais ou menos."),
+            ("Ele corre mas do que eu.", "Ele corre mais do que eu."),
+            ("Vieram mas de 10 pessoas.", "Vieram mais de 10 pessoas."),
+            ("É o mas bonito.", "É o mais bonito."),
+            // Mal / mau
+            (
+                "O carro estava mau conservado.",
+                "O carro estava mal conservado.",
+            ),
+            ("Dormi mau.", "Dormi mal."),
+            ("Acordou de mal humor.", "Acordou de mau humor."),
+            ("Foi de mau a pior.", "Foi de mal a pior."),
+            // Onde / aonde
+            ("Onde você vai?", "Aonde você vai?"),
+            ("Aonde você mora?", "Onde você mora?"),
+            // Porque / por que / porquê / por quê
+            ("Porque você saiu?", "Por que você saiu?"),
+            ("Você saiu porque?", "Você saiu por quê?"),
+            ("Não sei o porque disso.", "Não sei o porquê disso."),
+            (
+                "Explique o por que da demora.",
+                "Explique o porquê da demora.",
+            ),
+            ("Saí porquê estava cansado.", "Saí porque estava cansado."),
+            ("Não sei por quê ele saiu.", "Não sei por que ele saiu."),
+        ];
+        for (input, expected) in fixes {
+            assert_eq!(grammar.apply(input), expected, "{}", input);
+        }
+
+        // Frases corretas (e armadilhas parecidas) ficam intactas
+        let correct = [
+            "Entreguei à mulher o pacote.",
+            "Fui à praia.",
+            "Ficou aberto desde as 8h.",
+            "Conto as vezes que ele veio.",
+            "Tomaram a medida que faltava.",
+            "Isso aconteceu há dois anos.",
+            "Daqui a pouco tempo chegamos.",
+            "Chegou a pouco tempo do fim.",
+            "Quis ir, mas não pude.",
+            "Gostei muito, mas não comprei.",
+            "Tentei uma vez mas não deu.",
+            "Ele é um mau aluno.",
+            "Ele é mau soldado.",
+            "Ele se sente mal.",
+            "Onde você mora?",
+            "Onde vamos jantar?",
+            "Aonde você vai?",
+            "Por que você saiu?",
+            "Saí porque estava cansado.",
+            "Não sei o porquê.",
+            "Não sei por quê.",
+        ];
+        for text in correct {
+            assert_eq!(grammar.check(text), Vec::new(), "{}", text);
+        }
+
+        // Posições em bytes, caixa preservada e a mensagem da regra
+        let found = grammar.check("Vou À pé às vezes.");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].rule, "crase_masculino");
+        assert_eq!((found[0].start, found[0].end), (4, 6));
+        assert_eq!(found[0].original, "À");
+        assert_eq!(found[0].suggestion, "A");
+        assert!(!found[0].message.is_empty());
+
+        // Regras próprias: lemas, etiquetas, regex e erros com número da linha
+        let mut custom = GrammarEngine::new();
+        let added = custom
+            .add_rules(
+                "lemma\tfazer\tfaz fez\n\
+                 tag\tTEMPO\tanos dias\n\
+                 rule\tfazem\t{fazem} <TEMPO>\tfaz\tVerbo impessoal.\n\
+                 rule\tdata\t/\\d+/\\d+/ {de}\tem\tTeste de regex.\n",
+            )
+            .unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(custom.apply("Fazem dois anos."), "Fazem dois anos.");
+        assert_eq!(custom.apply("Fazem anos."), "Faz anos.");
+        let err = custom
+            .add_rules("# ok\nrule\tx\t{a} <NADA>\tb\tEtiqueta inexistente.\n")
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(custom.add_rules("rule\tx\t{a\tb\tChave aberta.\n").is_err());
+        assert!(custom.add_rules("regra\tx\n").is_err());
+        // Um arquivo com erro não muda nada
+        assert_eq!(custom.len(), 2);
+
+        // Pelo builder: embutidas mais os arquivos do diretório de gramática
+        let dir = std::env::temp_dir().join(format!("draco_grammar_{}", std::process::id()));
+        let rules_dir = dir.join(GRAMMAR_DIR);
+        std::fs::create_dir_all(&rules_dir).unwrap();
+        std::fs::write(
+            rules_dir.join("equipe.tsv"),
+            "rule\tsprint\t{sprintes}\tsprints\tPlural em inglês.\n",
+        )
+        .unwrap();
+        let (built, report) = EngineBuilder::new().data_dir(&dir).build_grammar().unwrap();
+        assert_eq!((report.resource, report.entries), (Resource::Grammar, 1));
+        assert_eq!(built.len(), grammar.len() + 1);
+        assert_eq!(built.apply("Dois sprintes."), "Dois sprints.");
+        std::fs::write(rules_dir.join("quebrado.tsv"), "rule\tx\n").unwrap();
+        let err = EngineBuilder::new()
+            .data_dir(&dir)
+            .build_grammar()
+            .unwrap_err();
+        assert_eq!(err.resource, Resource::Grammar);
+        assert!(err.message.contains("quebrado.tsv"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_edit_spans() {
+        use crate::edit::{apply_edits, diff, Edit, EditRange, EditSource};
+        use crate::grammar::GrammarEngine;
+
+        // Trechos em bytes, caracteres e UTF-16 (o emoji ocupa 4 bytes e 2 unidades)
+        let text = "🐉 ação à chover";
+        let range = EditRange::from_bytes(text, 12..14);
+        assert_eq!(&text[range.bytes.clone()], "à");
+        assert_eq!(range.chars, 7..8);
+        assert_eq!(range.utf16, 8..9);
+
+        // Regras viram edições com id, mensagem e confiança
+        let grammar = GrammarEngine::builtin();
+        let edits: Vec<Edit> = grammar
+            .check(text)
+            .iter()
+            .map(|m| Edit::from_grammar(text, m))
+            .collect();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range, range);
+        assert_eq!(edits[0].source, EditSource::Rule);
+        assert_eq!(edits[0].id, "crase_infinitivo");
+        assert_eq!(edits[0].replacement, "a");
+        assert!(!edits[0].message.is_empty());
+        assert!(edits[0].confidence > 0.0 && edits[0].confidence <= 1.0);
+        assert_eq!(apply_edits(text, &edits), grammar.apply(text));
+
+        // Diferença por palavras: trocas vizinhas se juntam, o resto fica
+        let original = "Eu vai na escola ontem, e gostei muito.";
+        let rewritten = "Eu fui à escola ontem e gostei muito.";
+        let spans: Vec<_> = diff(original, rewritten)
+            .into_iter()
+            .map(|(range, to)| (original[range].to_string(), to))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("vai na".to_string(), "fui à".to_string()),
+                (",".to_string(), String::new()),
+            ]
+        );
+
+        // Inserção, junção de palavras e espaços que não contam
+        assert_eq!(
+            diff("ele foi", "ele não foi"),
+            vec![(4..4, "não ".to_string())]
+        );
+        assert_eq!(
+            diff("a gente", "agente"),
+            vec![(0..7, "agente".to_string())]
+        );
+        assert_eq!(diff("  casa  amarela ", "casa amarela"), Vec::new());
+        assert_eq!(diff("igual", "igual"), Vec::new());
+
+        // Reescrita do modelo: cada edição se aplica sozinha
+        let edits = Edit::from_rewrite(original, rewritten, "stage_c", 0.5);
+        assert_eq!(edits.len(), 2);
+        assert!(edits
+            .iter()
+            .all(|e| e.source == EditSource::Model && e.id == "stage_c"));
+        assert_eq!(
+            edits[1].apply(original),
+            "Eu vai na escola ontem e gostei muito."
+        );
+        assert_eq!(apply_edits(original, &edits), rewritten);
+        assert_eq!(edits[1].range.chars, 22..23);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stage_b_debounce() {
+        use crate::shared::SharedEngine;
+        use crate::stage_b::{StageB, StageBOptions, StageBResponse, DEFAULT_DEBOUNCE};
+        use std::sync::Arc;
+        use std::time::Duration;
+        use tokio::sync::mpsc;
+        use tokio::time::{sleep, timeout};
+
+        // Modelo de mentira: avisa quando começa cada texto e só o termina
+        // quando liberado
+        let fake_model = || {
+            let (started_tx, started_rx) = mpsc::unbounded_channel::<String>();
+            let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+            let model: crate::stage_b::Model = Box::new(move |texts: &[&str]| {
+                texts
+                    .iter()
+                    .map(|text| {
+                        let _ = started_tx.send(text.to_string());
+                        release_rx.recv().ok()?;
+                        Some(crate::stage_c::Rewrite::new(text.to_uppercase()))
+                    })
+                    .collect()
+            });
+            (model, started_rx, release_tx)
+        };
+        // O filtro do modelo confere as palavras no dicionário
+        let mut dictionary = StageA::new();
+        dictionary.load_dictionary(&[
+            "outro", "texto", "segundo", "contexto", "um", "novo", "dois", "três",
+        ]);
+        let dictionary = Arc::new(SharedEngine::new(dictionary));
+        let stage_b = |model| {
+            let (tx, rx) = mpsc::channel::<StageBResponse>(10);
+            let options = StageBOptions::default();
+            let engine = Arc::clone(&dictionary);
+            (StageB::with_model(tx, options, engine, model), rx)
+        };
+        // Espera o worker (e as inferências, em threads próprias) chegarem lá
+        async fn until(stage_b: &StageB, done: impl Fn(&crate::stage_b::StageBMetrics) -> bool) {
+            for _ in 0..5000 {
+                if done(&stage_b.metrics()) {
+                    return;
+                }
+                tokio::task::yield_now().await;
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            panic!("métricas não chegaram: {:?}", stage_b.metrics());
+        }
+
+        // Rajada de teclas: só o último texto de cada contexto é corrigido
+        let (model, _started, release) = fake_model();
+        let (b, mut responses) = stage_b(model);
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        for text in ["c", "co", "com", "come", "começou à chover"] {
+            b.request_correction(text.to_string(), 1).await.unwrap();
+            sleep(Duration::from_millis(30)).await;
+        }
+        b.request_correction("outro texto".to_string(), 2)
+            .await
+            .unwrap();
+        // Nada sai antes do debounce do último pedido
+        assert!(timeout(DEFAULT_DEBOUNCE / 2, responses.recv())
+            .await
+            .is_err());
+        let mut got = [
+            responses.recv().await.unwrap(),
+            responses.recv().await.unwrap(),
+        ];
+        got.sort_by_key(|r| r.context_id);
+        assert_eq!(got[0].original, "começou à chover");
+        assert_eq!(got[0].corrected, "começou a chover");
+        assert_eq!(got[0].edits[0].id, "crase_infinitivo");
+        assert_eq!(got[1].corrected, "OUTRO TEXTO");
+        until(&b, |m| m.processed == 2).await;
+        let metrics = b.metrics();
+        assert_eq!(metrics.received, 6);
+        assert_eq!(metrics.superseded, 4);
+        assert_eq!(metrics.model_runs, 1);
+        assert_eq!(metrics.max_pending, 2);
+        assert_eq!((metrics.pending, metrics.queued), (0, 0));
+
+        // Texto novo durante a inferência: o resultado velho é descartado
+        let (model, mut started, release) = fake_model();
+        let (b, mut responses) = stage_b(model);
+        b.request_correction("primeiro".to_string(), 1)
+            .await
+            .unwrap();
+        assert_eq!(started.recv().await.unwrap(), "primeiro");
+        b.request_correction("segundo".to_string(), 1)
+            .await
+            .unwrap();
+        until(&b, |m| m.received == 2).await;
+        release.send(()).unwrap();
+        until(&b, |m| m.stale_dropped == 1).await;
+        release.send(()).unwrap();
+        let response = responses.recv().await.unwrap();
+        assert_eq!(response.corrected, "SEGUNDO");
+        assert_eq!(b.metrics().model_runs, 2);
+
+        // Pedido esperando o modelo (ocupado com outro contexto) e
+        // substituído nesse meio-tempo: a inferência é pulada
+        let (model, mut started, release) = fake_model();
+        let (b, mut responses) = stage_b(model);
+        b.request_correction("contexto um".to_string(), 1)
+            .await
+            .unwrap();
+        assert_eq!(started.recv().await.unwrap(), "contexto um");
+        b.request_correction("velho".to_string(), 2).await.unwrap();
+        until(&b, |m| m.received == 2).await;
+        // O relógio parado não anda sozinho com a inferência em curso
+        tokio::time::advance(DEFAULT_DEBOUNCE * 2).await;
+        until(&b, |m| m.pending == 0).await;
+        b.request_correction("novo".to_string(), 2).await.unwrap();
+        until(&b, |m| m.received == 3).await;
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        let mut got = [
+            responses.recv().await.unwrap(),
+            responses.recv().await.unwrap(),
+        ];
+        got.sort_by_key(|r| r.context_id);
+        assert_eq!(got[0].corrected, "CONTEXTO UM");
+        assert_eq!(got[1].corrected, "NOVO");
+        until(&b, |m| m.model_skipped == 1).await;
+        let metrics = b.metrics();
+        assert_eq!((metrics.model_runs, metrics.processed), (2, 2));
+        assert_eq!(metrics.superseded, 0);
+
+        // Pedidos que chegam com o modelo ocupado vão juntos no lote seguinte
+        let (model, mut started, release) = fake_model();
+        let (b, mut responses) = stage_b(model);
+        b.request_correction("um".to_string(), 1).await.unwrap();
+        assert_eq!(started.recv().await.unwrap(), "um");
+        b.request_correction("dois".to_string(), 2).await.unwrap();
+        b.request_correction("três".to_string(), 3).await.unwrap();
+        until(&b, |m| m.received == 3).await;
+        tokio::time::advance(DEFAULT_DEBOUNCE * 2).await;
+        until(&b, |m| m.model_waiting == 2).await;
+        for _ in 0..3 {
+            release.send(()).unwrap();
+        }
+        let mut got = Vec::new();
+        for _ in 0..3 {
+            got.push(responses.recv().await.unwrap().corrected);
+        }
+        got.sort();
+        assert_eq!(got, ["DOIS", "TRÊS", "UM"]);
+        let metrics = b.metrics();
+        assert_eq!((metrics.model_runs, metrics.model_batches), (3, 2));
+        assert_eq!(metrics.model_waiting, 0);
+        // Sem Stage C de verdade, sem cache
+        assert_eq!(metrics.cache, crate::cache::CacheStats::default());
+
+        // Modelo que não carrega: o Stage B guarda o erro e segue com as regras
+        let (tx, _rx) = mpsc::channel::<StageBResponse>(10);
+        let missing = std::env::temp_dir().join(format!("draco_sem_modelo_{}", std::process::id()));
+        let b = StageB::new(
+            tx,
+            Some(missing.display().to_string()),
+            Arc::clone(&dictionary),
+        );
+        assert!(matches!(
+            b.model_error(),
+            Some(crate::stage_c::StageCError::NoModel(_))
+        ));
+
+        // Sem quem leia as respostas, a thread do modelo termina: o pedido
+        // seguinte é contado como perdido, e não como à espera
+        let (model, mut started, release) = fake_model();
+        let (b, responses) = stage_b(model);
+        drop(responses);
+        b.request_correction("um".to_string(), 1).await.unwrap();
+        assert_eq!(started.recv().await.unwrap(), "um");
+        release.send(()).unwrap();
+        // O modelo é largado junto com a thread
+        assert!(started.recv().await.is_none());
+        b.request_correction("dois".to_string(), 2).await.unwrap();
+        until(&b, |m| m.received == 2).await;
+        tokio::time::advance(DEFAULT_DEBOUNCE * 2).await;
+        until(&b, |m| m.model_dropped == 1).await;
+        assert_eq!(b.metrics().model_waiting, 0);
+    }
+
+    #[test]
+    fn test_seq2seq_decoding() {
+        use crate::seq2seq::{decode, decode_batch, DecodeOptions, DecodeStrategy};
+        use crate::stage_c::{detect_model_kind, ModelKind};
+
+        // Vocabulário de 4 tokens, 0 inicia e 1 encerra. O caminho guloso
+        // (2, 2) perde para o (3) no beam
+        let probs = |seq: &[u32]| -> [f32; 4] {
+            match seq {
+                [0] => [0.0, 0.1, 0.5, 0.4],
+                [0, 2] => [0.0, 0.3, 0.36, 0.34],
+                [0, 3] => [0.0, 0.9, 0.05, 0.05],
+                _ => [0.0, 0.99, 0.005, 0.005],
+            }
+        };
+        let run = |options: &DecodeOptions| {
+            let mut previous: Vec<Vec<u32>> = Vec::new();
+            decode(options, |sequences, parents| {
+                // Cada hipótese continua a que `parents` aponta
+                for (seq, &parent) in sequences.iter().zip(parents) {
+                    if !previous.is_empty() {
+                        assert_eq!(seq[..seq.len() - 1], previous[parent][..]);
+                    }
+                }
+                previous = sequences.to_vec();
+                Ok::<_, ()>(
+                    sequences
+                        .iter()
+                        .map(|seq| probs(seq).iter().map(|p| p.ln()).collect())
+                        .collect(),
+                )
+            })
+            .unwrap()
+        };
+        let greedy = DecodeOptions::default();
+        assert_eq!(run(&greedy), [2, 2]);
+        let beam = DecodeOptions {
+            strategy: DecodeStrategy::Beam { width: 2 },
+            ..DecodeOptions::default()
+        };
+        assert_eq!(run(&beam), [3]);
+
+        // Vários textos juntos: cada um sai como se fosse sozinho, e cada
+        // linha continua a hipótese do mesmo texto na chamada anterior
+        let logits = |text: usize, seq: &[u32]| -> Vec<f32> {
+            let probs = match text {
+                0 => probs(seq),
+                1 if seq.len() < 3 => [0.0, 0.01, 0.09, 0.9],
+                _ => [0.0, 0.99, 0.005, 0.005],
+            };
+            probs.iter().map(|p| p.ln()).collect()
+        };
+        for options in [&greedy, &beam] {
+            let mut previous: Vec<(usize, Vec<u32>)> = Vec::new();
+            let batch = decode_batch(options, 3, |owners, sequences, parents| {
+                for ((&text, seq), &parent) in owners.iter().zip(sequences).zip(parents) {
+                    if !previous.is_empty() {
+                        assert_eq!(previous[parent].0, text);
+                        assert_eq!(seq[..seq.len() - 1], previous[parent].1[..]);
+                    }
+                }
+                previous = owners.iter().copied().zip(sequences.to_vec()).collect();
+                Ok::<_, ()>(
+                    owners
+                        .iter()
+                        .zip(sequences)
+                        .map(|(&text, seq)| logits(text, seq))
+                        .collect(),
+                )
+            })
+            .unwrap();
+            let tokens: Vec<Vec<u32>> = batch
+                .iter()
+                .map(|scored| scored.iter().map(|&(token, _)| token).collect())
+                .collect();
+            for (text, tokens) in tokens.iter().enumerate() {
+                let alone = decode(options, |sequences, _| {
+                    Ok::<_, ()>(sequences.iter().map(|seq| logits(text, seq)).collect())
+                })
+                .unwrap();
+                assert_eq!(*tokens, alone, "texto {}", text);
+            }
+            assert_eq!(tokens[1], [3, 3]);
+            assert!(tokens[2].is_empty());
+        }
+
+        // Sem fim de frase, para no limite de tamanho
+        let options = DecodeOptions {
+            max_length: 3,
+            ..beam
+        };
+        let tokens = decode(&options, |sequences, _| {
+            Ok::<_, ()>(vec![vec![0.0, -9.0, 1.0, 0.5]; sequences.len()])
+        });
+        assert_eq!(tokens.unwrap().len(), 3);
+
+        // Tipo de modelo e configuração de geração pelo diretório
+        let dir = std::env::temp_dir().join(format!("draco_seq2seq_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(detect_model_kind(&dir), None);
+        std::fs::write(dir.join("model.onnx"), "").unwrap();
+        assert_eq!(
+            detect_model_kind(&dir),
+            Some(ModelKind::Tagger(dir.join("model.onnx")))
+        );
+        std::fs::write(dir.join("encoder_model.onnx"), "").unwrap();
+        assert!(matches!(
+            detect_model_kind(&dir),
+            Some(ModelKind::Tagger(_))
+        ));
+        std::fs::write(dir.join("decoder_model.onnx"), "").unwrap();
+        std::fs::write(dir.join("decoder_model_merged.onnx"), "").unwrap();
+        assert_eq!(
+            detect_model_kind(&dir),
+            Some(ModelKind::Seq2Seq {
+                encoder: dir.join("encoder_model.onnx"),
+                decoder: dir.join("decoder_model_merged.onnx"),
+            })
+        );
+
+        assert_eq!(
+            DecodeOptions::from_model_dir(&dir),
+            DecodeOptions::default()
+        );
+        std::fs::write(
+            dir.join("config.json"),
+            r#"{"decoder_start_token_id": 2, "eos_token_id": 5, "max_length": 64}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("generation_config.json"),
+            r#"{"eos_token_id": [3, 5], "num_beams": 4}"#,
+        )
+        .unwrap();
+        let options = DecodeOptions::from_model_dir(&dir);
+        assert_eq!(options.decoder_start_token_id, 2);
+        assert_eq!(options.eos_token_id, 3);
+        assert_eq!(options.max_length, 64);
+        assert_eq!(options.strategy, DecodeStrategy::Beam { width: 4 });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_model_manifest() {
+        use crate::manifest::{ModelManifest, ModelTask};
+        use crate::seq2seq::DecodeStrategy;
+        use crate::stage_c::{StageC, StageCError};
+
+        // Só a tarefa é obrigatória
+        let manifest = ModelManifest::parse(r#"{"task": "tagger"}"#).unwrap();
+        assert_eq!(manifest, ModelManifest::new(ModelTask::Tagger));
+        assert_eq!(manifest.inputs.input_ids, "input_ids");
+        assert_eq!(manifest.threads, 1);
+
+        let manifest = ModelManifest::parse(
+            r#"{
+                "task": "seq2seq",
+                "version": "t5-small-2025.03",
+                "files": {"decoder": "decoder_model.onnx"},
+                "inputs": {"input_ids": "ids", "attention_mask": null},
+                "outputs": {"logits": "scores"},
+                "max_sequence_length": 64,
+                "special_tokens": {"eos": 7},
+                "strategy": {"beam": {"width": 3}},
+                "threads": 2
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.files.encoder, "encoder_model.onnx");
+        assert_eq!(manifest.files.decoder, "decoder_model.onnx");
+        assert_eq!(manifest.inputs.attention_mask, None);
+        assert_eq!(manifest.outputs.logits.as_deref(), Some("scores"));
+
+        for bad in [
+            r#"{"format": 2, "task": "tagger"}"#,
+            r#"{"task": "tagger", "threads": 0}"#,
+            r#"{"task": "classifier"}"#,
+            r#"{"version": "1.0"}"#,
+        ] {
+            assert!(ModelManifest::parse(bad).is_err(), "{}", bad);
+        }
+
+        let dir = std::env::temp_dir().join(format!("draco_manifest_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut stage_c = StageC::new();
+        assert_eq!(
+            stage_c.init_from_dir(&dir),
+            Err(StageCError::NoModel(dir.clone()))
+        );
+
+        // Sem manifesto, o tipo vem dos arquivos
+        std::fs::write(dir.join("model.onnx"), "").unwrap();
+        let inferred = ModelManifest::from_dir(&dir).unwrap();
+        assert_eq!(inferred.task, ModelTask::Tagger);
+        assert_eq!(
+            stage_c.init_from_dir(&dir),
+            Err(StageCError::MissingFile(dir.join("tokenizer.json")))
+        );
+        std::fs::write(dir.join("tokenizer.json"), "{}").unwrap();
+        assert!(matches!(
+            stage_c.init_from_dir(&dir),
+            Err(StageCError::Tokenizer { .. })
+        ));
+
+        // O manifesto manda nos arquivos e nos tokens especiais
+        std::fs::write(
+            dir.join("model.json"),
+            r#"{"task": "seq2seq", "special_tokens": {"eos": 7}, "max_sequence_length": 64}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            stage_c.init_from_dir(&dir),
+            Err(StageCError::MissingFile(dir.join("encoder_model.onnx")))
+        );
+        std::fs::write(
+            dir.join("config.json"),
+            r#"{"eos_token_id": 2, "num_beams": 4}"#,
+        )
+        .unwrap();
+        let options = ModelManifest::from_dir(&dir).unwrap().decode_options(&dir);
+        assert_eq!(options.eos_token_id, 7);
+        assert_eq!(options.max_length, 64);
+        assert_eq!(options.strategy, DecodeStrategy::Beam { width: 4 });
+
+        std::fs::write(dir.join("model.json"), r#"{"task": "seq2seq", "#).unwrap();
+        let err = stage_c.init_from_dir(&dir).unwrap_err();
+        assert!(matches!(err, StageCError::Manifest { .. }));
+        assert!(err.to_string().contains("model.json"));
+        assert!(!stage_c.is_ready());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rewrite_guard() {
+        use crate::edit::Edit;
+        use crate::guard::{GuardOptions, GuardReason, RewriteGuard};
+        use crate::shared::SharedEngine;
+        use crate::stage_c::Rewrite;
+        use std::sync::Arc;
+
+        // Trechos de cada token: o que ele acrescenta ao texto decodificado
+        let pieces = ["", " eu", " fui", " ao", " cinema"];
+        let decode = |ids: &[u32]| Some(ids.iter().map(|&id| pieces[id as usize]).collect());
+        let rewrite = Rewrite::from_tokens(
+            &[(0, 0.9), (1, 0.95), (2, 0.4), (3, 0.99), (4, 0.8)],
+            decode,
+        )
+        .unwrap();
+        assert_eq!(rewrite.text, "eu fui ao cinema");
+        assert_eq!(rewrite.tokens[0], (0..2, 0.9));
+        assert_eq!(rewrite.tokens[1], (2..6, 0.4));
+        assert_eq!(rewrite.probability(3..6), Some(0.4));
+        assert_eq!(rewrite.probability(7..16), Some(0.8));
+        assert_eq!(Rewrite::new("x").probability(0..1), None);
+
+        let mut engine = StageA::new();
+        engine.load_dictionary(&[
+            "eu", "fui", "vou", "ao", "cinema", "com", "a", "ontem", "chegou", "às", "veja",
+            "página", "ele", "disse", "que", "nós", "fomos", "hoje", "teatro", "de", "manhã", "e",
+            "depois", "voltou", "para", "casa", "saiu", "cedo", "muito",
+        ]);
+        let guard = RewriteGuard::new(GuardOptions::default(), Arc::new(SharedEngine::new(engine)));
+        let review = |original: &str, rewrite: &Rewrite| {
+            let edits = Edit::from_model(original, rewrite, "stage_c", 0.5);
+            guard.review(original, rewrite, edits)
+        };
+        let reasons = |original: &str, rewritten: &str| -> Vec<GuardReason> {
+            let result = review(original, &Rewrite::new(rewritten));
+            result
+                .rejected
+                .into_iter()
+                .map(|(_, reason)| reason)
+                .collect()
+        };
+
+        // Uma troca pequena, de palavra conhecida, passa
+        let ok = review(
+            "eu vou ontem ao cinema com a Maria",
+            &Rewrite::new("eu fui ontem ao cinema com a Maria"),
+        );
+        assert_eq!(ok.accepted.len(), 1);
+        assert_eq!(ok.accepted[0].replacement, "fui");
+        assert!(ok.rejected.is_empty());
+        // Maiúscula no começo da frase não é nome, se o dicionário conhece
+        assert!(reasons("Vou ao cinema.", "Fui ao cinema.").is_empty());
+
+        // Nomes, números e endereços ficam como estão
+        assert_eq!(
+            reasons(
+                "eu fui ao cinema com a Maria",
+                "eu fui ao cinema com a Mara"
+            ),
+            [GuardReason::Protected]
+        );
+        assert_eq!(
+            reasons("ele chegou às 10h ontem", "ele chegou às 11h ontem"),
+            [GuardReason::Protected]
+        );
+        assert_eq!(
+            reasons(
+                "veja www.exemplo.com/pagna hoje",
+                "veja www.exemplo.com/página hoje"
+            ),
+            [GuardReason::Protected]
+        );
+        assert_eq!(
+            reasons("ele disse que vou hoje", "ele disse que vou dia 12"),
+            [GuardReason::Protected]
+        );
+
+        // Palavra nova fora do dicionário
+        assert_eq!(
+            reasons("eu vou ao cinema", "eu fuii ao cinema"),
+            [GuardReason::UnknownWord("fuii".into())]
+        );
+        // Sem palavras no dicionário, nenhuma palavra nova passa; trocas de
+        // ordem, com palavras da própria frase, continuam valendo
+        let empty = RewriteGuard::new(GuardOptions::default(), Arc::new(SharedEngine::default()));
+        let rewrite = Rewrite::new("eu fui ao cinema");
+        let edits = Edit::from_model("eu vou ao cinema", &rewrite, "stage_c", 0.5);
+        let review_empty = empty.review("eu vou ao cinema", &rewrite, edits);
+        assert!(review_empty.accepted.is_empty());
+        assert_eq!(
+            review_empty.rejected[0].1,
+            GuardReason::UnknownWord("fui".into())
+        );
+        let rewrite = Rewrite::new("ao cinema eu vou");
+        let edits = Edit::from_model("eu vou ao cinema", &rewrite, "stage_c", 0.5);
+        assert!(empty
+            .review("eu vou ao cinema", &rewrite, edits)
+            .rejected
+            .iter()
+            .all(|(_, reason)| !matches!(reason, GuardReason::UnknownWord(_))));
+
+        // Reescritas grandes demais
+        assert_eq!(
+            reasons("eu vou ontem ao cinema", "nós fomos hoje ao cinema"),
+            [GuardReason::TooMuchChanged]
+        );
+        assert_eq!(
+            reasons(
+                "ele saiu cedo de manhã e depois voltou para casa muito cedo hoje",
+                "ele saiu cedo de manhã e nós fomos hoje ao cinema cedo hoje"
+            ),
+            [GuardReason::EditTooLong]
+        );
+        assert_eq!(
+            reasons(
+                "eu vou ao cinema e ele vou para casa e nós vou ao teatro e eu vou hoje",
+                "eu fui ao cinema e ele fui para casa e nós fui ao teatro e eu fui hoje"
+            ),
+            vec![GuardReason::TooManyEdits; 4]
+        );
+
+        // Probabilidade dos tokens da edição
+        let scored = |probability: f32| {
+            let pieces = [" eu", " fui", " ao", " cinema"];
+            let decode = |ids: &[u32]| Some(ids.iter().map(|&id| pieces[id as usize]).collect());
+            Rewrite::from_tokens(&[(0, 0.9), (1, probability), (2, 0.9), (3, 0.9)], decode).unwrap()
+        };
+        let low = review("eu vou ao cinema", &scored(0.2));
+        assert_eq!(low.rejected[0].1, GuardReason::LowProbability(0.2));
+        let high = review("eu vou ao cinema", &scored(0.8));
+        assert_eq!(high.accepted[0].confidence, 0.8);
+    }
+
+    #[test]
+    fn test_context_rerank() {
+        use crate::manifest::{ModelManifest, ModelTask};
+        use crate::rerank::{self, RERANK_LIMIT};
+        use crate::stage_a::CorrectionStep;
+
+        let mut engine = StageA::new();
+        engine.load_dictionary(&["casa", "caso", "cama", "bolo", "vaso"]);
+        let frequencies = [("casa", 1000), ("caso", 900), ("cama", 50), ("vaso", 20)];
+        let frequencies: Vec<(String, u32)> = frequencies
+            .iter()
+            .map(|&(w, f)| (w.to_string(), f))
+            .collect();
+        engine.load_frequency_data(&frequencies);
+
+        // A escolha do pipeline vem primeiro, na caixa da entrada
+        let candidates = engine.candidates("Casx", 1, RERANK_LIMIT);
+        let words: Vec<&str> = candidates.iter().map(|c| c.word.as_str()).collect();
+        assert_eq!(words[..2], ["Casa", "Caso"]);
+        assert_eq!(engine.correct("Casx", 1), "Casa");
+        assert_eq!(candidates[0].step, CorrectionStep::FuzzyDistance1);
+        assert_eq!((candidates[1].distance, candidates[1].frequency), (1, 900));
+        assert!(words.contains(&"Cama"));
+        assert_eq!(engine.candidates("casx", 1, 1).len(), 1);
+        assert!(engine.candidates("bolo", 1, RERANK_LIMIT).is_empty());
+        assert!(engine.candidates("xyzw", 0, RERANK_LIMIT).is_empty());
+
+        // Só candidatos próximos pedem o contexto
+        assert!(rerank::is_close(&candidates));
+        assert!(!rerank::is_close(&candidates[..1]));
+        let mut far = candidates.clone();
+        far[1].frequency = 10;
+        assert!(!rerank::is_close(&far));
+        let mut rule = candidates.clone();
+        rule[0].step = CorrectionStep::TypoModel;
+        assert!(!rerank::is_close(&rule));
+
+        // "o casx foi arquivado": o modelo prefere "caso"
+        let reranked = rerank::rerank(candidates.clone(), |words| {
+            Some(
+                words
+                    .iter()
+                    .map(|w| if w == "Caso" { -0.5 } else { -3.0 })
+                    .collect(),
+            )
+        });
+        assert_eq!(reranked[0].word, "Caso");
+        assert_eq!(reranked[0].context_score, Some(-0.5));
+        assert_eq!(reranked[1].word, "Casa");
+        assert_eq!(reranked.len(), candidates.len());
+        // Sem notas (modelo ausente), nada muda
+        assert_eq!(rerank::rerank(candidates.clone(), |_| None), candidates);
+
+        let manifest = ModelManifest::parse(
+            r#"{"task": "masked_lm", "special_tokens": {"mask": 4}, "outputs": {"logits": "logits"}}"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.task, ModelTask::MaskedLm);
+        assert_eq!(manifest.special_tokens.mask, Some(4));
+        let mut stage_c = crate::stage_c::StageC::new();
+        assert_eq!(
+            stage_c.score_candidates("o casx foi", 2..6, &["caso".into()]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_chunking() {
+        use crate::chunk::{self, Chunk, ChunkOptions, TokenCounts};
+        use crate::stage_c::Rewrite;
+
+        // Um token por palavra, com um especial em cada entrada
+        let words = |s: &str| s.split_whitespace().count() + 1;
+        let tokens = |s: &str| {
+            let offsets: Vec<(usize, usize)> = chunk::words(s)
+                .into_iter()
+                .map(|w| (w.start, w.end))
+                .collect();
+            TokenCounts::new(&offsets, 1)
+        };
+        let counts = tokens("eu fui ontem ao cinema");
+        assert_eq!(counts.count(&(0..6)), 3);
+        assert_eq!(counts.count(&(7..22)), 4);
+        let options = ChunkOptions {
+            max_tokens: 8,
+            overlap_words: 2,
+        };
+
+        // O que cabe vai inteiro
+        let short = "eu fui ontem.";
+        assert_eq!(
+            chunk::chunk(short, &options, &tokens(short)),
+            vec![Chunk {
+                range: 0..short.len(),
+                core: 0..short.len()
+            }]
+        );
+
+        // Frases juntas enquanto cabem com o contexto; núcleos cobrem o texto
+        let text = "eu vai em casa. ele foi la ontem. nós fomos tambem. acabou.";
+        let chunks = chunk::chunk(text, &options, &tokens(text));
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks[0].core.start, 0);
+        assert_eq!(chunks.last().unwrap().core.end, text.len());
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[0].core.end, pair[1].core.start);
+        }
+        for c in &chunks {
+            assert!(words(&text[c.range.clone()]) <= options.max_tokens);
+            assert!(c.range.start <= c.core.start && c.core.end <= c.range.end);
+        }
+        assert_eq!(&text[chunks[0].core.clone()], "eu vai em casa. ");
+        assert_eq!(&text[chunks[0].range.clone()], "eu vai em casa. ele foi");
+
+        // Uma frase longa demais vira janelas de palavras
+        let long = "um dois tres quatro cinco seis sete oito nove dez onze doze treze";
+        let windows = chunk::chunk(long, &options, &tokens(long));
+        assert!(windows.len() > 1);
+        assert!(windows
+            .iter()
+            .all(|c| words(&long[c.range.clone()]) <= options.max_tokens));
+        assert_eq!(windows.last().unwrap().core.end, long.len());
+
+        // Frases dentro de uma só palavra, um token por byte: sem pânico, e
+        // os núcleos ainda cobrem o texto
+        let glued = "xx yy.zzzzzzzz.ww";
+        let offsets: Vec<(usize, usize)> = (0..glued.len()).map(|i| (i, i + 1)).collect();
+        let tight = ChunkOptions {
+            max_tokens: 4,
+            overlap_words: 2,
+        };
+        let pieces = chunk::chunk(glued, &tight, &TokenCounts::new(&offsets, 1));
+        assert_eq!(pieces[0].core.start, 0);
+        assert_eq!(pieces.last().unwrap().core.end, glued.len());
+        for pair in pieces.windows(2) {
+            assert_eq!(pair[0].core.end, pair[1].core.start);
+        }
+
+        // Cada trecho corrige só o seu núcleo: a troca no contexto do
+        // primeiro trecho ("ele foi" → "ela foi") é ignorada
+        let rewrites: Vec<Option<Rewrite>> = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let part = text[c.range.clone()].trim();
+                let fixed = match i {
+                    0 => "eu vou em casa. ela foi".to_string(),
+                    _ => part
+                        .replace("la ontem", "lá ontem")
+                        .replace("tambem", "também"),
+                };
+                (fixed != part).then(|| Rewrite::new(fixed))
+            })
+            .collect();
+        let merged = chunk::merge(text, &chunks, &rewrites);
+        assert_eq!(
+            merged.text,
+            "eu vou em casa. ele foi lá ontem. nós fomos também. acabou."
+        );
+        assert_eq!(
+            chunk::merge(text, &chunks, &vec![None; chunks.len()]).text,
+            text
+        );
+    }
+
+    #[test]
+    fn test_rewrite_cache() {
+        use crate::cache::{self, CacheStats, RewriteCache};
+        use crate::stage_c::{Rewrite, StageC};
+
+        // LRU por versão do modelo e texto normalizado
+        let cache = RewriteCache::new(2);
+        let key = cache::normalize("  eu  vai\tembora ");
+        assert_eq!(key, "eu vai embora");
+        assert_eq!(cache.get("v1", &key), None);
+        cache.insert("v1", &key, Some(Rewrite::new("eu vou embora")));
+        cache.insert("v1", "tudo certo", None);
+        assert_eq!(
+            cache.get("v1", &key),
+            Some(Some(Rewrite::new("eu vou embora")))
+        );
+        assert_eq!(cache.get("v1", "tudo certo"), Some(None));
+        assert_eq!(cache.get("v2", &key), None);
+        // "eu vai embora" é o usado há mais tempo e sai
+        cache.insert("v1", "outra frase", None);
+        assert_eq!(cache.get("v1", &key), None);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 2,
+                misses: 3,
+                evictions: 1,
+                entries: 2,
+                capacity: 2,
+            }
+        );
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+        let off = RewriteCache::new(0);
+        off.insert("v1", "frase", None);
+        assert_eq!(off.get("v1", "frase"), None);
+
+        // A reescrita do texto normalizado volta aos espaços do original
+        let original = "  eu  vai\tembora ";
+        let mut rewrite = Rewrite::new("eu vou embora");
+        rewrite.tokens = vec![(0..2, 0.9), (3..6, 0.7), (7..13, 0.9)];
+        let projected = cache::project(original, &key, &rewrite);
+        assert_eq!(projected.text, "  eu  vou\tembora ");
+        assert_eq!(projected.tokens, vec![(6..9, 0.7)]);
+        let joined = cache::project("a  b", "a b", &Rewrite::new("ab"));
+        assert_eq!(joined.text, "ab");
+        assert_eq!(
+            cache::project("certo", "certo", &Rewrite::new("certa")).text,
+            "certa"
+        );
+
+        // Sem modelo, nada vai ao cache
+        let mut stage_c = StageC::new();
+        assert_eq!(stage_c.rewrite_batch(&["um", "dois"]), vec![None, None]);
+        assert_eq!(stage_c.cache().stats().misses, 0);
+    }
+
+    #[cfg(feature = "embedded-data")]
+    #[test]
+    fn test_embedded_data_fallback() {
+        use crate::builder::Resource;
+        use crate::paths::Origin;
+        use crate::EngineBuilder;
+
+        // Sem nenhum arquivo configurado, os dados embutidos são usados
+        let (engine, report) = EngineBuilder::new().build().unwrap();
+        let dict = report.get(Resource::Dictionary).unwrap();
+        assert_eq!(dict.resolution.origin(), Some(Origin::Embedded));
+        assert!(dict.entries > 100_000);
+        assert!(report.entries(Resource::Frequency) > 10_000);
+        assert_eq!(engine.correct("voce", 1), "você");
+    }
 }
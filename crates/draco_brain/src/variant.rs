@@ -0,0 +1,332 @@
+use crate::glossary;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Extensão das listas de palavras por norma (`variants/pt_pt.txt`).
+pub const VARIANT_EXTENSION: &str = "txt";
+
+/// Norma ortográfica do português usada pelo engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    /// Português do Brasil (a norma dos dados de base)
+    #[default]
+    PtBr,
+    /// Português europeu
+    PtPt,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 2] = [Variant::PtBr, Variant::PtPt];
+
+    /// Nome usado na configuração e no nome da lista de palavras.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Variant::PtBr => "pt_br",
+            Variant::PtPt => "pt_pt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.as_str() == name)
+    }
+
+    /// Palavras válidas nesta norma que faltam nos dados de base (que são
+    /// do Brasil). Entram na camada de variante do `StageA`; as famílias de
+    /// [`SUFFIXES`], abertas, são reconhecidas pela conversão.
+    pub fn words(self) -> Vec<String> {
+        match self {
+            Variant::PtBr => Vec::new(),
+            Variant::PtPt => SPELLINGS
+                .iter()
+                .map(|(_, pt)| pt.to_string())
+                .chain(STEMS.iter().flat_map(Rule::pt_words))
+                .chain(PRE_REFORM.iter().flat_map(Rule::pt_words))
+                .chain(PT_PT_ONLY.iter().map(|w| w.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Pares (Brasil, Portugal) de grafias que só existem numa das normas e não
+/// seguem nenhuma família de [`STEMS`] ou [`SUFFIXES`]. Ficam de fora
+/// palavras que existem nas duas com sentidos diferentes (`fato`/`facto`) e
+/// diferenças de vocabulário (`tela`/`ecrã`).
+const SPELLINGS: &[(&str, &str)] = &[
+    ("conosco", "connosco"),
+    ("cômodo", "cómodo"),
+    ("cômodos", "cómodos"),
+    ("tênis", "ténis"),
+    ("bebê", "bebé"),
+    ("bebês", "bebés"),
+];
+
+/// Família de palavras com a mesma diferença de grafia: `br` + terminação
+/// no Brasil, `pt` + terminação em Portugal.
+struct Rule {
+    br: &'static str,
+    pt: &'static str,
+    endings: &'static [&'static str],
+}
+
+impl Rule {
+    /// Troca `from` por `to` em `word`, se a palavra for `prefix + from +
+    /// terminação` (em [`STEMS`] o prefixo é vazio; em [`SUFFIXES`], não).
+    fn apply(&self, word: &str, from: &str, to: &str, stem: bool) -> Option<String> {
+        if !word.contains(from) {
+            return None;
+        }
+        self.endings.iter().find_map(|ending| {
+            let prefix = word.strip_suffix(ending)?.strip_suffix(from)?;
+            (prefix.is_empty() == stem).then(|| format!("{}{}{}", prefix, to, ending))
+        })
+    }
+
+    /// Todas as palavras da família na grafia de Portugal.
+    fn pt_words(&self) -> impl Iterator<Item = String> + '_ {
+        self.endings
+            .iter()
+            .map(|ending| format!("{}{}", self.pt, ending))
+    }
+}
+
+/// Substantivos e adjetivos variáveis.
+const NOMINAL: &[&str] = &["o", "a", "os", "as"];
+
+/// Verbos em -ar (formas mais comuns), com o substantivo em -o.
+const VERBAL: &[&str] = &[
+    "o", "os", "a", "as", "ar", "amos", "am", "ei", "ou", "aram", "ava", "avam", "ará", "arão",
+    "aria", "ariam", "e", "es", "em", "emos", "asse", "assem", "ado", "ada", "ados", "adas",
+    "ando", "ável", "áveis",
+];
+
+/// Consoante muda antes de -ção e família (recepção → receção).
+const MUTE_P: &[&str] = &[
+    "ção",
+    "ções",
+    "cionista",
+    "cionistas",
+    "cional",
+    "cionais",
+    "cionar",
+    "cionado",
+    "cionada",
+    "cionados",
+    "cionadas",
+    "cionou",
+    "tivo",
+    "tiva",
+    "tivos",
+    "tivas",
+    "tível",
+    "tíveis",
+];
+
+/// Radicais que mudam entre as normas, com as terminações que os seguem
+/// (registrado → registado, contatou → contactou). Só valem no começo da
+/// palavra: `seção` não é `sepção`.
+const STEMS: &[Rule] = &[
+    Rule {
+        br: "contat",
+        pt: "contact",
+        endings: VERBAL,
+    },
+    Rule {
+        br: "registr",
+        pt: "regist",
+        endings: VERBAL,
+    },
+    Rule {
+        br: "recep",
+        pt: "rece",
+        endings: MUTE_P,
+    },
+    Rule {
+        br: "concep",
+        pt: "conce",
+        endings: MUTE_P,
+    },
+    Rule {
+        br: "percep",
+        pt: "perce",
+        endings: MUTE_P,
+    },
+    Rule {
+        br: "decep",
+        pt: "dece",
+        endings: MUTE_P,
+    },
+    Rule {
+        br: "contracep",
+        pt: "contrace",
+        endings: MUTE_P,
+    },
+];
+
+/// Terminações com acento circunflexo no Brasil e agudo em Portugal, antes
+/// de `m` e `n` (econômico → económico, prêmio → prémio), para qualquer
+/// palavra com algo antes delas.
+const SUFFIXES: &[Rule] = &[
+    Rule {
+        br: "ômic",
+        pt: "ómic",
+        endings: NOMINAL,
+    },
+    Rule {
+        br: "ônic",
+        pt: "ónic",
+        endings: NOMINAL,
+    },
+    Rule {
+        br: "êmic",
+        pt: "émic",
+        endings: NOMINAL,
+    },
+    Rule {
+        br: "ônim",
+        pt: "ónim",
+        endings: NOMINAL,
+    },
+    Rule {
+        br: "ôni",
+        pt: "óni",
+        endings: NOMINAL,
+    },
+    Rule {
+        br: "êni",
+        pt: "éni",
+        endings: NOMINAL,
+    },
+    Rule {
+        br: "êmi",
+        pt: "émi",
+        endings: NOMINAL,
+    },
+    Rule {
+        br: "êmer",
+        pt: "émer",
+        endings: NOMINAL,
+    },
+    Rule {
+        br: "ômetr",
+        pt: "ómetr",
+        endings: &["o", "os"],
+    },
+    Rule {
+        br: "ômen",
+        pt: "ómen",
+        endings: &["o", "os"],
+    },
+    Rule {
+        br: "êner",
+        pt: "éner",
+        endings: &["o", "os"],
+    },
+];
+
+/// Grafias de Portugal anteriores ao Acordo de 1990, ainda comuns e aceitas
+/// na norma europeia, com a forma do Brasil (`pt` → `br`). Só são
+/// convertidas no sentido Portugal → Brasil; no europeu atual as duas
+/// formas valem.
+const PRE_REFORM: &[Rule] = &[
+    Rule {
+        br: "a",
+        pt: "ac",
+        endings: &["ção", "ções"],
+    },
+    Rule {
+        br: "atual",
+        pt: "actual",
+        endings: &[
+            "",
+            "mente",
+            "izar",
+            "izado",
+            "izada",
+            "izados",
+            "izadas",
+            "izou",
+            "ização",
+            "izações",
+            "idade",
+            "idades",
+        ],
+    },
+    Rule {
+        br: "dire",
+        pt: "direc",
+        endings: &[
+            "ção", "ções", "to", "ta", "tos", "tas", "tor", "tora", "tores", "toras", "tamente",
+        ],
+    },
+    Rule {
+        br: "cole",
+        pt: "colec",
+        endings: &[
+            "ção", "ções", "cionar", "cionador", "tivo", "tiva", "tivos", "tivas",
+        ],
+    },
+    Rule {
+        br: "ótim",
+        pt: "óptim",
+        endings: NOMINAL,
+    },
+    Rule {
+        br: "bati",
+        pt: "bapti",
+        endings: &["smo", "smos", "zar", "zado", "zada", "zou", "sta", "stas"],
+    },
+];
+
+/// Palavras do europeu sem par de grafia no Brasil (ver [`SPELLINGS`]).
+const PT_PT_ONLY: &[&str] = &["facto", "factos", "ecrã", "ecrãs"];
+
+/// Tabela de conversão para `target` (palavras em minúsculas).
+fn table(target: Variant) -> &'static HashMap<&'static str, &'static str> {
+    static TO_PT_BR: OnceLock<HashMap<&str, &str>> = OnceLock::new();
+    static TO_PT_PT: OnceLock<HashMap<&str, &str>> = OnceLock::new();
+    match target {
+        Variant::PtBr => {
+            TO_PT_BR.get_or_init(|| SPELLINGS.iter().map(|&(br, pt)| (pt, br)).collect())
+        }
+        Variant::PtPt => TO_PT_PT.get_or_init(|| SPELLINGS.iter().copied().collect()),
+    }
+}
+
+/// Forma de uma palavra (em minúsculas) na norma `target`, se ela for
+/// grafia exclusiva da outra norma: primeiro os pares fixos, depois as
+/// famílias por radical e por terminação, que cobrem as formas flexionadas.
+pub fn convert_word(word: &str, target: Variant) -> Option<String> {
+    if let Some(converted) = table(target).get(word) {
+        return Some(converted.to_string());
+    }
+    let stems = STEMS.iter().map(|rule| (rule, true));
+    let suffixes = SUFFIXES.iter().map(|rule| (rule, false));
+    match target {
+        Variant::PtPt => stems
+            .chain(suffixes)
+            .find_map(|(rule, stem)| rule.apply(word, rule.br, rule.pt, stem)),
+        Variant::PtBr => stems
+            .chain(PRE_REFORM.iter().map(|rule| (rule, true)))
+            .chain(suffixes)
+            .find_map(|(rule, stem)| rule.apply(word, rule.pt, rule.br, stem)),
+    }
+}
+
+/// Reescreve um texto corrido para a norma `target`, mantendo a caixa de
+/// cada palavra e todo o resto (pontuação, espaços) intacto.
+pub fn convert(text: &str, target: Variant) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (start, end) in glossary::word_spans(text) {
+        let typed = &text[start..end];
+        if let Some(converted) = convert_word(&typed.to_lowercase(), target) {
+            out.push_str(&text[copied..start]);
+            out.push_str(&glossary::match_case(&converted, typed));
+            copied = end;
+        }
+    }
+    out.push_str(&text[copied..]);
+    out
+}
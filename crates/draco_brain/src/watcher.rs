@@ -0,0 +1,326 @@
+use crate::blocklist::Blocklist;
+use crate::builder::{EngineBuilder, LoadError, LoadReport, Resource};
+use crate::glossary::{self, Glossary, GlossaryEntry};
+use crate::layers::{DictionaryLayer, LayerKind};
+use crate::paths;
+use crate::shared::SharedEngine;
+use crate::typo_model::TypoModel;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+/// Intervalo padrão entre duas verificações dos arquivos.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Recursos vigiados; o diretório de modelos, as regras gramaticais (que
+/// são do Stage B, fora do `SharedEngine`) e o `config.json` só mudam com
+/// o IME reiniciado.
+const WATCHED: [Resource; 8] = [
+    Resource::Frequency,
+    Resource::Dictionary,
+    Resource::Variants,
+    Resource::TeamDictionary,
+    Resource::Packs,
+    Resource::Glossary,
+    Resource::Blocklist,
+    Resource::TypoRules,
+];
+
+/// O que o vigia fez depois de notar uma mudança.
+#[derive(Debug, Clone)]
+pub enum ReloadEvent {
+    /// Os dados novos foram carregados e já estão em uso.
+    Reloaded {
+        resources: Vec<Resource>,
+        report: LoadReport,
+    },
+    /// Algum arquivo está quebrado; a versão anterior continua em uso.
+    Rejected {
+        resources: Vec<Resource>,
+        error: LoadError,
+    },
+}
+
+impl fmt::Display for ReloadEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = |resources: &[Resource]| {
+            resources
+                .iter()
+                .map(|r| r.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            ReloadEvent::Reloaded { resources, report } => write!(
+                f,
+                "recarregado ({}) em {:?}",
+                names(resources),
+                report.total
+            ),
+            ReloadEvent::Rejected { resources, error } => write!(
+                f,
+                "recarga rejeitada ({}), versão anterior mantida: {}",
+                names(resources),
+                error
+            ),
+        }
+    }
+}
+
+/// Identifica uma versão de um arquivo: onde foi resolvido, data de
+/// modificação e tamanho. `path` é `None` para dados embutidos ou ausentes.
+/// Para diretórios (pacotes, glossários) vale a modificação mais recente e
+/// a soma dos tamanhos dos arquivos.
+#[derive(Debug, Clone, PartialEq)]
+struct Stamp {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Stamp {
+    fn of(builder: &EngineBuilder, resource: Resource) -> Self {
+        let path = builder.resolve(resource).path().map(PathBuf::from);
+        let mut files: Vec<PathBuf> = path.iter().cloned().collect();
+        if let (Some(dir), Some(extension)) = (&path, resource.entry_extension()) {
+            files.extend(paths::files_with_extension(dir, extension).unwrap_or_default());
+        }
+        let metadata: Vec<_> = files
+            .iter()
+            .filter_map(|p| std::fs::metadata(p).ok())
+            .collect();
+        Self {
+            modified: metadata.iter().filter_map(|m| m.modified().ok()).max(),
+            len: metadata
+                .iter()
+                .filter(|m| m.is_file())
+                .map(|m| m.len())
+                .sum(),
+            path,
+        }
+    }
+
+    fn all(builder: &EngineBuilder) -> Vec<Self> {
+        WATCHED.iter().map(|&r| Self::of(builder, r)).collect()
+    }
+}
+
+/// Vigia os arquivos de dados e recarrega o engine quando mudam.
+///
+/// A verificação é por polling (data e tamanho de cada arquivo, resolvidos
+/// de novo a cada volta, então um arquivo criado numa origem de maior
+/// prioridade também conta). Uma mudança só é aplicada depois de ficar
+/// estável por uma volta inteira, para não pegar um arquivo no meio da
+/// gravação. Só as partes que mudaram (lista da norma, camadas da equipe e
+/// dos pacotes, glossário, correções proibidas, regras do `TypoModel`) são
+/// remontadas e trocadas numa cópia do engine atual; o engine inteiro só é
+/// recarregado, com [`SharedEngine::replace`], quando mudam o dicionário ou
+/// as frequências. Em caso de erro nada é trocado.
+///
+/// O vigia para quando é descartado.
+pub struct DataWatcher {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DataWatcher {
+    /// Começa a vigiar os recursos de `builder` numa thread própria.
+    /// `on_event` é chamado nessa thread a cada recarga ou rejeição.
+    pub fn spawn(
+        builder: EngineBuilder,
+        engine: Arc<SharedEngine>,
+        interval: Duration,
+        on_event: impl Fn(ReloadEvent) + Send + 'static,
+    ) -> Self {
+        let (stop, stopped) = mpsc::channel::<()>();
+        // Lido já aqui: mudanças feitas logo depois do `spawn` não se perdem
+        let mut seen = Stamp::all(&builder);
+        let thread = std::thread::Builder::new()
+            .name("draco-watcher".into())
+            .spawn(move || {
+                let mut pending: Option<Vec<Stamp>> = None;
+                // Qualquer coisa além do timeout (parar ou o dono sumir) encerra
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let current = Stamp::all(&builder);
+                    if current == seen {
+                        pending = None;
+                        continue;
+                    }
+                    // Espera a mudança ficar estável por uma volta
+                    if pending.as_ref() != Some(&current) {
+                        pending = Some(current);
+                        continue;
+                    }
+                    let changed: Vec<Resource> = WATCHED
+                        .iter()
+                        .zip(seen.iter().zip(&current))
+                        .filter(|(_, (old, new))| old != new)
+                        .map(|(&r, _)| r)
+                        .collect();
+                    // Mesmo rejeitada, esta versão não é tentada de novo
+                    seen = current;
+                    pending = None;
+                    on_event(reload(&builder, &engine, changed));
+                }
+            })
+            .expect("falha ao criar a thread do vigia de dados");
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    /// Para o vigia e espera a thread terminar.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for DataWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn reload(builder: &EngineBuilder, engine: &SharedEngine, resources: Vec<Resource>) -> ReloadEvent {
+    let result = if resources
+        .iter()
+        .any(|r| matches!(r, Resource::Frequency | Resource::Dictionary))
+    {
+        builder.build_strict().map(|(next, report)| {
+            engine.replace(next);
+            report
+        })
+    } else {
+        reload_parts(builder, engine, &resources)
+    };
+    match result {
+        Ok(report) => ReloadEvent::Reloaded { resources, report },
+        Err(error) => ReloadEvent::Rejected { resources, error },
+    }
+}
+
+/// Parte do engine remontada a partir de um recurso.
+enum Part {
+    VariantWords(Vec<(String, u32)>),
+    Team(Option<DictionaryLayer>),
+    Packs(Vec<DictionaryLayer>),
+    Glossary(Glossary),
+    Blocklist(Blocklist),
+    TypoModel(TypoModel),
+}
+
+/// Remonta só as partes que mudaram e as troca numa cópia do snapshot
+/// atual. Se alguma falhar, nenhuma é trocada.
+fn reload_parts(
+    builder: &EngineBuilder,
+    engine: &SharedEngine,
+    resources: &[Resource],
+) -> Result<LoadReport, LoadError> {
+    let mut parts = Vec::new();
+    let mut report = LoadReport::default();
+    for &resource in resources {
+        let (part, resource_report) = match resource {
+            Resource::Variants => {
+                let (words, r) = builder.build_variant_words()?;
+                (Part::VariantWords(words), r)
+            }
+            Resource::TeamDictionary => {
+                let (layer, r) = builder.build_team_layer()?;
+                (Part::Team(layer), r)
+            }
+            Resource::Packs => {
+                let (packs, r) = builder.load_packs(true)?;
+                (Part::Packs(packs), r)
+            }
+            Resource::Glossary => {
+                let (glossary, r) = builder.load_glossary(true)?;
+                (Part::Glossary(glossary), r)
+            }
+            Resource::Blocklist => {
+                let (blocklist, r) = builder.build_blocklist()?;
+                (Part::Blocklist(blocklist), r)
+            }
+            Resource::TypoRules => {
+                let (model, r) = builder.build_typo_model()?;
+                (Part::TypoModel(model), r)
+            }
+            // Não vigiados (ou tratados com a recarga completa)
+            _ => continue,
+        };
+        report.total += resource_report.elapsed;
+        report.resources.push(resource_report);
+        parts.push(part);
+    }
+    engine.update(|e| {
+        for part in parts {
+            match part {
+                Part::VariantWords(words) => {
+                    // Volta às palavras embutidas da norma antes de incluir as novas
+                    e.set_variant(e.variant());
+                    e.add_variant_words(&words);
+                }
+                Part::Team(layer) => {
+                    let name = LayerKind::Team.as_str();
+                    let old = e.remove_layer(name);
+                    if let Some(layer) = layer {
+                        e.add_layer(keep_runtime_state(layer, old.as_ref()));
+                    }
+                }
+                Part::Packs(packs) => {
+                    let names: Vec<String> = e
+                        .layers()
+                        .iter()
+                        .filter(|l| l.kind() == LayerKind::Pack)
+                        .map(|l| l.name().to_string())
+                        .collect();
+                    let old: Vec<DictionaryLayer> =
+                        names.iter().filter_map(|n| e.remove_layer(n)).collect();
+                    for layer in packs {
+                        let previous = old.iter().find(|l| l.name() == layer.name());
+                        e.add_layer(keep_runtime_state(layer, previous));
+                    }
+                }
+                Part::Glossary(glossary) => {
+                    // Os termos incluídos em tempo de execução continuam
+                    let runtime: Vec<GlossaryEntry> = e
+                        .glossary()
+                        .entries_from(glossary::RUNTIME_SOURCE)
+                        .cloned()
+                        .collect();
+                    e.set_glossary(glossary);
+                    if !runtime.is_empty() {
+                        e.add_glossary_entries(&runtime);
+                    }
+                }
+                Part::Blocklist(blocklist) => e.set_blocklist(blocklist),
+                Part::TypoModel(model) => e.set_typo_model(model),
+            }
+        }
+    });
+    Ok(report)
+}
+
+/// Uma camada recarregada continua ligada ou desligada, e com a
+/// prioridade, como estava em tempo de execução.
+fn keep_runtime_state(
+    layer: DictionaryLayer,
+    previous: Option<&DictionaryLayer>,
+) -> DictionaryLayer {
+    match previous {
+        Some(previous) => layer
+            .with_enabled(previous.is_enabled())
+            .with_priority(previous.priority()),
+        None => layer,
+    }
+}
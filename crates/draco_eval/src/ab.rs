@@ -0,0 +1,201 @@
+use draco_brain::stage_a::{CorrectionStep, StageA};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// Um lado da comparação: engine já carregado + agressividade usada.
+pub struct Side<'a> {
+    pub label: String,
+    pub engine: &'a StageA,
+    pub aggressiveness: u32,
+}
+
+/// Resultado de uma entrada cuja saída mudou entre A e B.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diff {
+    pub input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    pub output_a: String,
+    pub step_a: &'static str,
+    pub output_b: String,
+    pub step_b: &'static str,
+    pub verdict: Verdict,
+}
+
+/// Efeito da mudança, quando a saída esperada é conhecida.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// B acertou onde A errava
+    Win,
+    /// A acertava e B passou a errar
+    Loss,
+    /// Ambos erram (ou a saída esperada é desconhecida)
+    Neutral,
+}
+
+/// Diferenças agrupadas pela etapa responsável em cada lado (`etapa A → etapa B`).
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Group {
+    pub wins: usize,
+    pub losses: usize,
+    pub neutral: usize,
+    pub diffs: Vec<Diff>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AbReport {
+    pub label_a: String,
+    pub label_b: String,
+    pub inputs: usize,
+    pub changed: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub groups: BTreeMap<String, Group>,
+}
+
+impl AbReport {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Roda as mesmas entradas pelos dois lados e coleta toda saída diferente.
+/// Entradas repetidas são avaliadas uma única vez.
+pub fn compare(a: &Side, b: &Side, inputs: &[(String, Option<String>)]) -> AbReport {
+    let mut seen = HashSet::new();
+    let mut groups: BTreeMap<String, Group> = BTreeMap::new();
+    let mut total = 0;
+
+    for (input, expected) in inputs {
+        if !seen.insert(input.as_str()) {
+            continue;
+        }
+        total += 1;
+
+        let (output_a, step_a) = a.engine.correct_with_step(input, a.aggressiveness);
+        let (output_b, step_b) = b.engine.correct_with_step(input, b.aggressiveness);
+        if output_a == output_b {
+            continue;
+        }
+
+        let verdict = match expected {
+            Some(exp) if *exp == output_b => Verdict::Win,
+            Some(exp) if *exp == output_a => Verdict::Loss,
+            _ => Verdict::Neutral,
+        };
+
+        let group = groups.entry(group_key(step_a, step_b)).or_default();
+        match verdict {
+            Verdict::Win => group.wins += 1,
+            Verdict::Loss => group.losses += 1,
+            Verdict::Neutral => group.neutral += 1,
+        }
+        group.diffs.push(Diff {
+            input: input.clone(),
+            expected: expected.clone(),
+            output_a,
+            step_a: step_a.as_str(),
+            output_b,
+            step_b: step_b.as_str(),
+            verdict,
+        });
+    }
+
+    AbReport {
+        label_a: a.label.clone(),
+        label_b: b.label.clone(),
+        inputs: total,
+        changed: groups.values().map(|g| g.diffs.len()).sum(),
+        wins: groups.values().map(|g| g.wins).sum(),
+        losses: groups.values().map(|g| g.losses).sum(),
+        groups,
+    }
+}
+
+fn group_key(a: CorrectionStep, b: CorrectionStep) -> String {
+    if a == b {
+        a.as_str().to_string()
+    } else {
+        format!("{} → {}", a.as_str(), b.as_str())
+    }
+}
+
+impl fmt::Display for AbReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "A: {}", self.label_a)?;
+        writeln!(f, "B: {}", self.label_b)?;
+        writeln!(
+            f,
+            "{} entradas, {} mudaram: {} vitórias, {} derrotas (B em relação a A)",
+            self.inputs, self.changed, self.wins, self.losses
+        )?;
+
+        for (key, group) in &self.groups {
+            writeln!(
+                f,
+                "\n[{}] {} mudanças (+{} −{} ={})",
+                key,
+                group.diffs.len(),
+                group.wins,
+                group.losses,
+                group.neutral
+            )?;
+            for d in &group.diffs {
+                let mark = match d.verdict {
+                    Verdict::Win => '+',
+                    Verdict::Loss => '-',
+                    Verdict::Neutral => ' ',
+                };
+                write!(
+                    f,
+                    "  {} {:<20} A: {:<20} B: {:<20}",
+                    mark, d.input, d.output_a, d.output_b
+                )?;
+                match &d.expected {
+                    Some(exp) => writeln!(f, " esperado: {}", exp)?,
+                    None => writeln!(f)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_changes_by_step_and_scores_them() {
+        let mut a = StageA::new();
+        a.load_dictionary(&["casa", "carro"]);
+        let mut b = StageA::new();
+        b.load_dictionary(&["casa", "carro", "cassa"]);
+
+        let side_a = Side {
+            label: "A".into(),
+            engine: &a,
+            aggressiveness: 1,
+        };
+        let side_b = Side {
+            label: "B".into(),
+            engine: &b,
+            aggressiveness: 1,
+        };
+        let inputs = vec![
+            ("cassa".to_string(), Some("casa".to_string())),
+            ("cassa".to_string(), Some("casa".to_string())),
+            ("carro".to_string(), Some("carro".to_string())),
+        ];
+
+        let report = compare(&side_a, &side_b, &inputs);
+
+        assert_eq!(report.inputs, 2);
+        assert_eq!(report.changed, 1);
+        assert_eq!(report.losses, 1);
+        let group = &report.groups["phonetic → dictionary"];
+        assert_eq!(group.diffs[0].output_b, "cassa");
+    }
+}
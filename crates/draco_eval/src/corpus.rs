@@ -0,0 +1,106 @@
+use std::path::Path;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Um item do corpus anotado: entrada digitada e a saída esperada.
+/// Para palavras corretas (texto limpo), `expected == input`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorpusItem {
+    pub input: String,
+    pub expected: String,
+    /// Categoria opcional do erro (terceira coluna, ex.: `vizinha`).
+    pub tag: Option<String>,
+    /// Linha do arquivo de origem (1-based), para facilitar a depuração.
+    pub line: usize,
+}
+
+impl CorpusItem {
+    /// `true` quando a entrada contém um erro a ser corrigido.
+    pub fn is_typo(&self) -> bool {
+        self.input != self.expected
+    }
+}
+
+/// Corpus anotado para avaliação.
+///
+/// Formato do arquivo (UTF-8, uma entrada por linha):
+/// - `erro<TAB>esperado[<TAB>categoria]` → par de correção (ex.: `vc<TAB>você`)
+/// - linha sem TAB → texto limpo; cada palavra deve sair inalterada
+/// - linhas vazias e iniciadas por `#` são ignoradas
+#[derive(Debug, Default)]
+pub struct Corpus {
+    pub items: Vec<CorpusItem>,
+}
+
+impl Corpus {
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut corpus = Corpus::default();
+        for (idx, line) in text.lines().enumerate() {
+            corpus.push_line(line, idx + 1);
+        }
+        corpus
+    }
+
+    fn push_line(&mut self, line: &str, line_no: usize) {
+        let trimmed = line.trim_start_matches('\u{feff}').trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return;
+        }
+
+        if trimmed.contains('\t') {
+            let mut fields = trimmed.split('\t').map(str::trim);
+            let input = fields.next().unwrap_or_default();
+            let expected = fields.next().unwrap_or_default();
+            let tag = fields.next().filter(|t| !t.is_empty());
+            if !input.is_empty() && !expected.is_empty() {
+                self.items.push(CorpusItem {
+                    input: input.to_string(),
+                    expected: expected.to_string(),
+                    tag: tag.map(str::to_string),
+                    line: line_no,
+                });
+            }
+            return;
+        }
+
+        // Texto limpo: cada palavra é um caso de "não mexer"
+        for word in trimmed.unicode_words() {
+            self.items.push(CorpusItem {
+                input: word.to_string(),
+                expected: word.to_string(),
+                tag: None,
+                line: line_no,
+            });
+        }
+    }
+
+    pub fn typo_count(&self) -> usize {
+        self.items.iter().filter(|item| item.is_typo()).count()
+    }
+
+    pub fn clean_count(&self) -> usize {
+        self.items.len() - self.typo_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pairs_clean_text_and_comments() {
+        let corpus =
+            Corpus::parse("# comentário\nvc\tvocê\tabreviação\n\nO menino foi à escola.\n");
+
+        assert_eq!(corpus.typo_count(), 1);
+        assert_eq!(corpus.clean_count(), 5);
+        assert_eq!(corpus.items[0].expected, "você");
+        assert_eq!(corpus.items[0].tag.as_deref(), Some("abreviação"));
+        assert_eq!(corpus.items[1].input, "O");
+        assert_eq!(corpus.items[5].input, "escola");
+        assert_eq!(corpus.items[5].line, 4);
+    }
+}
@@ -0,0 +1,35 @@
+use draco_brain::builder::Resource;
+use draco_brain::stage_a::StageA;
+use draco_brain::variant::Variant;
+use draco_brain::EngineBuilder;
+use std::path::Path;
+
+/// Carrega o `StageA` a partir de um diretório de dados com o mesmo
+/// `EngineBuilder` dos frontends, na norma pedida e ligando os pacotes de
+/// domínio pedidos.
+pub fn load_engine(data_dir: &Path, packs: &[&str], variant: Variant) -> Result<StageA, String> {
+    let (mut engine, report) = EngineBuilder::new()
+        .data_dir(data_dir)
+        .variant(variant)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    for missing in report.missing() {
+        if missing.resource == Resource::Dictionary {
+            return Err(format!("dicionário não encontrado em {:?}", data_dir));
+        }
+        eprintln!(
+            "[eval] Aviso: {} ausente em {:?}",
+            missing.resource.as_str(),
+            data_dir
+        );
+    }
+
+    for pack in packs {
+        if !engine.set_layer_enabled(pack, true) {
+            return Err(format!("pacote desconhecido: {}", pack));
+        }
+    }
+
+    Ok(engine)
+}
@@ -0,0 +1,397 @@
+mod ab;
+mod corpus;
+mod engine;
+mod metrics;
+mod report;
+mod typo_gen;
+
+use corpus::Corpus;
+use draco_brain::variant::{self, Variant};
+use metrics::Preset;
+use report::EvalReport;
+use std::fmt::Write as _;
+use std::io::Read as _;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use typo_gen::{ErrorKind, ErrorRates, TypoGenerator};
+
+const USAGE: &str = "\
+Uso: draco_eval [run] [opções]
+     draco_eval gen --input <arq> [opções]
+     draco_eval ab --a <dir> --b <dir> [opções]
+     draco_eval explain [opções] <palavra>...
+     draco_eval convert --to <norma> [opções]
+
+run: executa um corpus anotado pelo StageA em cada preset e reporta
+acurácia, precisão, recall, falsos positivos, abstenções e latência.
+  --data <dir>       Diretório com os dados (padrão: data)
+  --corpus <arq>     Corpus anotado (padrão: data/eval/corpus_pt_br.tsv)
+  --preset <nome>    conservador | normal | agressivo (repetível; padrão: todos)
+  --pack <nome>      Liga um pacote de domínio de <dir>/packs (repetível)
+  --variant <norma>  pt_br | pt_pt (padrão: pt_br)
+  --json <arq>       Onde gravar o relatório JSON (padrão: eval_report.json; '-' = stdout)
+  --misses <n>       Mostra até n casos errados por preset (padrão: 0)
+
+gen: injeta erros sintéticos em texto limpo (uma palavra ou frase por
+linha) e grava um corpus no formato aceito por `run`.
+  --input <arq>      Texto limpo de entrada
+  --output <arq>     Corpus gerado (padrão: stdout)
+  --seed <n>         Semente do gerador (padrão: 1)
+  --rate <tipo>=<p>  Probabilidade por palavra de um tipo de erro (repetível).
+                     Tipos: vizinha, acento, dobrada, faltando, transposicao,
+                     fonetica, abreviacao
+
+ab: roda a mesma entrada por duas configurações do StageA e lista cada
+saída diferente, agrupada pela etapa do pipeline responsável.
+  --a <dir>, --b <dir>           Diretórios de dados de cada lado
+  --preset-a, --preset-b <nome>  Preset de cada lado (padrão: normal)
+  --pack-a, --pack-b <nome>      Pacote de domínio ligado em cada lado (repetível)
+  --variant-a, --variant-b <norma>  Norma ortográfica de cada lado (padrão: pt_br)
+  --corpus <arq>     Corpus anotado (vitórias/derrotas pelo esperado)
+  --words <arq>      Alternativa: lista de palavras sem saída esperada
+  --json <arq>       Relatório JSON (padrão: ab_report.json; '-' = stdout)
+
+explain: mostra cada etapa do pipeline visitada para corrigir a palavra.
+  --data <dir>       Diretório com os dados (padrão: data)
+  --preset <nome>    Preset usado (padrão: normal)
+  --pack <nome>      Liga um pacote de domínio (repetível)
+  --variant <norma>  pt_br | pt_pt (padrão: pt_br)
+  --json <arq>       Grava os traces em JSON ('-' = stdout)
+
+convert: reescreve um texto de uma norma ortográfica para a outra
+(contato ↔ contacto, econômico ↔ económico...), sem outras correções.
+  --to <norma>       Norma de destino: pt_br | pt_pt
+  --input <arq>      Texto de entrada (padrão: stdin)
+  --output <arq>     Texto convertido (padrão: stdout)
+";
+
+/// Argumentos no formato `--chave valor`, na ordem em que aparecem,
+/// mais os argumentos posicionais.
+struct Args {
+    pairs: Vec<(String, String)>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    fn parse(raw: &[String]) -> Result<Self, String> {
+        let mut pairs = Vec::new();
+        let mut positional = Vec::new();
+        let mut iter = raw.iter();
+        while let Some(key) = iter.next() {
+            let Some(name) = key.strip_prefix("--") else {
+                positional.push(key.clone());
+                continue;
+            };
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("faltou o valor de --{}", name))?;
+            pairs.push((name.to_string(), value.clone()));
+        }
+        Ok(Self { pairs, positional })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .rev()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn all(&self, name: &str) -> Vec<&str> {
+        self.pairs
+            .iter()
+            .filter(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+}
+
+fn main() -> ExitCode {
+    let mut raw: Vec<String> = std::env::args().skip(1).collect();
+    if raw.iter().any(|a| a == "-h" || a == "--help") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let command = match raw.first().map(String::as_str) {
+        Some(cmd) if !cmd.starts_with("--") => raw.remove(0),
+        _ => "run".to_string(),
+    };
+
+    let result = Args::parse(&raw).and_then(|args| match command.as_str() {
+        _ if command != "explain" && !args.positional.is_empty() => {
+            Err(format!("argumento inesperado: {}", args.positional[0]))
+        }
+        "run" => cmd_run(&args),
+        "gen" => cmd_gen(&args),
+        "ab" => cmd_ab(&args),
+        "explain" => cmd_explain(&args),
+        "convert" => cmd_convert(&args),
+        other => Err(format!("subcomando desconhecido: {}", other)),
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("draco_eval: {}\n\n{}", e, USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn cmd_run(args: &Args) -> Result<(), String> {
+    let data_dir = PathBuf::from(args.get("data").unwrap_or("data"));
+    let corpus_path = args
+        .get("corpus")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| data_dir.join("eval").join("corpus_pt_br.tsv"));
+
+    let presets = match args.all("preset").as_slice() {
+        [] => Preset::ALL.to_vec(),
+        names => names
+            .iter()
+            .map(|n| Preset::from_name(n).ok_or_else(|| format!("preset desconhecido: {}", n)))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    let show_misses: usize = match args.get("misses") {
+        Some(n) => n.parse().map_err(|_| format!("--misses inválido: {}", n))?,
+        None => 0,
+    };
+
+    let corpus = Corpus::load(&corpus_path)
+        .map_err(|e| format!("falha ao ler corpus {:?}: {}", corpus_path, e))?;
+    let variant = parse_variant(args.get("variant"))?;
+    let engine = engine::load_engine(&data_dir, &args.all("pack"), variant)?;
+
+    let report = EvalReport {
+        data_dir: data_dir.display().to_string(),
+        corpus: corpus_path.display().to_string(),
+        typo_items: corpus.typo_count(),
+        clean_items: corpus.clean_count(),
+        presets: presets
+            .into_iter()
+            .map(|p| metrics::evaluate(&engine, &corpus, p))
+            .collect(),
+    };
+
+    print!("{}", report);
+    if show_misses > 0 {
+        for r in &report.presets {
+            println!("\nErros ({}):", r.preset.name());
+            for m in r.misses.iter().take(show_misses) {
+                println!(
+                    "  linha {:>4}: {} → {} (esperado: {})",
+                    m.line, m.input, m.output, m.expected
+                );
+            }
+        }
+    }
+
+    let json = report.to_json().map_err(|e| e.to_string())?;
+    match args.get("json").unwrap_or("eval_report.json") {
+        "-" => println!("{}", json),
+        path => {
+            std::fs::write(path, json).map_err(|e| format!("falha ao gravar {}: {}", path, e))?
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_gen(args: &Args) -> Result<(), String> {
+    let input = args.get("input").ok_or("gen exige --input")?;
+    let seed: u64 = match args.get("seed") {
+        Some(n) => n.parse().map_err(|_| format!("--seed inválido: {}", n))?,
+        None => 1,
+    };
+
+    let mut rates = ErrorRates::default();
+    for spec in args.all("rate") {
+        let (name, value) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("--rate espera tipo=p, recebeu {}", spec))?;
+        let kind = ErrorKind::from_name(name)
+            .ok_or_else(|| format!("tipo de erro desconhecido: {}", name))?;
+        let rate: f64 = value
+            .parse()
+            .map_err(|_| format!("taxa inválida em --rate {}", spec))?;
+        rates.set(kind, rate);
+    }
+
+    let text =
+        std::fs::read_to_string(input).map_err(|e| format!("falha ao ler {}: {}", input, e))?;
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# Corpus sintético gerado por draco_eval gen");
+    let _ = writeln!(out, "# entrada: {}  semente: {}", input, seed);
+    let _ = writeln!(
+        out,
+        "# taxas: {}",
+        ErrorKind::ALL
+            .iter()
+            .map(|&k| format!("{}={}", k.name(), rates.get(k)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+
+    let mut generator = TypoGenerator::new(seed, rates);
+    let mut clean_lines = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (pairs, clean) = generator.corrupt_line(line);
+        for (typo, expected, kind) in pairs {
+            let _ = writeln!(out, "{}\t{}\t{}", typo, expected, kind.name());
+        }
+        if !clean.is_empty() {
+            clean_lines.push(clean.join(" "));
+        }
+    }
+
+    // Palavras que ficaram intactas entram como texto limpo (falsos positivos)
+    if !clean_lines.is_empty() {
+        let _ = writeln!(out, "\n# Texto limpo");
+        for line in clean_lines {
+            let _ = writeln!(out, "{}", line);
+        }
+    }
+
+    match args.get("output") {
+        Some(path) => {
+            std::fs::write(path, out).map_err(|e| format!("falha ao gravar {}: {}", path, e))?
+        }
+        None => print!("{}", out),
+    }
+    Ok(())
+}
+
+fn cmd_ab(args: &Args) -> Result<(), String> {
+    let dir_a = PathBuf::from(args.get("a").ok_or("ab exige --a")?);
+    let dir_b = PathBuf::from(args.get("b").ok_or("ab exige --b")?);
+    let preset = |name: &str| -> Result<Preset, String> {
+        let value = args.get(name).unwrap_or("normal");
+        Preset::from_name(value).ok_or_else(|| format!("preset desconhecido: {}", value))
+    };
+    let (preset_a, preset_b) = (preset("preset-a")?, preset("preset-b")?);
+
+    let inputs: Vec<(String, Option<String>)> = match (args.get("corpus"), args.get("words")) {
+        (Some(path), _) => Corpus::load(path)
+            .map_err(|e| format!("falha ao ler corpus {}: {}", path, e))?
+            .items
+            .into_iter()
+            .map(|item| (item.input, Some(item.expected)))
+            .collect(),
+        (None, Some(path)) => draco_brain::dict_loader::load_from_file(path)
+            .map_err(|e| format!("falha ao ler {}: {}", path, e))?
+            .into_iter()
+            .map(|word| (word, None))
+            .collect(),
+        (None, None) => return Err("ab exige --corpus ou --words".into()),
+    };
+
+    let (packs_a, packs_b) = (args.all("pack-a"), args.all("pack-b"));
+    let variant_a = parse_variant(args.get("variant-a"))?;
+    let variant_b = parse_variant(args.get("variant-b"))?;
+    let engine_a = engine::load_engine(&dir_a, &packs_a, variant_a)?;
+    let engine_b = engine::load_engine(&dir_b, &packs_b, variant_b)?;
+    let label = |dir: &PathBuf, preset: Preset, variant: Variant, packs: &[&str]| {
+        let mut label = format!("{} ({}", dir.display(), preset.name());
+        if variant != Variant::default() {
+            let _ = write!(label, " {}", variant.as_str());
+        }
+        for pack in packs {
+            let _ = write!(label, " +{}", pack);
+        }
+        label + ")"
+    };
+    let side_a = ab::Side {
+        label: label(&dir_a, preset_a, variant_a, &packs_a),
+        engine: &engine_a,
+        aggressiveness: preset_a.aggressiveness(),
+    };
+    let side_b = ab::Side {
+        label: label(&dir_b, preset_b, variant_b, &packs_b),
+        engine: &engine_b,
+        aggressiveness: preset_b.aggressiveness(),
+    };
+
+    let report = ab::compare(&side_a, &side_b, &inputs);
+    print!("{}", report);
+
+    let json = report.to_json().map_err(|e| e.to_string())?;
+    match args.get("json").unwrap_or("ab_report.json") {
+        "-" => println!("{}", json),
+        path => {
+            std::fs::write(path, json).map_err(|e| format!("falha ao gravar {}: {}", path, e))?
+        }
+    }
+    Ok(())
+}
+
+fn cmd_explain(args: &Args) -> Result<(), String> {
+    if args.positional.is_empty() {
+        return Err("explain exige ao menos uma palavra".into());
+    }
+    let data_dir = PathBuf::from(args.get("data").unwrap_or("data"));
+    let name = args.get("preset").unwrap_or("normal");
+    let preset = Preset::from_name(name).ok_or_else(|| format!("preset desconhecido: {}", name))?;
+
+    let variant = parse_variant(args.get("variant"))?;
+    let engine = engine::load_engine(&data_dir, &args.all("pack"), variant)?;
+    let traces: Vec<_> = args
+        .positional
+        .iter()
+        .map(|word| engine.explain(word, preset.aggressiveness()))
+        .collect();
+
+    for trace in &traces {
+        println!("{}", trace);
+    }
+
+    if let Some(target) = args.get("json") {
+        let json = serde_json::to_string_pretty(&traces).map_err(|e| e.to_string())?;
+        match target {
+            "-" => println!("{}", json),
+            path => std::fs::write(path, json)
+                .map_err(|e| format!("falha ao gravar {}: {}", path, e))?,
+        }
+    }
+    Ok(())
+}
+
+fn cmd_convert(args: &Args) -> Result<(), String> {
+    let target = args.get("to").ok_or("convert exige --to")?;
+    let target = parse_variant(Some(target))?;
+    let text = match args.get("input") {
+        Some(path) => {
+            std::fs::read_to_string(path).map_err(|e| format!("falha ao ler {}: {}", path, e))?
+        }
+        None => {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| format!("falha ao ler stdin: {}", e))?;
+            text
+        }
+    };
+
+    let converted = variant::convert(&text, target);
+    match args.get("output") {
+        Some(path) => std::fs::write(path, converted)
+            .map_err(|e| format!("falha ao gravar {}: {}", path, e))?,
+        None => print!("{}", converted),
+    }
+    Ok(())
+}
+
+fn parse_variant(name: Option<&str>) -> Result<Variant, String> {
+    match name {
+        None => Ok(Variant::default()),
+        Some(name) => {
+            Variant::from_name(name).ok_or_else(|| format!("norma desconhecida: {}", name))
+        }
+    }
+}
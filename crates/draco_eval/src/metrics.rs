@@ -0,0 +1,206 @@
+use crate::corpus::Corpus;
+use draco_brain::stage_a::StageA;
+use serde::Serialize;
+use std::time::Instant;
+
+/// Presets de política do `StageA` (nível de agressividade do IPC).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    Conservador,
+    Normal,
+    Agressivo,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Conservador, Preset::Normal, Preset::Agressivo];
+
+    pub fn aggressiveness(self) -> u32 {
+        match self {
+            Preset::Conservador => 0,
+            Preset::Normal => 1,
+            Preset::Agressivo => 2,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Conservador => "conservador",
+            Preset::Normal => "normal",
+            Preset::Agressivo => "agressivo",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.name() == name)
+    }
+}
+
+/// Contagens brutas de uma rodada.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Counts {
+    /// Erros corrigidos para a forma esperada.
+    pub corrected: usize,
+    /// Erros alterados para uma forma diferente da esperada.
+    pub wrong: usize,
+    /// Erros devolvidos sem alteração.
+    pub abstained: usize,
+    /// Palavras corretas preservadas.
+    pub clean_kept: usize,
+    /// Palavras corretas alteradas (falsos positivos).
+    pub false_positives: usize,
+}
+
+/// Percentis de latência por chamada de `correct`, em microssegundos.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Latency {
+    pub mean_us: f64,
+    pub p50_us: f64,
+    pub p90_us: f64,
+    pub p99_us: f64,
+    pub max_us: f64,
+}
+
+impl Latency {
+    fn from_samples(mut samples: Vec<f64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let mean_us = samples.iter().sum::<f64>() / samples.len() as f64;
+        Self {
+            mean_us,
+            p50_us: percentile(&samples, 50.0),
+            p90_us: percentile(&samples, 90.0),
+            p99_us: percentile(&samples, 99.0),
+            max_us: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Percentil pelo método nearest-rank (amostras já ordenadas).
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Um caso em que a saída não bateu com a esperada.
+#[derive(Debug, Clone, Serialize)]
+pub struct Miss {
+    pub line: usize,
+    pub input: String,
+    pub expected: String,
+    pub output: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+/// Resultado de um preset sobre o corpus inteiro.
+#[derive(Debug, Clone, Serialize)]
+pub struct PresetReport {
+    pub preset: Preset,
+    pub aggressiveness: u32,
+    pub counts: Counts,
+    /// Acertos (erros corrigidos + corretas preservadas) / total.
+    pub accuracy: f64,
+    /// Erros corrigidos / todas as alterações feitas.
+    pub precision: f64,
+    /// Erros corrigidos / total de erros.
+    pub recall: f64,
+    /// Corretas alteradas / total de corretas.
+    pub false_positive_rate: f64,
+    /// Erros devolvidos sem alteração / total de erros.
+    pub abstention_rate: f64,
+    pub latency: Latency,
+    pub misses: Vec<Miss>,
+}
+
+fn ratio(num: usize, den: usize) -> f64 {
+    if den == 0 {
+        0.0
+    } else {
+        num as f64 / den as f64
+    }
+}
+
+/// Executa o corpus inteiro pelo `StageA` com o preset dado.
+pub fn evaluate(engine: &StageA, corpus: &Corpus, preset: Preset) -> PresetReport {
+    let agg = preset.aggressiveness();
+    let mut counts = Counts::default();
+    let mut samples = Vec::with_capacity(corpus.items.len());
+    let mut misses = Vec::new();
+
+    for item in &corpus.items {
+        let start = Instant::now();
+        let output = engine.correct(&item.input, agg);
+        samples.push(start.elapsed().as_secs_f64() * 1_000_000.0);
+
+        match (
+            item.is_typo(),
+            output == item.expected,
+            output == item.input,
+        ) {
+            (true, true, _) => counts.corrected += 1,
+            (true, false, true) => counts.abstained += 1,
+            (true, false, false) => counts.wrong += 1,
+            (false, true, _) => counts.clean_kept += 1,
+            (false, false, _) => counts.false_positives += 1,
+        }
+
+        if output != item.expected {
+            misses.push(Miss {
+                line: item.line,
+                input: item.input.clone(),
+                expected: item.expected.clone(),
+                output,
+                tag: item.tag.clone(),
+            });
+        }
+    }
+
+    let typos = counts.corrected + counts.wrong + counts.abstained;
+    let clean = counts.clean_kept + counts.false_positives;
+    let changes = counts.corrected + counts.wrong + counts.false_positives;
+
+    PresetReport {
+        preset,
+        aggressiveness: agg,
+        accuracy: ratio(counts.corrected + counts.clean_kept, typos + clean),
+        precision: ratio(counts.corrected, changes),
+        recall: ratio(counts.corrected, typos),
+        false_positive_rate: ratio(counts.false_positives, clean),
+        abstention_rate: ratio(counts.abstained, typos),
+        latency: Latency::from_samples(samples),
+        counts,
+        misses,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_each_outcome_once() {
+        let mut engine = StageA::new();
+        engine.load_dictionary(&["casa", "carro", "você"]);
+        let corpus = Corpus::parse("cassa\tcasa\nxyzw\tcarro\ncasa carro\n");
+
+        let report = evaluate(&engine, &corpus, Preset::Normal);
+
+        assert_eq!(report.counts.corrected, 1);
+        assert_eq!(report.counts.abstained, 1);
+        assert_eq!(report.counts.clean_kept, 2);
+        assert_eq!(report.recall, 0.5);
+        assert_eq!(report.false_positive_rate, 0.0);
+        assert_eq!(report.misses.len(), 1);
+    }
+
+    #[test]
+    fn nearest_rank_percentile() {
+        let samples: Vec<f64> = (1..=100).map(|v| v as f64).collect();
+        assert_eq!(percentile(&samples, 50.0), 50.0);
+        assert_eq!(percentile(&samples, 99.0), 99.0);
+        assert_eq!(percentile(&[7.0], 90.0), 7.0);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::metrics::PresetReport;
+use serde::Serialize;
+use std::fmt;
+
+/// Relatório completo de uma rodada de avaliação (serializado em JSON
+/// para comparar execuções diferentes).
+#[derive(Debug, Serialize)]
+pub struct EvalReport {
+    pub data_dir: String,
+    pub corpus: String,
+    pub typo_items: usize,
+    pub clean_items: usize,
+    pub presets: Vec<PresetReport>,
+}
+
+impl EvalReport {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+fn pct(v: f64) -> String {
+    format!("{:.1}%", v * 100.0)
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Corpus: {}", self.corpus)?;
+        writeln!(f, "Dados:  {}", self.data_dir)?;
+        writeln!(
+            f,
+            "Itens:  {} com erro, {} corretos",
+            self.typo_items, self.clean_items
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<12} {:>8} {:>8} {:>8} {:>8} {:>8} {:>9} {:>9} {:>9}",
+            "preset",
+            "acurácia",
+            "precisão",
+            "recall",
+            "FP",
+            "abstém",
+            "p50 µs",
+            "p99 µs",
+            "máx µs"
+        )?;
+        writeln!(f, "{}", "-".repeat(89))?;
+        for r in &self.presets {
+            writeln!(
+                f,
+                "{:<12} {:>8} {:>8} {:>8} {:>8} {:>8} {:>9.1} {:>9.1} {:>9.1}",
+                r.preset.name(),
+                pct(r.accuracy),
+                pct(r.precision),
+                pct(r.recall),
+                pct(r.false_positive_rate),
+                pct(r.abstention_rate),
+                r.latency.p50_us,
+                r.latency.p99_us,
+                r.latency.max_us,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,409 @@
+use draco_brain::phonetic::PhoneticNormalizer;
+use draco_brain::typo_model::CHAT_ABBREVIATIONS;
+use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Gerador pseudoaleatório SplitMix64: pequeno, rápido e reprodutível
+/// (a mesma semente sempre gera o mesmo corpus, em qualquer plataforma).
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Número uniforme em [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Índice uniforme em [0, n). `n` deve ser > 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// Tipos de erro injetados.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Tecla vizinha no teclado ABNT2 (casa → csaa, xasa)
+    Neighbor,
+    /// Acentos ou cedilha esquecidos (você → voce, começar → comecar)
+    Accent,
+    /// Letra dobrada (casa → cassa)
+    Doubled,
+    /// Letra faltando (carro → caro)
+    Missing,
+    /// Letras adjacentes trocadas (trabalho → tarbalho)
+    Transposition,
+    /// Troca fonética das regras do `PhoneticNormalizer` (pesquisa → pesquiza)
+    Phonetic,
+    /// Abreviação de chat (você → vc)
+    Abbreviation,
+}
+
+impl ErrorKind {
+    pub const ALL: [ErrorKind; 7] = [
+        ErrorKind::Neighbor,
+        ErrorKind::Accent,
+        ErrorKind::Doubled,
+        ErrorKind::Missing,
+        ErrorKind::Transposition,
+        ErrorKind::Phonetic,
+        ErrorKind::Abbreviation,
+    ];
+
+    /// Nome usado na terceira coluna do corpus e nas opções da CLI.
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Neighbor => "vizinha",
+            ErrorKind::Accent => "acento",
+            ErrorKind::Doubled => "dobrada",
+            ErrorKind::Missing => "faltando",
+            ErrorKind::Transposition => "transposicao",
+            ErrorKind::Phonetic => "fonetica",
+            ErrorKind::Abbreviation => "abreviacao",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == name)
+    }
+}
+
+/// Probabilidade, por palavra, de cada tipo de erro.
+/// A soma é a chance de uma palavra receber algum erro (limitada a 1).
+#[derive(Debug, Clone)]
+pub struct ErrorRates {
+    rates: [f64; 7],
+}
+
+impl Default for ErrorRates {
+    fn default() -> Self {
+        Self {
+            rates: [0.04, 0.05, 0.02, 0.03, 0.03, 0.02, 0.01],
+        }
+    }
+}
+
+impl ErrorRates {
+    pub fn get(&self, kind: ErrorKind) -> f64 {
+        self.rates[kind as usize]
+    }
+
+    pub fn set(&mut self, kind: ErrorKind, rate: f64) {
+        self.rates[kind as usize] = rate.clamp(0.0, 1.0);
+    }
+}
+
+/// Linhas do teclado ABNT2 (apenas as teclas de letras).
+const ABNT2_ROWS: [&str; 3] = ["qwertyuiop", "asdfghjklç", "zxcvbnm"];
+
+/// Injeta erros realistas de digitação PT-BR em texto limpo.
+pub struct TypoGenerator {
+    rng: Rng,
+    rates: ErrorRates,
+    neighbors: HashMap<char, Vec<char>>,
+    phonetic_rules: Vec<(String, Vec<String>)>,
+    abbreviations: HashMap<&'static str, Vec<&'static str>>,
+}
+
+impl TypoGenerator {
+    pub fn new(seed: u64, rates: ErrorRates) -> Self {
+        let mut abbreviations: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (abbr, full) in CHAT_ABBREVIATIONS {
+            abbreviations.entry(full).or_default().push(abbr);
+        }
+
+        Self {
+            rng: Rng::new(seed),
+            rates,
+            neighbors: abnt2_neighbors(),
+            phonetic_rules: PhoneticNormalizer::get_sibilant_rules(),
+            abbreviations,
+        }
+    }
+
+    /// Tenta corromper uma palavra. Retorna `None` quando a palavra é
+    /// sorteada para ficar intacta ou quando nenhum erro se aplica a ela.
+    pub fn corrupt_word(&mut self, word: &str) -> Option<(String, ErrorKind)> {
+        if word.chars().count() < 2 || !word.chars().all(char::is_alphabetic) {
+            return None;
+        }
+
+        // Sorteio do tipo de erro proporcional às taxas
+        let roll = self.rng.next_f64();
+        let mut acc = 0.0;
+        let chosen = ErrorKind::ALL.into_iter().find(|&kind| {
+            acc += self.rates.get(kind);
+            roll < acc
+        })?;
+
+        if let Some(typo) = self.apply(word, chosen) {
+            return Some((typo, chosen));
+        }
+
+        // O tipo sorteado não se aplica (ex.: acento numa palavra sem acento):
+        // tenta os demais tipos com taxa > 0, a partir de um ponto aleatório.
+        let offset = self.rng.below(ErrorKind::ALL.len());
+        for i in 0..ErrorKind::ALL.len() {
+            let kind = ErrorKind::ALL[(offset + i) % ErrorKind::ALL.len()];
+            if kind == chosen || self.rates.get(kind) <= 0.0 {
+                continue;
+            }
+            if let Some(typo) = self.apply(word, kind) {
+                return Some((typo, kind));
+            }
+        }
+        None
+    }
+
+    /// Aplica um tipo específico de erro. `None` se não se aplica à palavra.
+    pub fn apply(&mut self, word: &str, kind: ErrorKind) -> Option<String> {
+        let chars: Vec<char> = word.chars().collect();
+        let typo = match kind {
+            ErrorKind::Neighbor => {
+                let positions: Vec<usize> = (0..chars.len())
+                    .filter(|&i| self.neighbors.contains_key(&lower(chars[i])))
+                    .collect();
+                if positions.is_empty() {
+                    return None;
+                }
+                let pos = positions[self.rng.below(positions.len())];
+                let options = &self.neighbors[&lower(chars[pos])];
+                let replacement = options[self.rng.below(options.len())];
+                let mut out = chars.clone();
+                out[pos] = match_case(replacement, chars[pos]);
+                out.into_iter().collect()
+            }
+            ErrorKind::Accent => chars.iter().map(|&c| strip_diacritic(c)).collect(),
+            ErrorKind::Doubled => {
+                let pos = self.rng.below(chars.len());
+                let mut out = chars.clone();
+                out.insert(pos, chars[pos]);
+                out.into_iter().collect()
+            }
+            ErrorKind::Missing => {
+                if chars.len() < 3 {
+                    return None;
+                }
+                let pos = self.rng.below(chars.len());
+                let mut out = chars.clone();
+                out.remove(pos);
+                out.into_iter().collect()
+            }
+            ErrorKind::Transposition => {
+                let positions: Vec<usize> = (0..chars.len() - 1)
+                    .filter(|&i| chars[i] != chars[i + 1])
+                    .collect();
+                if positions.is_empty() {
+                    return None;
+                }
+                let pos = positions[self.rng.below(positions.len())];
+                let mut out = chars.clone();
+                out.swap(pos, pos + 1);
+                out.into_iter().collect()
+            }
+            ErrorKind::Phonetic => {
+                let lowered = word.to_lowercase();
+                let applicable: Vec<usize> = (0..self.phonetic_rules.len())
+                    .filter(|&i| lowered.contains(self.phonetic_rules[i].0.as_str()))
+                    .collect();
+                if applicable.is_empty() {
+                    return None;
+                }
+                let (from, alternatives) =
+                    &self.phonetic_rules[applicable[self.rng.below(applicable.len())]];
+                let alt = &alternatives[self.rng.below(alternatives.len())];
+                // Troca apenas uma ocorrência, escolhida ao acaso
+                let hits: Vec<usize> = lowered
+                    .match_indices(from.as_str())
+                    .map(|(i, _)| i)
+                    .collect();
+                let at = hits[self.rng.below(hits.len())];
+                let typo = format!("{}{}{}", &lowered[..at], alt, &lowered[at + from.len()..]);
+                restore_case(&typo, word)
+            }
+            ErrorKind::Abbreviation => {
+                let options = self.abbreviations.get(word.to_lowercase().as_str())?;
+                restore_case(options[self.rng.below(options.len())], word)
+            }
+        };
+
+        (typo != word).then_some(typo)
+    }
+
+    /// Processa uma linha de texto limpo. Retorna os pares (erro, esperado, tipo)
+    /// gerados e as palavras que ficaram intactas, na ordem original.
+    pub fn corrupt_line(&mut self, line: &str) -> (Vec<(String, String, ErrorKind)>, Vec<String>) {
+        let mut pairs = Vec::new();
+        let mut clean = Vec::new();
+        for word in line.unicode_words() {
+            match self.corrupt_word(word) {
+                Some((typo, kind)) => pairs.push((typo, word.to_string(), kind)),
+                None => clean.push(word.to_string()),
+            }
+        }
+        (pairs, clean)
+    }
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn match_case(c: char, like: char) -> char {
+    if like.is_uppercase() {
+        c.to_uppercase().next().unwrap_or(c)
+    } else {
+        c
+    }
+}
+
+/// Aplica a caixa de `like` (toda maiúscula ou só a inicial) a `word`, em
+/// minúsculas. Assim o par gerado difere do esperado só pelo erro.
+fn restore_case(word: &str, like: &str) -> String {
+    let mut letters = like.chars().filter(|c| c.is_alphabetic());
+    let first_upper = letters.next().is_some_and(char::is_uppercase);
+    if first_upper && letters.clone().next().is_some() && letters.all(char::is_uppercase) {
+        return word.to_uppercase();
+    }
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) if first_upper => first.to_uppercase().chain(chars).collect(),
+        _ => word.to_string(),
+    }
+}
+
+fn strip_diacritic(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' => 'a',
+        'é' | 'ê' => 'e',
+        'í' => 'i',
+        'ó' | 'ô' | 'õ' => 'o',
+        'ú' | 'ü' => 'u',
+        'ç' => 'c',
+        'Á' | 'À' | 'Â' | 'Ã' => 'A',
+        'É' | 'Ê' => 'E',
+        'Í' => 'I',
+        'Ó' | 'Ô' | 'Õ' => 'O',
+        'Ú' | 'Ü' => 'U',
+        'Ç' => 'C',
+        other => other,
+    }
+}
+
+/// Vizinhança física de cada letra no ABNT2: mesma linha (±1) e as teclas
+/// adjacentes das linhas de cima e de baixo (que são deslocadas à direita).
+fn abnt2_neighbors() -> HashMap<char, Vec<char>> {
+    let rows: Vec<Vec<char>> = ABNT2_ROWS.iter().map(|r| r.chars().collect()).collect();
+    let mut map = HashMap::new();
+
+    for (r, row) in rows.iter().enumerate() {
+        for (i, &c) in row.iter().enumerate() {
+            let mut near = Vec::new();
+            let mut push = |row: &Vec<char>, idx: isize| {
+                if idx >= 0 && (idx as usize) < row.len() {
+                    near.push(row[idx as usize]);
+                }
+            };
+            let i = i as isize;
+            push(row, i - 1);
+            push(row, i + 1);
+            if r > 0 {
+                push(&rows[r - 1], i);
+                push(&rows[r - 1], i + 1);
+            }
+            if r + 1 < rows.len() {
+                push(&rows[r + 1], i - 1);
+                push(&rows[r + 1], i);
+            }
+            map.insert(c, near);
+        }
+    }
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn only(kind: ErrorKind) -> ErrorRates {
+        let mut rates = ErrorRates { rates: [0.0; 7] };
+        rates.set(kind, 1.0);
+        rates
+    }
+
+    #[test]
+    fn same_seed_same_output() {
+        let text = "O menino foi para a escola e você também";
+        let mut a = TypoGenerator::new(42, ErrorRates::default());
+        let mut b = TypoGenerator::new(42, ErrorRates::default());
+        for _ in 0..20 {
+            assert_eq!(a.corrupt_line(text), b.corrupt_line(text));
+        }
+    }
+
+    #[test]
+    fn each_kind_produces_its_error() {
+        let mut g = TypoGenerator::new(7, only(ErrorKind::Accent));
+        assert_eq!(
+            g.corrupt_word("começar"),
+            Some(("comecar".into(), ErrorKind::Accent))
+        );
+
+        let mut g = TypoGenerator::new(7, only(ErrorKind::Abbreviation));
+        let (typo, _) = g.corrupt_word("também").unwrap();
+        assert!(["tb", "tbm", "tmb"].contains(&typo.as_str()));
+
+        // A caixa do original se mantém
+        let (typo, _) = g.corrupt_word("Também").unwrap();
+        assert!(["Tb", "Tbm", "Tmb"].contains(&typo.as_str()));
+        let (typo, _) = g.corrupt_word("TAMBÉM").unwrap();
+        assert!(["TB", "TBM", "TMB"].contains(&typo.as_str()));
+
+        let mut g = TypoGenerator::new(7, only(ErrorKind::Phonetic));
+        let (typo, _) = g.corrupt_word("Cassino").unwrap();
+        assert!(typo.starts_with('C') && typo[1..].chars().all(char::is_lowercase));
+        let (typo, _) = g.corrupt_word("CASSINO").unwrap();
+        assert_eq!(typo, typo.to_uppercase());
+        assert_ne!(typo, "CASSINO");
+
+        let mut g = TypoGenerator::new(7, only(ErrorKind::Neighbor));
+        let (typo, _) = g.corrupt_word("casa").unwrap();
+        assert_eq!(typo.chars().count(), 4);
+        let diff: Vec<(char, char)> = typo
+            .chars()
+            .zip("casa".chars())
+            .filter(|(a, b)| a != b)
+            .collect();
+        assert_eq!(diff.len(), 1);
+        assert!(abnt2_neighbors()[&diff[0].1].contains(&diff[0].0));
+
+        let mut g = TypoGenerator::new(7, only(ErrorKind::Transposition));
+        let (typo, _) = g.corrupt_word("casa").unwrap();
+        let mut sorted: Vec<char> = typo.chars().collect();
+        sorted.sort();
+        assert_eq!(sorted, vec!['a', 'a', 'c', 's']);
+    }
+
+    #[test]
+    fn falls_back_when_kind_does_not_apply() {
+        let mut rates = only(ErrorKind::Accent);
+        rates.set(ErrorKind::Doubled, 0.5);
+        let mut g = TypoGenerator::new(3, rates);
+        // "casa" não tem acento: o gerador recorre à letra dobrada
+        let (typo, kind) = g.corrupt_word("casa").unwrap();
+        assert_eq!(kind, ErrorKind::Doubled);
+        assert_eq!(typo.chars().count(), 5);
+    }
+}
@@ -1,6 +1,7 @@
-use draco_brain::stage_a::StageA;
+use draco_brain::shared::SharedEngine;
 use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use windows::Win32::Foundation::*;
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
@@ -17,8 +18,8 @@ static IS_CORRECTING: AtomicBool = AtomicBool::new(false);
 /// Handle do hook
 static HOOK_HANDLE: AtomicIsize = AtomicIsize::new(0);
 
-/// Engine de correção (thread-safe)
-static ENGINE: OnceLock<Arc<Mutex<StageA>>> = OnceLock::new();
+/// Engine de correção (snapshots imutáveis, leitura sem lock)
+static ENGINE: OnceLock<Arc<SharedEngine>> = OnceLock::new();
 
 /// Buffer de composição (thread-safe)
 static BUFFER: OnceLock<Arc<Mutex<String>>> = OnceLock::new();
@@ -29,9 +30,14 @@ static CHAR_COUNT: OnceLock<Arc<Mutex<usize>>> = OnceLock::new();
 /// Flag LLKHF_INJECTED
 const LLKHF_INJECTED: u32 = 0x00000010;
 
-/// Inicializa o engine de correção.
-pub fn init_engine(engine: StageA) {
-    let _ = ENGINE.set(Arc::new(Mutex::new(engine)));
+/// Tempo para o espaço digitado chegar ao aplicativo antes dos backspaces.
+/// Corre junto com a busca (ver [`KEYSTROKE_SEARCH_TIMEOUT`](draco_brain::stage_a::KEYSTROKE_SEARCH_TIMEOUT)):
+/// só o que sobrar dele é esperado, e só quando há correção a digitar.
+const KEY_SETTLE: Duration = Duration::from_millis(30);
+
+/// Inicializa o engine de correção (compartilhado com o vigia de dados).
+pub fn init_engine(engine: Arc<SharedEngine>) {
+    let _ = ENGINE.set(engine);
     let _ = BUFFER.set(Arc::new(Mutex::new(String::new())));
     let _ = CHAR_COUNT.set(Arc::new(Mutex::new(0)));
 }
@@ -138,16 +144,12 @@ fn spawn_correction(word: String, char_count: usize) {
     };
 
     std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_millis(30));
-
+        let started = Instant::now();
         let agg = AGGRESSIVENESS.load(Ordering::SeqCst);
-        let corrected = if let Ok(engine) = engine_arc.lock() {
-            engine.correct(&word, agg)
-        } else {
-            return;
-        };
+        let corrected = engine_arc.correct(&word, agg);
 
         if corrected != word {
+            std::thread::sleep(KEY_SETTLE.saturating_sub(started.elapsed()));
             IS_CORRECTING.store(true, Ordering::SeqCst);
 
             unsafe {
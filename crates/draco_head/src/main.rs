@@ -3,6 +3,9 @@
 mod keyboard_hook;
 mod tray;
 
+use draco_brain::shared::SharedEngine;
+use draco_brain::watcher::{DataWatcher, DEFAULT_POLL_INTERVAL};
+use std::sync::Arc;
 use tray::*;
 use windows::core::*;
 use windows::Win32::Foundation::*;
@@ -10,41 +13,38 @@ use windows::Win32::System::LibraryLoader::GetModuleHandleW;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
 fn main() -> Result<()> {
-    // 1. Inicializar engine de correção
-    let mut engine = draco_brain::stage_a::StageA::new();
-
-    // 2. Carregar dados de FREQUÊNCIA primeiro (para que o dicionário já tenha os ranks)
-    let freq_path = resolve_freq_path();
-    if freq_path.exists() {
-        if let Ok(entries) = draco_brain::dict_loader::load_frequency_file(&freq_path) {
-            eprintln!(
-                "[IME] Frequências carregadas: {} palavras de {:?}",
-                entries.len(),
-                freq_path
-            );
-            engine.load_frequency_data(&entries);
+    // 1-3. Inicializar engine de correção: localiza os dados (config.json,
+    // DRACO_DATA_DIR, pasta do usuário, junto ao .exe) e carrega frequências + dicionário
+    let builder = match draco_brain::EngineBuilder::from_environment() {
+        Ok(builder) => Some(builder.keystroke_defaults()),
+        Err(e) => {
+            eprintln!("[IME] Erro: {}", e);
+            None
         }
-    }
-
-    // 3. Carregar dicionário
-    let dict_path = resolve_dict_path();
-    if dict_path.exists() {
-        if let Ok(words) = draco_brain::dict_loader::load_from_file(&dict_path) {
-            engine.load_dictionary_strings(&words);
-            eprintln!(
-                "[IME] Dicionário carregado: {} palavras de {:?}",
-                words.len(),
-                dict_path
-            );
-        } else {
-            eprintln!("[IME] Erro ao carregar dicionário de {:?}", dict_path);
+    };
+    let engine = match builder.as_ref().map(|b| b.build()) {
+        Some(Ok((engine, report))) => {
+            for line in report.to_string().lines() {
+                eprintln!("[IME] {}", line);
+            }
+            engine
         }
-    } else {
-        eprintln!("[IME] Dicionário não encontrado em {:?}", dict_path);
-    }
-
-    // 4. Inicializar o engine no hook de teclado
-    keyboard_hook::init_engine(engine);
+        Some(Err(e)) => {
+            eprintln!("[IME] Erro: {}", e);
+            draco_brain::stage_a::StageA::new()
+        }
+        None => draco_brain::stage_a::StageA::new(),
+    };
+    let engine = Arc::new(SharedEngine::new(engine));
+
+    // 4. Inicializar o engine no hook de teclado e vigiar os arquivos de dados:
+    // editar o dicionário ou as regras recarrega sem reiniciar o app
+    keyboard_hook::init_engine(Arc::clone(&engine));
+    let _watcher = builder.map(|builder| {
+        DataWatcher::spawn(builder, engine, DEFAULT_POLL_INTERVAL, |event| {
+            eprintln!("[IME] {}", event)
+        })
+    });
 
     // 5. Instalar hook global de teclado
     keyboard_hook::start_hook()?;
@@ -148,54 +148,3 @@ unsafe extern "system" fn window_proc(
         _ => DefWindowProcW(hwnd, msg, wparam, lparam),
     }
 }
-
-/// Resolve o caminho do dicionário
-fn resolve_dict_path() -> std::path::PathBuf {
-    let fallbacks = [
-        // Relativo ao executável
-        std::env::current_exe()
-            .ok()
-            .and_then(|p| {
-                p.parent()
-                    .map(|d| d.join("data").join("dictionary_pt_br.txt"))
-            })
-            .unwrap_or_default(),
-        // Caminho absoluto do projeto
-        std::path::PathBuf::from(
-            r"c:\System-wide AI-assisted Input Method for Portuguese (PT-BR IME)\data\dictionary_pt_br.txt",
-        ),
-    ];
-
-    for path in &fallbacks {
-        if path.exists() {
-            return path.clone();
-        }
-    }
-
-    // Último recurso
-    fallbacks[1].clone()
-}
-
-/// Resolve o caminho do arquivo de frequência
-fn resolve_freq_path() -> std::path::PathBuf {
-    let fallbacks = [
-        std::env::current_exe()
-            .ok()
-            .and_then(|p| {
-                p.parent()
-                    .map(|d| d.join("data").join("frequency_pt_br.txt"))
-            })
-            .unwrap_or_default(),
-        std::path::PathBuf::from(
-            r"c:\System-wide AI-assisted Input Method for Portuguese (PT-BR IME)\data\frequency_pt_br.txt",
-        ),
-    ];
-
-    for path in &fallbacks {
-        if path.exists() {
-            return path.clone();
-        }
-    }
-
-    fallbacks[1].clone()
-}